@@ -0,0 +1,6 @@
+-- DeskCraft extract action
+-- Migration 006: O que fazer com o arquivo compactado depois de extraído
+
+-- post_action: 'keep' (mantém), 'delete' (exclui) ou 'move' (move para post_destination)
+ALTER TABLE rule_actions ADD COLUMN post_action TEXT NOT NULL DEFAULT 'keep';
+ALTER TABLE rule_actions ADD COLUMN post_destination TEXT NOT NULL DEFAULT '';
@@ -0,0 +1,4 @@
+-- DeskCraft archive action
+-- Migration 007: Nome da entrada dentro do .zip para itens arquivados (usado no rollback)
+
+ALTER TABLE run_items ADD COLUMN archive_entry TEXT;
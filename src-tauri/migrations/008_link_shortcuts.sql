@@ -0,0 +1,7 @@
+-- DeskCraft link actions
+-- Migration 008: Vincula atalhos criados pelas ações link/move_and_link aos itens da execução
+
+ALTER TABLE shortcuts ADD COLUMN link_path TEXT NOT NULL DEFAULT '';
+ALTER TABLE shortcuts ADD COLUMN run_item_id TEXT REFERENCES run_items(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_shortcuts_run_item ON shortcuts(run_item_id);
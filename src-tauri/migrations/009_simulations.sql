@@ -0,0 +1,13 @@
+-- DeskCraft stored simulations
+-- Migration 009: Simulações ficam no backend; a execução recebe apenas o ID
+
+CREATE TABLE IF NOT EXISTS simulations (
+    id TEXT PRIMARY KEY NOT NULL,
+    profile_id TEXT NOT NULL,
+    source_folder TEXT NOT NULL DEFAULT '',
+    result_json TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_simulations_created_at ON simulations(created_at);
@@ -0,0 +1,7 @@
+-- DeskCraft move verification
+-- Migration 010: SHA-256 das cópias verificadas entre dispositivos e a opção que ativa a verificação
+
+ALTER TABLE run_items ADD COLUMN checksum TEXT;
+
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES
+    ('verify_moves', '0', datetime('now'));
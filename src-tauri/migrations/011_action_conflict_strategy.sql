@@ -0,0 +1,5 @@
+-- DeskCraft per-action conflict strategies
+-- Migration 011: Estratégia de conflito por ação (vazia = configuração global) e arquivo substituído por item
+
+ALTER TABLE rule_actions ADD COLUMN conflict_strategy TEXT NOT NULL DEFAULT '';
+ALTER TABLE run_items ADD COLUMN replaced_path TEXT;
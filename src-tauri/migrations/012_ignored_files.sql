@@ -0,0 +1,11 @@
+-- DeskCraft ignored files
+-- Migration 012: Arquivos excluídos para sempre das simulações de uma pasta
+
+CREATE TABLE IF NOT EXISTS ignored_files (
+    id TEXT PRIMARY KEY NOT NULL,
+    folder TEXT NOT NULL,
+    path TEXT NOT NULL UNIQUE,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_ignored_files_folder ON ignored_files(folder);
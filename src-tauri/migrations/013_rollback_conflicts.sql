@@ -0,0 +1,10 @@
+-- DeskCraft conflict-safe rollback
+-- Migration 013: Data de modificação do destino, o que o rollback fez com cada item e a estratégia de conflito do rollback
+
+ALTER TABLE run_items ADD COLUMN dest_mtime TEXT;
+ALTER TABLE run_items ADD COLUMN dest_modified INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE run_items ADD COLUMN rollback_resolution TEXT;
+ALTER TABLE run_items ADD COLUMN restored_path TEXT;
+
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES
+    ('rollback_conflict_strategy', 'suffix', datetime('now'));
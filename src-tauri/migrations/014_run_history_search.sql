@@ -0,0 +1,34 @@
+-- DeskCraft run history search
+-- Migration 014: Busca textual nos caminhos dos itens de execução e índices para filtrar o histórico
+
+CREATE VIRTUAL TABLE IF NOT EXISTS run_items_fts USING fts5(
+    original_path,
+    destination_path,
+    restored_path,
+    content='run_items',
+    content_rowid='rowid',
+    tokenize='trigram'
+);
+
+CREATE TRIGGER IF NOT EXISTS run_items_fts_insert AFTER INSERT ON run_items BEGIN
+    INSERT INTO run_items_fts (rowid, original_path, destination_path, restored_path)
+    VALUES (new.rowid, new.original_path, new.destination_path, new.restored_path);
+END;
+
+CREATE TRIGGER IF NOT EXISTS run_items_fts_delete AFTER DELETE ON run_items BEGIN
+    INSERT INTO run_items_fts (run_items_fts, rowid, original_path, destination_path, restored_path)
+    VALUES ('delete', old.rowid, old.original_path, old.destination_path, old.restored_path);
+END;
+
+CREATE TRIGGER IF NOT EXISTS run_items_fts_update
+AFTER UPDATE OF original_path, destination_path, restored_path ON run_items BEGIN
+    INSERT INTO run_items_fts (run_items_fts, rowid, original_path, destination_path, restored_path)
+    VALUES ('delete', old.rowid, old.original_path, old.destination_path, old.restored_path);
+    INSERT INTO run_items_fts (rowid, original_path, destination_path, restored_path)
+    VALUES (new.rowid, new.original_path, new.destination_path, new.restored_path);
+END;
+
+INSERT INTO run_items_fts (run_items_fts) VALUES ('rebuild');
+
+CREATE INDEX IF NOT EXISTS idx_runs_run_type ON runs(run_type);
+CREATE INDEX IF NOT EXISTS idx_run_items_executed_at ON run_items(executed_at);
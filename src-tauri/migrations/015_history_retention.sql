@@ -0,0 +1,10 @@
+-- DeskCraft history retention
+-- Migration 015: Marca execuções cujos itens foram removidos pela retenção e configura a política de retenção
+
+ALTER TABLE runs ADD COLUMN pruned_at TEXT;
+
+-- 0 mantém o histórico sem limite
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES
+    ('history_retention_days', '0', datetime('now')),
+    ('history_retention_runs', '0', datetime('now')),
+    ('history_keep_undoable_days', '30', datetime('now'));
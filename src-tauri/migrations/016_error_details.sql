@@ -0,0 +1,7 @@
+-- DeskCraft error details
+-- Migration 016: Código de erro categorizado e data da falha em cada item de execução
+
+ALTER TABLE run_items ADD COLUMN error_code TEXT;
+ALTER TABLE run_items ADD COLUMN failed_at TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_run_items_error_code ON run_items(error_code);
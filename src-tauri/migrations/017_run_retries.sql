@@ -0,0 +1,6 @@
+-- DeskCraft run retries
+-- Migration 017: Liga uma execução que repete os itens com erro à execução original
+
+ALTER TABLE runs ADD COLUMN parent_run_id TEXT REFERENCES runs(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_runs_parent_run_id ON runs(parent_run_id);
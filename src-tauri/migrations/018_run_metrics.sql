@@ -0,0 +1,15 @@
+-- DeskCraft run metrics
+-- Migration 018: Arquivos analisados, com e sem regra, bytes e duração de cada execução
+
+ALTER TABLE runs ADD COLUMN scanned_files INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE runs ADD COLUMN matched_files INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE runs ADD COLUMN unmatched_files INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE runs ADD COLUMN total_bytes INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE runs ADD COLUMN duration_ms INTEGER;
+
+-- Execuções anteriores: bytes pelos itens e duração pelas datas gravadas
+UPDATE runs SET total_bytes = (
+    SELECT COALESCE(SUM(file_size), 0) FROM run_items WHERE run_items.run_id = runs.id
+);
+UPDATE runs SET duration_ms = CAST((julianday(completed_at) - julianday(started_at)) * 86400000 AS INTEGER)
+WHERE completed_at IS NOT NULL;
@@ -1,7 +1,10 @@
-use tauri::State;
+use std::collections::HashMap;
+use tauri::{AppHandle, State};
 
-use crate::db::models::{Run, RunItem};
-use crate::db::queries::runs;
+use crate::commands::organizer_commands::save_report;
+use crate::db::models::{DatabaseSize, FileLocation, PruneResult, Run, RunFilter, RunItem, RunStats};
+use crate::db::queries::{rules, runs};
+use crate::organizer::{locator, report, retention};
 use crate::AppState;
 
 #[tauri::command]
@@ -15,6 +18,92 @@ pub fn list_runs(
         .map_err(|e| format!("Falha ao listar execuções: {}", e))
 }
 
+/// Lists runs by profile, type, status, start date or source folder, most
+/// recent first.
+#[tauri::command]
+pub fn search_runs(filter: RunFilter, state: State<AppState>) -> Result<Vec<Run>, String> {
+    let conn = state.db.lock().map_err(|e| format!("Erro de acesso ao banco de dados: {}", e))?;
+    runs::search_runs(&conn, &filter).map_err(|e| format!("Falha ao buscar execuções: {}", e))
+}
+
+/// Finds run items by any part of the paths a file had, to tell where it went.
+#[tauri::command]
+pub fn search_run_items(
+    query: String,
+    limit: Option<i32>,
+    state: State<AppState>,
+) -> Result<Vec<RunItem>, String> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+    let conn = state.db.lock().map_err(|e| format!("Erro de acesso ao banco de dados: {}", e))?;
+    runs::search_run_items(&conn, query, limit.unwrap_or(100))
+        .map_err(|e| format!("Falha ao buscar arquivos no histórico: {}", e))
+}
+
+/// Files and bytes organized per day, per rule and per destination folder by
+/// the runs `filter` picks, or by all runs.
+#[tauri::command]
+pub fn get_run_stats(
+    filter: Option<RunFilter>,
+    state: State<AppState>,
+) -> Result<RunStats, String> {
+    let conn = state.db.lock().map_err(|e| format!("Erro de acesso ao banco de dados: {}", e))?;
+    runs::run_stats(&conn, &filter.unwrap_or_default())
+        .map_err(|e| format!("Falha ao calcular estatísticas: {}", e))
+}
+
+/// Applies the history retention settings now instead of waiting for the
+/// scheduler's daily pass.
+#[tauri::command]
+pub fn prune_history(state: State<AppState>) -> Result<PruneResult, String> {
+    let conn = state.db.lock().map_err(|e| format!("Erro de acesso ao banco de dados: {}", e))?;
+    let policy = retention::RetentionPolicy::from_settings(&conn)
+        .map_err(|e| format!("Falha ao ler a política de retenção: {}", e))?;
+    retention::prune_history(&conn, &policy)
+        .map_err(|e| format!("Falha ao limpar o histórico: {}", e))
+}
+
+/// Size of the database file and how much history it holds.
+#[tauri::command]
+pub fn get_database_size(state: State<AppState>) -> Result<DatabaseSize, String> {
+    let conn = state.db.lock().map_err(|e| format!("Erro de acesso ao banco de dados: {}", e))?;
+    retention::database_size(&conn)
+        .map_err(|e| format!("Falha ao calcular o tamanho do banco de dados: {}", e))
+}
+
+/// Compacts the database file after pruning. Returns the new size.
+#[tauri::command]
+pub fn vacuum_database(state: State<AppState>) -> Result<DatabaseSize, String> {
+    let conn = state.db.lock().map_err(|e| format!("Erro de acesso ao banco de dados: {}", e))?;
+    retention::vacuum(&conn).map_err(|e| format!("Falha ao compactar o banco de dados: {}", e))
+}
+
+/// Tells where the files whose original path contains `query` are now,
+/// following later runs that moved them again and checking the disk.
+#[tauri::command]
+pub fn locate_file(
+    query: String,
+    limit: Option<usize>,
+    state: State<AppState>,
+) -> Result<Vec<FileLocation>, String> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+    let conn = state.db.lock().map_err(|e| format!("Erro de acesso ao banco de dados: {}", e))?;
+    locator::locate(&conn, query, limit.unwrap_or(20))
+        .map_err(|e| format!("Falha ao localizar arquivo: {}", e))
+}
+
+/// Opens the folder holding `path` in the system file manager.
+#[tauri::command]
+pub fn open_containing_folder(path: String) -> Result<(), String> {
+    locator::open_containing_folder(std::path::Path::new(&path))
+        .map_err(|e| format!("Falha ao abrir pasta: {}", e))
+}
+
 #[tauri::command]
 pub fn get_run(id: String, state: State<AppState>) -> Result<Option<Run>, String> {
     let conn = state.db.lock().map_err(|e| format!("Erro de acesso ao banco de dados: {}", e))?;
@@ -27,3 +116,34 @@ pub fn list_run_items(run_id: String, state: State<AppState>) -> Result<Vec<RunI
     runs::get_run_items(&conn, &run_id)
         .map_err(|e| format!("Falha ao obter itens da execução: {}", e))
 }
+
+/// Exports a run's items as a `csv`, `json`, `markdown` or `html` report,
+/// asking where to save it. Returns the saved path, or `None` if cancelled.
+#[tauri::command]
+pub async fn export_run(
+    run_id: String,
+    format: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Option<String>, String> {
+    let contents = {
+        let conn = state.db.lock().map_err(|e| format!("Erro de acesso ao banco de dados: {}", e))?;
+        let run = runs::get_run(&conn, &run_id)
+            .map_err(|e| format!("Falha ao obter execução: {}", e))?
+            .ok_or_else(|| "Execução não encontrada".to_string())?;
+        let items = runs::get_run_items(&conn, &run_id)
+            .map_err(|e| format!("Falha ao obter itens da execução: {}", e))?;
+        let rule_names: HashMap<String, String> = rules::list_rules(&conn)
+            .map_err(|e| format!("Falha ao listar regras: {}", e))?
+            .into_iter()
+            .map(|r| (r.id, r.name))
+            .collect();
+
+        let title = format!("Execução de {}: {}", run.started_at, run.source_folder);
+        report::render(&report::from_run(&title, &items, &rule_names), &format)
+            .map_err(|e| format!("Falha ao gerar relatório: {}", e))?
+    };
+
+    let name = format!("execucao-{}", run_id.chars().take(8).collect::<String>());
+    save_report(&app, &name, &format, &contents)
+}
@@ -1,9 +1,17 @@
 use std::collections::HashMap;
-use tauri::State;
+use std::sync::atomic::Ordering;
+use rusqlite::Connection;
+use tauri::{AppHandle, Emitter, Manager, State};
 
-use crate::db::models::{ExecutionResult, FileEntry, SimulationResult};
-use crate::db::queries::{profiles, rules, runs, settings};
-use crate::organizer::{executor, rollback, scanner, simulator};
+use crate::db::connection;
+use crate::db::models::{
+    ExecutionResult, FileEntry, IgnoredFile, ItemSelection, OrganizerProgress, PreflightReport,
+    RollbackDependencies, RollbackPreview, RollbackResult, SimulationResult,
+};
+use crate::db::queries::{ignored_files, profiles, rules, runs, settings, simulations};
+use crate::organizer::progress::{RunControl, PROGRESS_EVENT};
+use crate::organizer::rollback::{self, ChainMode, RollbackScope};
+use crate::organizer::{executor, preflight, recovery, report, retry, scanner, simulator};
 use crate::AppState;
 
 /// Scans a folder and returns a list of file entries with metadata.
@@ -56,70 +64,380 @@ pub fn simulate_folder(
         actions_map.insert(rule.id.clone(), acts);
     }
 
-    // Scan the folder
-    let files = scanner::scan_folder(&path, false)
+    // Scan the folder, leaving out files the user excluded for good
+    let ignored = ignored_files::ignored_paths(&conn)
+        .map_err(|e| format!("Falha ao obter arquivos ignorados: {}", e))?;
+    let mut files = scanner::scan_folder(&path, false)
         .map_err(|e| format!("Falha ao escanear pasta: {}", e))?;
+    files.retain(|f| !ignored.contains(&f.path));
+
+    let conflict_strategy = settings::get_setting(&conn, "conflict_strategy")
+        .map_err(|e| format!("Falha ao obter estratégia de conflito: {}", e))?
+        .unwrap_or_else(|| "suffix".to_string());
+
+    // Simulate and keep the plan server-side; execution refers to it by ID
+    let mut result = simulator::simulate(
+        &files,
+        &profile_rules,
+        &conditions_map,
+        &actions_map,
+        &conflict_strategy,
+    );
+    let simulation_id = simulations::save_simulation(&conn, &pid, &path, &result)
+        .map_err(|e| format!("Falha ao salvar simulação: {}", e))?;
+    result.simulation_id = Some(simulation_id);
 
-    // Simulate
-    let result = simulator::simulate(&files, &profile_rules, &conditions_map, &actions_map);
     Ok(result)
 }
 
-/// Executes a simulation result, actually moving files.
-/// Expects a JSON-serialized SimulationResult.
+/// Checks free space, permissions and locked files for a stored simulation
+/// without touching anything. Execution refuses to start while there are
+/// blockers. `selection` limits the check to the items that will run.
 #[tauri::command]
-pub fn execute_simulation(
-    simulation_json: String,
+pub fn preflight_simulation(
+    simulation_id: String,
+    selection: Option<ItemSelection>,
     state: State<AppState>,
+) -> Result<PreflightReport, String> {
+    let conn = state.db.lock().map_err(|e| format!("Erro de acesso ao banco de dados: {}", e))?;
+
+    let stored = simulations::get_simulation(&conn, &simulation_id)
+        .map_err(|e| format!("Falha ao obter simulação: {}", e))?
+        .ok_or_else(|| "Simulação não encontrada ou expirada. Simule novamente.".to_string())?;
+
+    let plan = simulator::select_items(&stored.result, &selection.unwrap_or_default());
+    Ok(preflight::preflight(&plan))
+}
+
+/// Executes a stored simulation, actually moving files. Files that changed
+/// since the simulation are skipped and reported in `drifted`.
+/// `selection` picks the items to run by index or path; all of them when absent.
+/// Nothing runs if the preflight check finds blockers.
+///
+/// Runs in the background (see [`run_in_background`]) so progress events
+/// reach the UI and `cancel_execution` can be handled while files are moved.
+#[tauri::command]
+pub async fn execute_simulation(
+    simulation_id: String,
+    selection: Option<ItemSelection>,
+    app: AppHandle,
 ) -> Result<ExecutionResult, String> {
+    run_in_background(app, move |conn, control| {
+        let mut stored = simulations::get_simulation(conn, &simulation_id)
+            .map_err(|e| format!("Falha ao obter simulação: {}", e))?
+            .ok_or_else(|| {
+                "Simulação não encontrada ou expirada. Simule novamente.".to_string()
+            })?;
+        stored.result = simulator::select_items(&stored.result, &selection.unwrap_or_default());
+
+        let (conflict_strategy, verify) = execution_settings(conn)?;
+
+        let report = preflight::preflight(&stored.result);
+        if !report.go {
+            return Err(format!(
+                "A organização não pode ser executada: {}",
+                preflight::describe_blockers(&report)
+            ));
+        }
+
+        // Create a run record
+        let run = runs::create_run(conn, &stored.profile_id, "manual", &stored.source_folder)
+            .map_err(|e| format!("Falha ao criar execução: {}", e))?;
+        runs::set_run_plan(conn, &run.id, &stored.result)
+            .map_err(|e| format!("Falha ao registrar execução: {}", e))?;
+
+        // A plan is executed at most once
+        simulations::delete_simulation(conn, &stored.id)
+            .map_err(|e| format!("Falha ao descartar simulação: {}", e))?;
+
+        executor::execute(conn, &stored.result, &run.id, &conflict_strategy, verify, control)
+            .map_err(|e| {
+                // Kept on the run, which recovery marks `interrupted`
+                runs::set_run_error(conn, &run.id, Some(&format!("{:#}", e))).ok();
+                format!("Falha na execução: {}", e)
+            })
+    })
+    .await
+}
+
+/// Excludes a file from every future simulation of `folder`.
+#[tauri::command]
+pub fn ignore_file(
+    path: String,
+    folder: String,
+    state: State<AppState>,
+) -> Result<IgnoredFile, String> {
     let conn = state.db.lock().map_err(|e| format!("Erro de acesso ao banco de dados: {}", e))?;
+    ignored_files::add_ignored_file(&conn, &folder, &path)
+        .map_err(|e| format!("Falha ao ignorar arquivo: {}", e))
+}
 
-    let simulation: SimulationResult = serde_json::from_str(&simulation_json)
-        .map_err(|e| format!("JSON de simulação inválido: {}", e))?;
+/// Lists ignored files, optionally only those of one folder.
+#[tauri::command]
+pub fn list_ignored_files(
+    folder: Option<String>,
+    state: State<AppState>,
+) -> Result<Vec<IgnoredFile>, String> {
+    let conn = state.db.lock().map_err(|e| format!("Erro de acesso ao banco de dados: {}", e))?;
+    ignored_files::list_ignored_files(&conn, folder.as_deref())
+        .map_err(|e| format!("Falha ao listar arquivos ignorados: {}", e))
+}
 
-    // Get the active profile
-    let active_profile = profiles::get_active_profile(&conn)
-        .map_err(|e| format!("Falha ao obter perfil ativo: {}", e))?
-        .ok_or_else(|| "Nenhum perfil ativo encontrado".to_string())?;
+/// Lets simulations pick up an ignored file again.
+#[tauri::command]
+pub fn unignore_file(id: String, state: State<AppState>) -> Result<(), String> {
+    let conn = state.db.lock().map_err(|e| format!("Erro de acesso ao banco de dados: {}", e))?;
+    ignored_files::delete_ignored_file(&conn, &id)
+        .map_err(|e| format!("Falha ao remover arquivo ignorado: {}", e))
+}
 
-    // Get conflict strategy from settings
-    let conflict_strategy = settings::get_setting(&conn, "conflict_strategy")
+/// Asks the running execution to stop after the file it is currently moving.
+/// The run ends as `cancelled` and can be rolled back like any other.
+#[tauri::command]
+pub fn cancel_execution(state: State<AppState>) -> Result<(), String> {
+    state.cancel_requested.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Rolls back a previous run, moving files back to their original locations.
+/// Files whose original path is taken again are handled with the
+/// `rollback_conflict_strategy` setting instead of being overwritten.
+///
+/// Files a later run moved again follow `chain_mode`: `"cascade"` (default)
+/// rolls back those later moves first, `"latest"` restores straight from
+/// where the files are now.
+#[tauri::command]
+pub async fn rollback_run(
+    run_id: String,
+    chain_mode: Option<String>,
+    app: AppHandle,
+) -> Result<RollbackResult, String> {
+    rollback_scope(run_id, None, None, chain_mode, app).await
+}
+
+/// Rolls back only the given items of a run. The run becomes
+/// `rollback_partial` while other items remain in place.
+#[tauri::command]
+pub async fn rollback_run_items(
+    run_id: String,
+    item_ids: Vec<String>,
+    chain_mode: Option<String>,
+    app: AppHandle,
+) -> Result<RollbackResult, String> {
+    if item_ids.is_empty() {
+        return Err("Nenhum item selecionado para reverter".to_string());
+    }
+    rollback_scope(run_id, Some(item_ids), None, chain_mode, app).await
+}
+
+/// Rolls back the items one rule produced in a run.
+#[tauri::command]
+pub async fn rollback_run_rule(
+    run_id: String,
+    rule_id: String,
+    chain_mode: Option<String>,
+    app: AppHandle,
+) -> Result<RollbackResult, String> {
+    rollback_scope(run_id, None, Some(rule_id), chain_mode, app).await
+}
+
+/// Lists the later runs that moved files of a run again, for review before
+/// rolling it back. `item_ids` or `rule_id` narrow it like the partial
+/// rollback commands.
+#[tauri::command]
+pub fn get_rollback_dependencies(
+    run_id: String,
+    item_ids: Option<Vec<String>>,
+    rule_id: Option<String>,
+    state: State<AppState>,
+) -> Result<RollbackDependencies, String> {
+    let conn = state.db.lock().map_err(|e| format!("Erro de acesso ao banco de dados: {}", e))?;
+    let scope = scope_of(&item_ids, &rule_id);
+    rollback::rollback_dependencies(&conn, &run_id, scope)
+        .map_err(|e| format!("Falha ao verificar dependências do rollback: {}", e))
+}
+
+/// Works out what rolling back a run would do to each file without moving
+/// anything: whether it is still at its destination, whether its original
+/// path is free, whether it changed and which later runs moved it.
+/// `item_ids` or `rule_id` narrow it like the partial rollback commands.
+#[tauri::command]
+pub fn simulate_rollback(
+    run_id: String,
+    item_ids: Option<Vec<String>>,
+    rule_id: Option<String>,
+    state: State<AppState>,
+) -> Result<RollbackPreview, String> {
+    let conn = state.db.lock().map_err(|e| format!("Erro de acesso ao banco de dados: {}", e))?;
+    let strategy = settings::get_setting(&conn, "rollback_conflict_strategy")
+        .map_err(|e| format!("Falha ao obter estratégia de conflito do rollback: {}", e))?
+        .unwrap_or_else(|| "suffix".to_string());
+    let scope = scope_of(&item_ids, &rule_id);
+    rollback::simulate_rollback(&conn, &run_id, scope, &strategy)
+        .map_err(|e| format!("Falha ao simular rollback: {}", e))
+}
+
+fn scope_of<'a>(
+    item_ids: &'a Option<Vec<String>>,
+    rule_id: &'a Option<String>,
+) -> RollbackScope<'a> {
+    match (item_ids, rule_id) {
+        (Some(ids), _) => RollbackScope::Items(ids),
+        (None, Some(rule_id)) => RollbackScope::Rule(rule_id),
+        (None, None) => RollbackScope::All,
+    }
+}
+
+async fn rollback_scope(
+    run_id: String,
+    item_ids: Option<Vec<String>>,
+    rule_id: Option<String>,
+    chain_mode: Option<String>,
+    app: AppHandle,
+) -> Result<RollbackResult, String> {
+    run_in_background(app, move |conn, control| {
+        let strategy = settings::get_setting(conn, "rollback_conflict_strategy")
+            .map_err(|e| format!("Falha ao obter estratégia de conflito do rollback: {}", e))?
+            .unwrap_or_else(|| "suffix".to_string());
+        let verify = settings::get_bool_setting(conn, "verify_moves", false)
+            .map_err(|e| format!("Falha ao obter configuração de verificação: {}", e))?;
+        let chain = ChainMode::parse(chain_mode.as_deref().unwrap_or("cascade"));
+        let scope = scope_of(&item_ids, &rule_id);
+        rollback::rollback_run(conn, &run_id, scope, &strategy, chain, verify, control)
+            .map_err(|e| format!("Falha ao reverter: {}", e))
+    })
+    .await
+}
+
+/// Resumes a run that was interrupted by a crash, executing the items that had
+/// not been started yet.
+#[tauri::command]
+pub async fn resume_run(
+    run_id: String,
+    app: AppHandle,
+) -> Result<ExecutionResult, String> {
+    run_in_background(app, move |conn, control| {
+        let (conflict_strategy, verify) = execution_settings(conn)?;
+        recovery::resume_run(conn, &run_id, &conflict_strategy, verify, control)
+            .map_err(|e| format!("Falha ao retomar execução: {}", e))
+    })
+    .await
+}
+
+/// Executes the failed items of a run again, with the current conflict
+/// settings, under a new run linked to it.
+#[tauri::command]
+pub async fn retry_run_errors(
+    run_id: String,
+    app: AppHandle,
+) -> Result<ExecutionResult, String> {
+    run_in_background(app, move |conn, control| {
+        let (conflict_strategy, verify) = execution_settings(conn)?;
+        retry::retry_run_errors(conn, &run_id, &conflict_strategy, verify, control)
+            .map_err(|e| format!("Falha ao repetir itens com erro: {}", e))
+    })
+    .await
+}
+
+/// Reads the conflict strategy and whether moves are verified.
+fn execution_settings(conn: &Connection) -> Result<(String, bool), String> {
+    let conflict_strategy = settings::get_setting(conn, "conflict_strategy")
         .map_err(|e| format!("Falha ao obter estratégia de conflito: {}", e))?
         .unwrap_or_else(|| "suffix".to_string());
+    let verify = settings::get_bool_setting(conn, "verify_moves", false)
+        .map_err(|e| format!("Falha ao obter configuração de verificação: {}", e))?;
+    Ok((conflict_strategy, verify))
+}
 
-    // Determine source folder from the first item
-    let source_folder = simulation
-        .items
-        .first()
-        .map(|item| {
-            std::path::Path::new(&item.file.path)
-                .parent()
-                .map(|p| p.to_string_lossy().to_string())
-                .unwrap_or_default()
-        })
-        .unwrap_or_default();
-
-    // Create a run record
-    let run = runs::create_run(&conn, &active_profile.id, "manual", &source_folder)
-        .map_err(|e| format!("Falha ao criar execução: {}", e))?;
-
-    // Execute
-    let result = executor::execute(&conn, &simulation, &run.id, &conflict_strategy)
-        .map_err(|e| format!("Falha na execução: {}", e))?;
+/// Runs a command that moves files on a blocking thread, with a database
+/// connection of its own. The shared connection stays free for every other
+/// command meanwhile; the run's journal writes go through its own connection.
+/// Runs still go one at a time, scheduled ones included, and a cancel requested
+/// before a run got its turn is dropped rather than stopping it.
+async fn run_in_background<T, F>(app: AppHandle, task: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&Connection, &RunControl) -> Result<T, String> + Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        let _running = state
+            .run_lock
+            .lock()
+            .map_err(|e| format!("Erro ao aguardar a execução em andamento: {}", e))?;
+        state.cancel_requested.store(false, Ordering::SeqCst);
+        let conn = connection::open(&state.app_data_dir)
+            .map_err(|e| format!("Erro de acesso ao banco de dados: {}", e))?;
+        let emit = emit_progress(&app);
+        let control = RunControl::new(&emit, &state.cancel_requested);
+        task(&conn, &control)
+    })
+    .await
+    .map_err(|e| format!("Falha ao executar em segundo plano: {}", e))?
+}
 
-    Ok(result)
+/// Builds the callback that forwards progress to the frontend.
+fn emit_progress(app: &AppHandle) -> impl Fn(&OrganizerProgress) + '_ {
+    move |progress| {
+        if let Err(e) = app.emit(PROGRESS_EVENT, progress.clone()) {
+            log::warn!("Failed to emit progress event: {}", e);
+        }
+    }
 }
 
-/// Rolls back a previous run, moving files back to their original locations.
+/// Exports a stored simulation as a `csv`, `json`, `markdown` or `html`
+/// report, asking where to save it. Returns the saved path, or `None` if the
+/// user cancelled the dialog.
 #[tauri::command]
-pub fn rollback_run(run_id: String, state: State<AppState>) -> Result<u32, String> {
-    let conn = state.db.lock().map_err(|e| format!("Erro de acesso ao banco de dados: {}", e))?;
-    rollback::rollback_run(&conn, &run_id).map_err(|e| format!("Falha ao reverter: {}", e))
+pub async fn export_simulation(
+    simulation_id: String,
+    format: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Option<String>, String> {
+    let contents = {
+        let conn = state.db.lock().map_err(|e| format!("Erro de acesso ao banco de dados: {}", e))?;
+        let stored = simulations::get_simulation(&conn, &simulation_id)
+            .map_err(|e| format!("Falha ao obter simulação: {}", e))?
+            .ok_or_else(|| "Simulação não encontrada ou expirada. Simule novamente.".to_string())?;
+
+        let title = format!("Plano de organização: {}", stored.source_folder);
+        report::render(&report::from_simulation(&title, &stored.result), &format)
+            .map_err(|e| format!("Falha ao gerar relatório: {}", e))?
+    };
+
+    save_report(&app, "plano", &format, &contents)
+}
+
+/// Asks where to save a rendered report and writes it there.
+pub(crate) fn save_report(
+    app: &AppHandle,
+    name: &str,
+    format: &str,
+    contents: &str,
+) -> Result<Option<String>, String> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let extension = report::extension(format);
+    let path = app
+        .dialog()
+        .file()
+        .set_title("Exportar relatório")
+        .set_file_name(&format!("{}.{}", name, extension))
+        .add_filter("Relatório", &[extension])
+        .blocking_save_file();
+
+    let path = match path {
+        Some(p) => p.to_string(),
+        None => return Ok(None),
+    };
+    std::fs::write(&path, contents).map_err(|e| format!("Falha ao salvar relatório: {}", e))?;
+    Ok(Some(path))
 }
 
 /// Opens a native folder picker dialog and returns the selected path.
 #[tauri::command]
-pub async fn select_folder(app: tauri::AppHandle) -> Result<Option<String>, String> {
+pub async fn select_folder(app: AppHandle) -> Result<Option<String>, String> {
     use tauri_plugin_dialog::DialogExt;
     let folder = app
         .dialog()
@@ -1,7 +1,11 @@
+use std::collections::HashMap;
+use std::path::Path;
 use tauri::State;
 
-use crate::db::models::{Rule, RuleAction, RuleCondition};
-use crate::db::queries::rules;
+use crate::db::models::{FileEntry, Rule, RuleAction, RuleCondition, RuleTestResult};
+use crate::db::queries::{rules, settings};
+use crate::organizer::{scanner, simulator};
+use crate::rules::conditions::collect_captures;
 use crate::AppState;
 
 // ── Rule CRUD ─────────────────────────────────────────────────────────────
@@ -95,12 +99,16 @@ pub fn get_rule_actions(
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn add_rule_action(
     rule_id: String,
     action_type: String,
     destination: String,
     rename_pattern: String,
     tag_name: String,
+    post_action: Option<String>,
+    post_destination: Option<String>,
+    conflict_strategy: Option<String>,
     state: State<AppState>,
 ) -> Result<RuleAction, String> {
     let conn = state.db.lock().map_err(|e| format!("Erro de acesso ao banco de dados: {}", e))?;
@@ -111,6 +119,9 @@ pub fn add_rule_action(
         &destination,
         &rename_pattern,
         &tag_name,
+        post_action.as_deref().unwrap_or("keep"),
+        post_destination.as_deref().unwrap_or(""),
+        conflict_strategy.as_deref().unwrap_or(""),
     )
     .map_err(|e| format!("Falha ao adicionar ação: {}", e))
 }
@@ -121,3 +132,76 @@ pub fn delete_rule_action(id: String, state: State<AppState>) -> Result<(), Stri
     rules::delete_action(&conn, &id)
         .map_err(|e| format!("Falha ao excluir ação: {}", e))
 }
+
+// ── Rule Tester ───────────────────────────────────────────────────────────
+
+/// Tests a single rule against a file path, returning whether it matches, the
+/// regex captures available to templates and the expanded destinations.
+/// If the path does not exist on disk, a file with that name is simulated.
+#[tauri::command]
+pub fn test_rule(
+    rule_id: String,
+    file_path: String,
+    state: State<AppState>,
+) -> Result<RuleTestResult, String> {
+    let conn = state.db.lock().map_err(|e| format!("Erro de acesso ao banco de dados: {}", e))?;
+
+    let mut rule = rules::get_rule(&conn, &rule_id)
+        .map_err(|e| format!("Falha ao obter regra: {}", e))?
+        .ok_or_else(|| "Regra não encontrada".to_string())?;
+    // The tester evaluates the rule even while it is disabled
+    rule.is_enabled = true;
+
+    let conds = rules::get_conditions(&conn, &rule_id)
+        .map_err(|e| format!("Falha ao obter condições: {}", e))?;
+    let acts = rules::get_actions(&conn, &rule_id)
+        .map_err(|e| format!("Falha ao obter ações: {}", e))?;
+
+    let path = Path::new(&file_path);
+    let file = if path.is_file() {
+        scanner::file_entry_for_path(path)
+            .map_err(|e| format!("Falha ao ler arquivo: {}", e))?
+    } else {
+        let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        FileEntry {
+            path: file_path.clone(),
+            name: path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| file_path.clone()),
+            extension: path
+                .extension()
+                .map(|e| e.to_string_lossy().to_lowercase())
+                .unwrap_or_default(),
+            size: 0,
+            created_at: now.clone(),
+            modified_at: now,
+        }
+    };
+
+    let captures = collect_captures(&file, &conds);
+
+    let mut conditions_map = HashMap::new();
+    let mut actions_map = HashMap::new();
+    conditions_map.insert(rule.id.clone(), conds);
+    actions_map.insert(rule.id.clone(), acts);
+
+    let conflict_strategy = settings::get_setting(&conn, "conflict_strategy")
+        .map_err(|e| format!("Falha ao obter estratégia de conflito: {}", e))?
+        .unwrap_or_else(|| "suffix".to_string());
+
+    let simulation = simulator::simulate(
+        std::slice::from_ref(&file),
+        std::slice::from_ref(&rule),
+        &conditions_map,
+        &actions_map,
+        &conflict_strategy,
+    );
+
+    Ok(RuleTestResult {
+        file,
+        matched: simulation.matched_files > 0,
+        captures,
+        items: simulation.items,
+    })
+}
@@ -1,9 +1,16 @@
 use anyhow::{Context, Result};
 use rusqlite::Connection;
 use std::path::Path;
+use std::time::Duration;
 
 use super::migrations;
 
+/// How long a write waits for the other connection (the UI's or the
+/// scheduler's) to finish its own before failing with `SQLITE_BUSY`.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+
+const DB_FILE: &str = "deskcraft.db";
+
 /// Initializes the SQLite database at the given app data directory path.
 /// Creates the database file if it does not exist, enables WAL mode,
 /// and runs all pending migrations.
@@ -14,9 +21,20 @@ pub fn init(app_data_dir: &str) -> Result<Connection> {
     std::fs::create_dir_all(db_dir)
         .with_context(|| format!("Failed to create app data directory: {}", app_data_dir))?;
 
-    let db_path = db_dir.join("deskcraft.db");
-    log::info!("Opening database at: {}", db_path.display());
+    log::info!("Opening database at: {}", db_dir.join(DB_FILE).display());
+    let conn = open(app_data_dir)?;
 
+    // Run migrations
+    migrations::run_migrations(&conn).context("Failed to run database migrations")?;
+
+    log::info!("Database initialized successfully");
+    Ok(conn)
+}
+
+/// Opens another connection to a database [`init`] already set up, for work
+/// that should not hold the shared one, such as a run moving files.
+pub fn open(app_data_dir: &str) -> Result<Connection> {
+    let db_path = Path::new(app_data_dir).join(DB_FILE);
     let conn = Connection::open(&db_path)
         .with_context(|| format!("Failed to open database at: {}", db_path.display()))?;
 
@@ -28,10 +46,10 @@ pub fn init(app_data_dir: &str) -> Result<Connection> {
     conn.execute_batch("PRAGMA foreign_keys=ON;")
         .context("Failed to enable foreign keys")?;
 
-    // Run migrations
-    migrations::run_migrations(&conn).context("Failed to run database migrations")?;
+    // Run journal writes wait for concurrent writers instead of aborting
+    conn.busy_timeout(BUSY_TIMEOUT)
+        .context("Failed to set busy timeout")?;
 
-    log::info!("Database initialized successfully");
     Ok(conn)
 }
 
@@ -49,6 +67,10 @@ mod tests {
             .query_row("SELECT COUNT(*) FROM settings", [], |row| row.get(0))
             .unwrap();
         assert!(count > 0);
+        let timeout: i64 = conn
+            .query_row("PRAGMA busy_timeout", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(timeout, 10_000);
         let _ = std::fs::remove_dir_all(&temp_dir);
     }
 }
@@ -23,6 +23,58 @@ const MIGRATIONS: &[(&str, &str)] = &[
         "005_extra_rules",
         include_str!("../../migrations/005_extra_rules.sql"),
     ),
+    (
+        "006_extract_action",
+        include_str!("../../migrations/006_extract_action.sql"),
+    ),
+    (
+        "007_archive_entries",
+        include_str!("../../migrations/007_archive_entries.sql"),
+    ),
+    (
+        "008_link_shortcuts",
+        include_str!("../../migrations/008_link_shortcuts.sql"),
+    ),
+    (
+        "009_simulations",
+        include_str!("../../migrations/009_simulations.sql"),
+    ),
+    (
+        "010_run_item_checksums",
+        include_str!("../../migrations/010_run_item_checksums.sql"),
+    ),
+    (
+        "011_action_conflict_strategy",
+        include_str!("../../migrations/011_action_conflict_strategy.sql"),
+    ),
+    (
+        "012_ignored_files",
+        include_str!("../../migrations/012_ignored_files.sql"),
+    ),
+    (
+        "013_rollback_conflicts",
+        include_str!("../../migrations/013_rollback_conflicts.sql"),
+    ),
+    (
+        "014_run_history_search",
+        include_str!("../../migrations/014_run_history_search.sql"),
+    ),
+    (
+        "015_history_retention",
+        include_str!("../../migrations/015_history_retention.sql"),
+    ),
+    (
+        "016_error_details",
+        include_str!("../../migrations/016_error_details.sql"),
+    ),
+    (
+        "017_run_retries",
+        include_str!("../../migrations/017_run_retries.sql"),
+    ),
+    (
+        "018_run_metrics",
+        include_str!("../../migrations/018_run_metrics.sql"),
+    ),
 ];
 
 /// Runs all pending migrations against the provided database connection.
@@ -87,6 +139,6 @@ mod tests {
         let count: i64 = conn
             .query_row("SELECT COUNT(*) FROM _migrations", [], |row| row.get(0))
             .unwrap();
-        assert_eq!(count, 5);
+        assert_eq!(count, 18);
     }
 }
@@ -2,3 +2,5 @@ pub mod connection;
 pub mod migrations;
 pub mod models;
 pub mod queries;
+#[cfg(test)]
+pub mod test_support;
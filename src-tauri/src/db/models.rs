@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 // ── Profiles ──────────────────────────────────────────────────────────────
 
@@ -50,6 +51,13 @@ pub struct RuleAction {
     pub tag_name: String,
     pub sort_order: i32,
     pub created_at: String,
+    /// What to do with the source after an `extract` action: `keep`, `delete` or `move`.
+    pub post_action: String,
+    /// Destination folder template used when `post_action` is `move`.
+    pub post_destination: String,
+    /// Conflict strategy for this action; empty to use the global setting.
+    #[serde(default)]
+    pub conflict_strategy: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -91,6 +99,96 @@ pub struct Run {
     pub completed_at: Option<String>,
     pub rolled_back_at: Option<String>,
     pub error_message: Option<String>,
+    /// When retention removed the run's items. Pruned runs keep their summary
+    /// but can no longer be rolled back.
+    pub pruned_at: Option<String>,
+    /// For `retry` runs, the run whose failed items they retried.
+    pub parent_run_id: Option<String>,
+    /// Files the run looked at, and how many of them a rule matched.
+    pub scanned_files: i32,
+    pub matched_files: i32,
+    pub unmatched_files: i32,
+    /// Size of the files the run planned to act on.
+    pub total_bytes: i64,
+    /// Time spent executing, across resumes. Unknown for unfinished runs.
+    pub duration_ms: Option<i64>,
+}
+
+/// Where a file DeskCraft moved is now.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileLocation {
+    /// The item that first moved the file.
+    pub run_item_id: String,
+    /// The run that last moved it.
+    pub run_id: String,
+    pub original_path: String,
+    /// Every path the file had, oldest first.
+    pub path_history: Vec<String>,
+    pub current_path: Option<String>,
+    /// `found`, `renamed` (found by content under another name next to where
+    /// it was left), `restored` (rolled back), `deleted` or `missing`.
+    pub status: String,
+    /// The rule that last moved it.
+    pub rule_id: Option<String>,
+    pub rule_name: Option<String>,
+    pub moved_at: Option<String>,
+}
+
+/// Narrows the run history. Unset fields don't filter; `from` and `to` are
+/// inclusive `YYYY-MM-DD` bounds on the day a run started.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunFilter {
+    pub profile_id: Option<String>,
+    pub run_type: Option<String>,
+    pub status: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    /// Runs of this folder or of any folder inside it.
+    pub source_folder: Option<String>,
+    /// Retries of this run.
+    pub parent_run_id: Option<String>,
+    pub limit: Option<i32>,
+    pub offset: Option<i32>,
+}
+
+/// Files and bytes organized by the runs a [`RunFilter`] picks. Only items
+/// still in place count; failed, skipped and rolled back ones don't.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunStats {
+    pub files: u32,
+    pub bytes: u64,
+    /// Oldest day first.
+    pub by_day: Vec<StatBucket>,
+    /// Most bytes first. Items of deleted rules are grouped by rule ID.
+    pub by_rule: Vec<StatBucket>,
+    /// Destination folders, most bytes first.
+    pub by_destination: Vec<StatBucket>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatBucket {
+    pub name: String,
+    pub files: u32,
+    pub bytes: u64,
+}
+
+/// What a retention pass removed from the history.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PruneResult {
+    pub runs_pruned: u32,
+    pub items_removed: u32,
+}
+
+/// Size of the database file and of the history kept in it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseSize {
+    pub total_bytes: u64,
+    /// Space held by deleted rows, given back to the disk by `VACUUM`.
+    pub free_bytes: u64,
+    pub runs: u32,
+    pub pruned_runs: u32,
+    pub run_items: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -105,8 +203,28 @@ pub struct RunItem {
     pub status: String,
     pub conflict_strategy: String,
     pub error_message: Option<String>,
+    /// Category of the last failure: `permission_denied`, `not_found`,
+    /// `disk_full`, `cross_device`, `locked`, `path_too_long`,
+    /// `unsafe_destination` or `other`.
+    pub error_code: Option<String>,
+    /// When the item last failed, on execution or rollback.
+    pub failed_at: Option<String>,
     pub executed_at: Option<String>,
     pub rolled_back_at: Option<String>,
+    /// Entry name inside the destination `.zip` for `archive` items.
+    pub archive_entry: Option<String>,
+    pub checksum: Option<String>,
+    /// Where the file replaced by an `overwrite_*` strategy was moved aside.
+    pub replaced_path: Option<String>,
+    /// Modification time of the file the run left at the destination.
+    pub dest_mtime: Option<String>,
+    /// Set by rollback when the destination changed after the run.
+    pub dest_modified: bool,
+    /// What rollback did with the item: `restored`, `suffix`,
+    /// `restored_folder` or `skipped`.
+    pub rollback_resolution: Option<String>,
+    /// Where rollback put the file back, when not at `original_path`.
+    pub restored_path: Option<String>,
 }
 
 // ── Schedules ─────────────────────────────────────────────────────────────
@@ -154,6 +272,10 @@ pub struct Shortcut {
     pub hotkey: String,
     pub sort_order: i32,
     pub created_at: String,
+    /// Path of the link on disk for `link` shortcuts created by an action.
+    pub link_path: String,
+    /// Run item that created the link, used by rollback.
+    pub run_item_id: Option<String>,
 }
 
 // ── Help & Tour ───────────────────────────────────────────────────────────
@@ -214,6 +336,20 @@ pub struct SimulationResult {
     pub total_files: u32,
     pub matched_files: u32,
     pub unmatched_files: u32,
+    /// ID of the stored plan; execution takes this instead of the items.
+    #[serde(default)]
+    pub simulation_id: Option<String>,
+}
+
+/// A simulation kept server-side until it is executed. The items carry the
+/// size and mtime each file had when it was simulated.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredSimulation {
+    pub id: String,
+    pub profile_id: String,
+    pub source_folder: String,
+    pub result: SimulationResult,
+    pub created_at: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -225,6 +361,61 @@ pub struct SimulationItem {
     pub destination: String,
     #[serde(rename = "has_conflict")]
     pub conflict: bool,
+    /// Set when the destination could not be resolved safely; the item is
+    /// reported as an error instead of being executed.
+    #[serde(default)]
+    pub error: Option<String>,
+    /// For `extract`: what happens to the archive afterwards (`keep`, `delete`, `move`).
+    #[serde(default)]
+    pub post_action: String,
+    /// For `extract` with `post_action = "move"`: the resolved folder for the archive.
+    #[serde(default)]
+    pub post_destination: String,
+    /// The action's own conflict strategy; empty to use the global setting.
+    #[serde(default)]
+    pub conflict_strategy: String,
+    /// Where the file will end up once conflicts are resolved, taking the
+    /// items before it into account. Empty when it won't be placed anywhere.
+    #[serde(default)]
+    pub final_path: String,
+    /// Why the executor is expected to skip the item, if it is.
+    #[serde(default)]
+    pub skip_reason: Option<String>,
+    /// For an item of a resumed run: the journal entry it continues, which
+    /// the executor re-plans in place instead of journaling the item again.
+    #[serde(skip)]
+    pub journal_id: Option<String>,
+}
+
+/// Which items of a stored simulation to execute. Items are picked by their
+/// index in the plan or by source path; empty `include_*` lists mean all items.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ItemSelection {
+    #[serde(default)]
+    pub include_indices: Vec<usize>,
+    #[serde(default)]
+    pub include_paths: Vec<String>,
+    #[serde(default)]
+    pub exclude_indices: Vec<usize>,
+    #[serde(default)]
+    pub exclude_paths: Vec<String>,
+}
+
+/// A file left out of every future simulation of its folder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IgnoredFile {
+    pub id: String,
+    pub folder: String,
+    pub path: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleTestResult {
+    pub file: FileEntry,
+    pub matched: bool,
+    pub captures: HashMap<String, String>,
+    pub items: Vec<SimulationItem>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -235,4 +426,162 @@ pub struct ExecutionResult {
     pub skipped: u32,
     pub errors: u32,
     pub error_messages: Vec<String>,
+    /// Items skipped because the file changed since it was simulated.
+    #[serde(default)]
+    pub drifted: Vec<DriftedItem>,
+    /// True when the run was stopped by the user before every item ran.
+    #[serde(default)]
+    pub cancelled: bool,
+}
+
+/// Progress of an execution or rollback, emitted after each item.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrganizerProgress {
+    pub run_id: String,
+    /// `execute` or `rollback`.
+    pub phase: String,
+    pub done: u32,
+    pub total: u32,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    pub current_file: String,
+}
+
+/// Outcome of rolling back a run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollbackResult {
+    pub run_id: String,
+    pub rolled_back: u32,
+    pub skipped: u32,
+    pub errors: u32,
+    /// Items that could not simply go back where they came from.
+    pub items: Vec<RollbackItemReport>,
+    /// Later runs whose moves were rolled back first, latest first.
+    pub dependent_runs: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollbackItemReport {
+    pub run_item_id: String,
+    pub original_path: String,
+    /// `suffix`, `restored_folder`, `skipped` or `restored` (for a modified file).
+    pub resolution: String,
+    /// Where the file was put back, if anywhere.
+    pub restored_path: Option<String>,
+    /// The file at the destination changed after the run.
+    pub destination_modified: bool,
+}
+
+/// What rolling back a run would do, worked out without touching any file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollbackPreview {
+    pub run_id: String,
+    pub items: Vec<RollbackPreviewItem>,
+    pub restored: u32,
+    pub relocated: u32,
+    pub kept: u32,
+    pub missing: u32,
+    pub irreversible: u32,
+    pub modified: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollbackPreviewItem {
+    pub run_item_id: String,
+    pub action_type: String,
+    pub original_path: String,
+    /// Where the file is now: the run's destination, or wherever later runs
+    /// moved it.
+    pub current_path: String,
+    pub destination_exists: bool,
+    /// Nothing but the run's own link occupies the original path.
+    pub original_free: bool,
+    /// The file changed after the run (or after the last later run moving it).
+    pub content_changed: bool,
+    /// Later runs that moved the file again, oldest first.
+    pub later_runs: Vec<String>,
+    /// `restore`, `relocate`, `keep` (the original path is taken and the
+    /// strategy is `skip`, or an extracted file was edited since), `remove`
+    /// (extracted files and links), `missing` or `irreversible` (deleted
+    /// files).
+    pub outcome: String,
+    /// Where the file would go, for `restore` and `relocate`.
+    pub target_path: Option<String>,
+}
+
+/// Later runs that moved files of a run again, found before rolling it back.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollbackDependencies {
+    pub run_id: String,
+    /// Only the items whose file was moved again.
+    pub chains: Vec<FileChain>,
+    /// Runs whose items would be rolled back first, latest first.
+    pub dependent_runs: Vec<DependentRun>,
+}
+
+/// Where a run item's file went after the run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileChain {
+    pub run_item_id: String,
+    pub original_path: String,
+    pub destination_path: String,
+    /// The later moves, oldest first.
+    pub steps: Vec<ChainStep>,
+    /// Where the file is now, or `None` if a later run deleted it.
+    pub latest_path: Option<String>,
+    /// Whether the file can be moved straight back from `latest_path`.
+    pub direct: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainStep {
+    pub run_id: String,
+    pub run_item_id: String,
+    pub action_type: String,
+    pub from: String,
+    pub to: String,
+    pub executed_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependentRun {
+    pub run_id: String,
+    pub started_at: String,
+    pub source_folder: String,
+    /// How many of its items depend on the run being rolled back.
+    pub items: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftedItem {
+    pub path: String,
+    pub reason: String,
+}
+
+/// Go/no-go report on whether a simulation can be executed safely.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreflightReport {
+    /// True when there are no blockers.
+    pub go: bool,
+    pub blockers: Vec<PreflightIssue>,
+    pub warnings: Vec<PreflightIssue>,
+    pub volumes: Vec<VolumeUsage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreflightIssue {
+    /// `insufficient_space`, `destination_not_writable`, `source_not_readable`,
+    /// `source_not_deletable`, `file_locked` or `low_space`.
+    pub kind: String,
+    pub path: String,
+    pub message: String,
+}
+
+/// Bytes a run will write to one filesystem, against what is available there.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VolumeUsage {
+    /// An existing folder on the volume, used to identify it.
+    pub path: String,
+    pub required_bytes: u64,
+    pub available_bytes: u64,
 }
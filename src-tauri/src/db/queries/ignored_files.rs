@@ -0,0 +1,78 @@
+use anyhow::{Context, Result};
+use rusqlite::Connection;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use crate::db::models::IgnoredFile;
+
+/// Adds a file to the ignore list. Ignoring a file twice keeps the first entry.
+pub fn add_ignored_file(conn: &Connection, folder: &str, path: &str) -> Result<IgnoredFile> {
+    let id = Uuid::new_v4().to_string();
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+
+    conn.execute(
+        "INSERT OR IGNORE INTO ignored_files (id, folder, path, created_at)
+         VALUES (?1, ?2, ?3, ?4)",
+        rusqlite::params![id, folder, path, now],
+    )
+    .context("Failed to insert ignored file")?;
+
+    conn.query_row(
+        "SELECT id, folder, path, created_at FROM ignored_files WHERE path = ?1",
+        [path],
+        |row| {
+            Ok(IgnoredFile {
+                id: row.get(0)?,
+                folder: row.get(1)?,
+                path: row.get(2)?,
+                created_at: row.get(3)?,
+            })
+        },
+    )
+    .context("Failed to read ignored file")
+}
+
+/// Lists ignored files, optionally only those of one folder.
+pub fn list_ignored_files(conn: &Connection, folder: Option<&str>) -> Result<Vec<IgnoredFile>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, folder, path, created_at FROM ignored_files
+             WHERE ?1 IS NULL OR folder = ?1
+             ORDER BY folder ASC, path ASC",
+        )
+        .context("Failed to prepare list_ignored_files query")?;
+
+    let rows = stmt
+        .query_map([folder], |row| {
+            Ok(IgnoredFile {
+                id: row.get(0)?,
+                folder: row.get(1)?,
+                path: row.get(2)?,
+                created_at: row.get(3)?,
+            })
+        })
+        .context("Failed to execute list_ignored_files query")?;
+
+    let mut files = Vec::new();
+    for row in rows {
+        files.push(row.context("Failed to read ignored file row")?);
+    }
+    Ok(files)
+}
+
+/// Paths of every ignored file, for filtering scan results. Paths are
+/// absolute, so an entry only ever matches files of the folder it was made in.
+pub fn ignored_paths(conn: &Connection) -> Result<HashSet<String>> {
+    Ok(list_ignored_files(conn, None)?
+        .into_iter()
+        .map(|f| f.path)
+        .collect())
+}
+
+/// Removes a file from the ignore list.
+pub fn delete_ignored_file(conn: &Connection, id: &str) -> Result<()> {
+    conn.execute("DELETE FROM ignored_files WHERE id = ?1", [id])
+        .context("Failed to delete ignored file")?;
+
+    Ok(())
+}
@@ -1,9 +1,12 @@
 pub mod help;
+pub mod ignored_files;
 pub mod profiles;
 pub mod rules;
 pub mod runs;
 pub mod schedules;
 pub mod settings;
+pub mod shortcuts;
+pub mod simulations;
 pub mod tips;
 pub mod tour;
 pub mod watched_folders;
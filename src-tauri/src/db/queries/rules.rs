@@ -200,7 +200,8 @@ pub fn delete_condition(conn: &Connection, id: &str) -> Result<()> {
 pub fn get_actions(conn: &Connection, rule_id: &str) -> Result<Vec<RuleAction>> {
     let mut stmt = conn
         .prepare(
-            "SELECT id, rule_id, action_type, destination, rename_pattern, tag_name, sort_order, created_at
+            "SELECT id, rule_id, action_type, destination, rename_pattern, tag_name, sort_order, created_at,
+                    post_action, post_destination, conflict_strategy
              FROM rule_actions WHERE rule_id = ?1 ORDER BY sort_order ASC",
         )
         .context("Failed to prepare get_actions query")?;
@@ -216,6 +217,9 @@ pub fn get_actions(conn: &Connection, rule_id: &str) -> Result<Vec<RuleAction>>
                 tag_name: row.get(5)?,
                 sort_order: row.get(6)?,
                 created_at: row.get(7)?,
+                post_action: row.get(8)?,
+                post_destination: row.get(9)?,
+                conflict_strategy: row.get(10)?,
             })
         })
         .context("Failed to execute get_actions query")?;
@@ -228,6 +232,7 @@ pub fn get_actions(conn: &Connection, rule_id: &str) -> Result<Vec<RuleAction>>
 }
 
 /// Adds a new action to a rule.
+#[allow(clippy::too_many_arguments)]
 pub fn add_action(
     conn: &Connection,
     rule_id: &str,
@@ -235,6 +240,9 @@ pub fn add_action(
     destination: &str,
     rename_pattern: &str,
     tag_name: &str,
+    post_action: &str,
+    post_destination: &str,
+    conflict_strategy: &str,
 ) -> Result<RuleAction> {
     let id = Uuid::new_v4().to_string();
     let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
@@ -248,9 +256,10 @@ pub fn add_action(
         .unwrap_or(-1);
 
     conn.execute(
-        "INSERT INTO rule_actions (id, rule_id, action_type, destination, rename_pattern, tag_name, sort_order, created_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-        rusqlite::params![id, rule_id, action_type, destination, rename_pattern, tag_name, max_order + 1, now],
+        "INSERT INTO rule_actions (id, rule_id, action_type, destination, rename_pattern, tag_name, sort_order, created_at,
+         post_action, post_destination, conflict_strategy)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        rusqlite::params![id, rule_id, action_type, destination, rename_pattern, tag_name, max_order + 1, now, post_action, post_destination, conflict_strategy],
     )
     .context("Failed to insert rule action")?;
 
@@ -263,6 +272,9 @@ pub fn add_action(
         tag_name: tag_name.to_string(),
         sort_order: max_order + 1,
         created_at: now,
+        post_action: post_action.to_string(),
+        post_destination: post_destination.to_string(),
+        conflict_strategy: conflict_strategy.to_string(),
     })
 }
 
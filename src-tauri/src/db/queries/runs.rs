@@ -1,39 +1,41 @@
 use anyhow::{Context, Result};
-use rusqlite::Connection;
+use rusqlite::types::Value;
+use rusqlite::{params_from_iter, Connection};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 use uuid::Uuid;
 
-use crate::db::models::{Run, RunItem};
+use crate::db::models::{Run, RunFilter, RunItem, RunStats, SimulationResult, StatBucket};
 
 /// Lists runs with pagination, most recent first.
 pub fn list_runs(conn: &Connection, limit: i32, offset: i32) -> Result<Vec<Run>> {
+    search_runs(
+        conn,
+        &RunFilter {
+            limit: Some(limit),
+            offset: Some(offset),
+            ..Default::default()
+        },
+    )
+}
+
+/// Lists the runs `filter` picks, most recent first.
+pub fn search_runs(conn: &Connection, filter: &RunFilter) -> Result<Vec<Run>> {
+    let mut params = Vec::new();
+    let clause = filter_clause(filter, &mut params);
+    params.push(Value::from(i64::from(filter.limit.unwrap_or(50))));
+    params.push(Value::from(i64::from(filter.offset.unwrap_or(0))));
+
     let mut stmt = conn
-        .prepare(
-            "SELECT id, profile_id, run_type, status, source_folder,
-                    total_files, moved_files, skipped_files, error_files,
-                    started_at, completed_at, rolled_back_at, error_message
-             FROM runs ORDER BY started_at DESC LIMIT ?1 OFFSET ?2",
-        )
-        .context("Failed to prepare list_runs query")?;
+        .prepare(&format!(
+            "SELECT {} FROM runs r WHERE 1 = 1{} ORDER BY r.started_at DESC LIMIT ? OFFSET ?",
+            RUN_COLUMNS, clause
+        ))
+        .context("Failed to prepare search_runs query")?;
 
     let rows = stmt
-        .query_map(rusqlite::params![limit, offset], |row| {
-            Ok(Run {
-                id: row.get(0)?,
-                profile_id: row.get(1)?,
-                run_type: row.get(2)?,
-                status: row.get(3)?,
-                source_folder: row.get(4)?,
-                total_files: row.get(5)?,
-                moved_files: row.get(6)?,
-                skipped_files: row.get(7)?,
-                error_files: row.get(8)?,
-                started_at: row.get(9)?,
-                completed_at: row.get(10)?,
-                rolled_back_at: row.get(11)?,
-                error_message: row.get(12)?,
-            })
-        })
-        .context("Failed to execute list_runs query")?;
+        .query_map(params_from_iter(params), run_from_row)
+        .context("Failed to execute search_runs query")?;
 
     let mut runs = Vec::new();
     for row in rows {
@@ -45,28 +47,9 @@ pub fn list_runs(conn: &Connection, limit: i32, offset: i32) -> Result<Vec<Run>>
 /// Gets a single run by ID.
 pub fn get_run(conn: &Connection, id: &str) -> Result<Option<Run>> {
     let result = conn.query_row(
-        "SELECT id, profile_id, run_type, status, source_folder,
-                total_files, moved_files, skipped_files, error_files,
-                started_at, completed_at, rolled_back_at, error_message
-         FROM runs WHERE id = ?1",
+        &format!("SELECT {} FROM runs r WHERE r.id = ?1", RUN_COLUMNS),
         [id],
-        |row| {
-            Ok(Run {
-                id: row.get(0)?,
-                profile_id: row.get(1)?,
-                run_type: row.get(2)?,
-                status: row.get(3)?,
-                source_folder: row.get(4)?,
-                total_files: row.get(5)?,
-                moved_files: row.get(6)?,
-                skipped_files: row.get(7)?,
-                error_files: row.get(8)?,
-                started_at: row.get(9)?,
-                completed_at: row.get(10)?,
-                rolled_back_at: row.get(11)?,
-                error_message: row.get(12)?,
-            })
-        },
+        run_from_row,
     );
 
     match result {
@@ -76,6 +59,187 @@ pub fn get_run(conn: &Connection, id: &str) -> Result<Option<Run>> {
     }
 }
 
+const RUN_COLUMNS: &str = "r.id, r.profile_id, r.run_type, r.status, r.source_folder,
+    r.total_files, r.moved_files, r.skipped_files, r.error_files,
+    r.started_at, r.completed_at, r.rolled_back_at, r.error_message, r.pruned_at,
+    r.parent_run_id, r.scanned_files, r.matched_files, r.unmatched_files, r.total_bytes,
+    r.duration_ms";
+
+fn run_from_row(row: &rusqlite::Row) -> rusqlite::Result<Run> {
+    Ok(Run {
+        id: row.get(0)?,
+        profile_id: row.get(1)?,
+        run_type: row.get(2)?,
+        status: row.get(3)?,
+        source_folder: row.get(4)?,
+        total_files: row.get(5)?,
+        moved_files: row.get(6)?,
+        skipped_files: row.get(7)?,
+        error_files: row.get(8)?,
+        started_at: row.get(9)?,
+        completed_at: row.get(10)?,
+        rolled_back_at: row.get(11)?,
+        error_message: row.get(12)?,
+        pruned_at: row.get(13)?,
+        parent_run_id: row.get(14)?,
+        scanned_files: row.get(15)?,
+        matched_files: row.get(16)?,
+        unmatched_files: row.get(17)?,
+        total_bytes: row.get(18)?,
+        duration_ms: row.get(19)?,
+    })
+}
+
+/// SQL conditions on the runs table, aliased `r`, for a filter. Each starts
+/// with ` AND `; their parameters are appended to `params` in order.
+fn filter_clause(filter: &RunFilter, params: &mut Vec<Value>) -> String {
+    let mut clause = String::new();
+    let mut add = |condition: &str, values: &[&str]| {
+        clause.push_str(" AND ");
+        clause.push_str(condition);
+        params.extend(values.iter().map(|v| Value::from(v.to_string())));
+    };
+
+    if let Some(profile_id) = &filter.profile_id {
+        add("r.profile_id = ?", &[profile_id.as_str()]);
+    }
+    if let Some(run_type) = &filter.run_type {
+        add("r.run_type = ?", &[run_type.as_str()]);
+    }
+    if let Some(status) = &filter.status {
+        add("r.status = ?", &[status.as_str()]);
+    }
+    if let Some(parent_run_id) = &filter.parent_run_id {
+        add("r.parent_run_id = ?", &[parent_run_id.as_str()]);
+    }
+    if let Some(from) = &filter.from {
+        add("date(r.started_at) >= ?", &[from.as_str()]);
+    }
+    if let Some(to) = &filter.to {
+        add("date(r.started_at) <= ?", &[to.as_str()]);
+    }
+    if let Some(folder) = &filter.source_folder {
+        let folder = folder.trim_end_matches(['/', '\\']);
+        let (slash, backslash) = (format!("{}/", folder), format!("{}\\", folder));
+        add(
+            "(r.source_folder = ?
+              OR instr(r.source_folder, ?) = 1 OR instr(r.source_folder, ?) = 1)",
+            &[folder, &slash, &backslash],
+        );
+    }
+    clause
+}
+
+/// Finds run items whose original, destination or restored path contains
+/// `query`, ignoring case, most recent first.
+pub fn search_run_items(conn: &Connection, query: &str, limit: i32) -> Result<Vec<RunItem>> {
+    // The trigram index only matches three or more characters
+    let (condition, pattern) = if query.chars().count() >= 3 {
+        (
+            "rowid IN (SELECT rowid FROM run_items_fts WHERE run_items_fts MATCH ?1)",
+            format!("\"{}\"", query.replace('"', "\"\"")),
+        )
+    } else {
+        (
+            "(instr(lower(original_path), lower(?1)) > 0
+              OR instr(lower(destination_path), lower(?1)) > 0
+              OR instr(lower(COALESCE(restored_path, '')), lower(?1)) > 0)",
+            query.to_string(),
+        )
+    };
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM run_items WHERE {} ORDER BY executed_at DESC LIMIT ?2",
+            RUN_ITEM_COLUMNS, condition
+        ))
+        .context("Failed to prepare search_run_items query")?;
+
+    let rows = stmt
+        .query_map(rusqlite::params![pattern, limit], run_item_from_row)
+        .context("Failed to execute search_run_items query")?;
+
+    let mut items = Vec::new();
+    for row in rows {
+        items.push(row.context("Failed to read run item row")?);
+    }
+    Ok(items)
+}
+
+/// Adds up what the runs `filter` picks organized, per day, per rule and per
+/// destination folder. The filter's limit and offset are ignored.
+pub fn run_stats(conn: &Connection, filter: &RunFilter) -> Result<RunStats> {
+    let mut params = Vec::new();
+    let clause = filter_clause(filter, &mut params);
+    let from = "FROM run_items ri JOIN runs r ON r.id = ri.run_id
+                LEFT JOIN rules ru ON ru.id = ri.rule_id
+                WHERE ri.status = 'completed'";
+
+    let buckets = |sql: String| -> Result<Vec<StatBucket>> {
+        let mut stmt = conn.prepare(&sql).context("Failed to prepare run stats query")?;
+        let rows = stmt
+            .query_map(params_from_iter(params.iter()), |row| {
+                Ok(StatBucket {
+                    name: row.get(0)?,
+                    files: row.get(1)?,
+                    bytes: row.get::<_, i64>(2)?.max(0) as u64,
+                })
+            })
+            .context("Failed to execute run stats query")?;
+        let mut buckets = Vec::new();
+        for row in rows {
+            buckets.push(row.context("Failed to read run stats row")?);
+        }
+        Ok(buckets)
+    };
+
+    let by_day = buckets(format!(
+        "SELECT COALESCE(date(ri.executed_at), ''), COUNT(*), COALESCE(SUM(ri.file_size), 0)
+         {}{} GROUP BY 1 ORDER BY 1",
+        from, clause
+    ))?;
+    let by_rule = buckets(format!(
+        "SELECT COALESCE(ru.name, ri.rule_id, ''), COUNT(*), COALESCE(SUM(ri.file_size), 0)
+         {}{} GROUP BY ri.rule_id ORDER BY 3 DESC",
+        from, clause
+    ))?;
+
+    // Folders are worked out from the paths, which SQL can't split portably
+    let mut folders: HashMap<String, StatBucket> = HashMap::new();
+    let mut stmt = conn
+        .prepare(&format!("SELECT ri.destination_path, ri.file_size {}{}", from, clause))
+        .context("Failed to prepare run stats query")?;
+    let rows = stmt
+        .query_map(params_from_iter(params.iter()), |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })
+        .context("Failed to execute run stats query")?;
+    for row in rows {
+        let (destination, size) = row.context("Failed to read run stats row")?;
+        let folder = match Path::new(&destination).parent() {
+            Some(parent) if !destination.is_empty() => parent.to_string_lossy().to_string(),
+            _ => continue,
+        };
+        let bucket = folders.entry(folder.clone()).or_insert(StatBucket {
+            name: folder,
+            files: 0,
+            bytes: 0,
+        });
+        bucket.files += 1;
+        bucket.bytes += size.max(0) as u64;
+    }
+    let mut by_destination: Vec<StatBucket> = folders.into_values().collect();
+    by_destination.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+
+    Ok(RunStats {
+        files: by_day.iter().map(|b| b.files).sum(),
+        bytes: by_day.iter().map(|b| b.bytes).sum(),
+        by_day,
+        by_rule,
+        by_destination,
+    })
+}
+
 /// Creates a new run record.
 pub fn create_run(
     conn: &Connection,
@@ -97,6 +261,102 @@ pub fn create_run(
         .ok_or_else(|| anyhow::anyhow!("Run was inserted but could not be retrieved"))
 }
 
+/// Records what the plan a run executes covers: files scanned, matched and
+/// unmatched, the items to execute and the size of the files they act on.
+pub fn set_run_plan(conn: &Connection, id: &str, plan: &SimulationResult) -> Result<()> {
+    let mut files = HashSet::new();
+    let bytes: u64 = plan
+        .items
+        .iter()
+        .filter(|item| files.insert(item.file.path.as_str()))
+        .map(|item| item.file.size)
+        .sum();
+
+    conn.execute(
+        "UPDATE runs SET scanned_files = ?1, matched_files = ?2, unmatched_files = ?3,
+         total_files = ?4, total_bytes = ?5 WHERE id = ?6",
+        rusqlite::params![
+            plan.total_files,
+            plan.matched_files,
+            plan.unmatched_files,
+            plan.items.len() as i64,
+            bytes as i64,
+            id
+        ],
+    )
+    .context("Failed to record run plan")?;
+
+    Ok(())
+}
+
+/// Counts items journaled outside the plan in the run's total, such as the
+/// failed items a retry could not plan again.
+pub fn add_run_total_files(conn: &Connection, id: &str, count: i32) -> Result<()> {
+    conn.execute(
+        "UPDATE runs SET total_files = total_files + ?1 WHERE id = ?2",
+        rusqlite::params![count, id],
+    )
+    .context("Failed to update run total")?;
+
+    Ok(())
+}
+
+/// Closes a run whose plan had nothing to do, such as a scheduled run on a
+/// folder with no matching files. It stays in the history as an `idle`
+/// record with its scan counts; nothing was executed, so its duration is 0.
+pub fn finish_idle_run(conn: &Connection, id: &str) -> Result<()> {
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    conn.execute(
+        "UPDATE runs SET status = 'idle', completed_at = ?1, duration_ms = 0 WHERE id = ?2",
+        rusqlite::params![now, id],
+    )
+    .context("Failed to finish idle run")?;
+
+    Ok(())
+}
+
+/// Adds to the time a run spent executing.
+pub fn add_run_duration(conn: &Connection, id: &str, duration_ms: i64) -> Result<()> {
+    conn.execute(
+        "UPDATE runs SET duration_ms = COALESCE(duration_ms, 0) + ?1 WHERE id = ?2",
+        rusqlite::params![duration_ms, id],
+    )
+    .context("Failed to record run duration")?;
+
+    Ok(())
+}
+
+/// Links a run to the run whose failed items it retries.
+pub fn set_run_parent(conn: &Connection, id: &str, parent_run_id: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE runs SET parent_run_id = ?1 WHERE id = ?2",
+        rusqlite::params![parent_run_id, id],
+    )
+    .context("Failed to link run to its parent")?;
+
+    Ok(())
+}
+
+/// Original paths of the files that retries of a run have since organized.
+pub fn retried_paths(conn: &Connection, run_id: &str) -> Result<HashSet<String>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT ri.original_path FROM run_items ri JOIN runs r ON r.id = ri.run_id
+             WHERE r.parent_run_id = ?1 AND ri.status = 'completed'",
+        )
+        .context("Failed to prepare retried_paths query")?;
+
+    let rows = stmt
+        .query_map([run_id], |row| row.get(0))
+        .context("Failed to execute retried_paths query")?;
+
+    let mut paths = HashSet::new();
+    for row in rows {
+        paths.insert(row.context("Failed to read retried path")?);
+    }
+    Ok(paths)
+}
+
 /// Updates the status and counters of a run.
 pub fn update_run_status(
     conn: &Connection,
@@ -108,10 +368,9 @@ pub fn update_run_status(
 ) -> Result<()> {
     let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
 
-    let completed_at = if status == "completed" || status == "error" {
-        Some(now.clone())
-    } else {
-        None
+    let completed_at = match status {
+        "completed" | "completed_with_errors" | "error" | "cancelled" => Some(now.clone()),
+        _ => None,
     };
 
     let rolled_back_at = if status == "rolled_back" {
@@ -120,47 +379,63 @@ pub fn update_run_status(
         None
     };
 
-    let total = moved + skipped + errors;
-
+    // `total_files` is the planned count set by `set_run_plan`: a cancelled
+    // or failed run keeps it
     conn.execute(
-        "UPDATE runs SET status = ?1, total_files = ?2, moved_files = ?3, skipped_files = ?4,
-         error_files = ?5, completed_at = COALESCE(?6, completed_at),
-         rolled_back_at = COALESCE(?7, rolled_back_at)
-         WHERE id = ?8",
-        rusqlite::params![status, total, moved, skipped, errors, completed_at, rolled_back_at, id],
+        "UPDATE runs SET status = ?1, moved_files = ?2, skipped_files = ?3,
+         error_files = ?4, completed_at = COALESCE(?5, completed_at),
+         rolled_back_at = COALESCE(?6, rolled_back_at)
+         WHERE id = ?7",
+        rusqlite::params![status, moved, skipped, errors, completed_at, rolled_back_at, id],
     )
     .context("Failed to update run status")?;
 
     Ok(())
 }
 
+/// Sets a run's status after a rollback: `rolled_back` once every item it
+/// placed has been put back, `rollback_partial` while some are still at their
+/// destination. The execution's counters are left as they were and
+/// `rolled_back_at` records the latest rollback. Runs with nothing rolled back
+/// keep their status. Returns the status the run ends up with.
+pub fn mark_run_rolled_back(conn: &Connection, id: &str) -> Result<String> {
+    let (remaining, rolled_back): (i64, i64) = conn
+        .query_row(
+            "SELECT COALESCE(SUM(status IN ('completed', 'rollback_skipped')), 0),
+                    COALESCE(SUM(status = 'rolled_back'), 0)
+             FROM run_items WHERE run_id = ?1",
+            [id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .context("Failed to count rolled back items")?;
+
+    if rolled_back == 0 {
+        let run = get_run(conn, id)?.ok_or_else(|| anyhow::anyhow!("Run {} not found", id))?;
+        return Ok(run.status);
+    }
+
+    let status = if remaining == 0 { "rolled_back" } else { "rollback_partial" };
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    conn.execute(
+        "UPDATE runs SET status = ?1, rolled_back_at = ?2 WHERE id = ?3",
+        rusqlite::params![status, now, id],
+    )
+    .context("Failed to update run rollback status")?;
+
+    Ok(status.to_string())
+}
+
 /// Gets all items for a given run.
 pub fn get_run_items(conn: &Connection, run_id: &str) -> Result<Vec<RunItem>> {
     let mut stmt = conn
-        .prepare(
-            "SELECT id, run_id, rule_id, original_path, destination_path, file_size,
-                    action_type, status, conflict_strategy, error_message, executed_at, rolled_back_at
-             FROM run_items WHERE run_id = ?1 ORDER BY executed_at ASC",
-        )
+        .prepare(&format!(
+            "SELECT {} FROM run_items WHERE run_id = ?1 ORDER BY executed_at ASC",
+            RUN_ITEM_COLUMNS
+        ))
         .context("Failed to prepare get_run_items query")?;
 
     let rows = stmt
-        .query_map([run_id], |row| {
-            Ok(RunItem {
-                id: row.get(0)?,
-                run_id: row.get(1)?,
-                rule_id: row.get(2)?,
-                original_path: row.get(3)?,
-                destination_path: row.get(4)?,
-                file_size: row.get(5)?,
-                action_type: row.get(6)?,
-                status: row.get(7)?,
-                conflict_strategy: row.get(8)?,
-                error_message: row.get(9)?,
-                executed_at: row.get(10)?,
-                rolled_back_at: row.get(11)?,
-            })
-        })
+        .query_map([run_id], run_item_from_row)
         .context("Failed to execute get_run_items query")?;
 
     let mut items = Vec::new();
@@ -170,6 +445,80 @@ pub fn get_run_items(conn: &Connection, run_id: &str) -> Result<Vec<RunItem>> {
     Ok(items)
 }
 
+/// Finds the first completed item of another run that moved the file at
+/// `path` on, at or after `since`. Links and extractions leave their source in
+/// place, so they are not counted as moves.
+pub fn find_next_move(
+    conn: &Connection,
+    path: &str,
+    run_id: &str,
+    since: &str,
+) -> Result<Option<RunItem>> {
+    let result = conn.query_row(
+        &format!(
+            "SELECT {} FROM run_items
+             WHERE original_path = ?1 AND run_id != ?2 AND executed_at >= ?3
+               AND status = 'completed' AND action_type NOT IN ('link', 'extract')
+             ORDER BY executed_at ASC LIMIT 1",
+            RUN_ITEM_COLUMNS
+        ),
+        rusqlite::params![path, run_id, since],
+        run_item_from_row,
+    );
+
+    match result {
+        Ok(item) => Ok(Some(item)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(anyhow::anyhow!(e).context("Failed to find next move")),
+    }
+}
+
+/// Gets a single run item by ID.
+pub fn get_run_item(conn: &Connection, id: &str) -> Result<Option<RunItem>> {
+    let result = conn.query_row(
+        &format!("SELECT {} FROM run_items WHERE id = ?1", RUN_ITEM_COLUMNS),
+        [id],
+        run_item_from_row,
+    );
+
+    match result {
+        Ok(item) => Ok(Some(item)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(anyhow::anyhow!(e).context("Failed to get run item")),
+    }
+}
+
+const RUN_ITEM_COLUMNS: &str = "id, run_id, rule_id, original_path, destination_path, file_size,
+    action_type, status, conflict_strategy, error_message, executed_at, rolled_back_at,
+    archive_entry, checksum, replaced_path, dest_mtime, dest_modified,
+    rollback_resolution, restored_path, error_code, failed_at";
+
+fn run_item_from_row(row: &rusqlite::Row) -> rusqlite::Result<RunItem> {
+    Ok(RunItem {
+        id: row.get(0)?,
+        run_id: row.get(1)?,
+        rule_id: row.get(2)?,
+        original_path: row.get(3)?,
+        destination_path: row.get(4)?,
+        file_size: row.get(5)?,
+        action_type: row.get(6)?,
+        status: row.get(7)?,
+        conflict_strategy: row.get(8)?,
+        error_message: row.get(9)?,
+        executed_at: row.get(10)?,
+        rolled_back_at: row.get(11)?,
+        archive_entry: row.get(12)?,
+        checksum: row.get(13)?,
+        replaced_path: row.get(14)?,
+        dest_mtime: row.get(15)?,
+        dest_modified: row.get(16)?,
+        rollback_resolution: row.get(17)?,
+        restored_path: row.get(18)?,
+        error_code: row.get(19)?,
+        failed_at: row.get(20)?,
+    })
+}
+
 /// Creates a new run item record.
 pub fn create_run_item(
     conn: &Connection,
@@ -204,11 +553,280 @@ pub fn create_run_item(
         status: status.to_string(),
         conflict_strategy: conflict_strategy.to_string(),
         error_message: None,
+        error_code: None,
+        failed_at: None,
         executed_at: Some(now),
         rolled_back_at: None,
+        archive_entry: None,
+        checksum: None,
+        replaced_path: None,
+        dest_mtime: None,
+        dest_modified: false,
+        rollback_resolution: None,
+        restored_path: None,
     })
 }
 
+/// Completes a journaled run item: sets its final status, the destination it
+/// actually ended up at and, for skipped items, why. Failures go through
+/// [`fail_run_item`].
+pub fn finish_run_item(
+    conn: &Connection,
+    id: &str,
+    status: &str,
+    destination_path: &str,
+    error_message: Option<&str>,
+) -> Result<()> {
+    conn.execute(
+        "UPDATE run_items SET status = ?1, destination_path = ?2, error_message = ?3 WHERE id = ?4",
+        rusqlite::params![status, destination_path, error_message, id],
+    )
+    .context("Failed to finish run item")?;
+
+    Ok(())
+}
+
+/// Plans a `pending` run item again before it is executed anew, as when an
+/// interrupted run is resumed: its destination and strategy are replaced and
+/// anything recorded about an earlier attempt is cleared.
+pub fn replan_run_item(
+    conn: &Connection,
+    id: &str,
+    destination_path: &str,
+    conflict_strategy: &str,
+) -> Result<()> {
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+
+    conn.execute(
+        "UPDATE run_items SET status = 'pending', destination_path = ?1, conflict_strategy = ?2,
+         executed_at = ?3, error_message = NULL, error_code = NULL, failed_at = NULL
+         WHERE id = ?4",
+        rusqlite::params![destination_path, conflict_strategy, now, id],
+    )
+    .context("Failed to re-plan run item")?;
+
+    Ok(())
+}
+
+/// Completes a journaled run item as `error`, recording why it failed, the
+/// category of the failure and when it happened.
+pub fn fail_run_item(
+    conn: &Connection,
+    id: &str,
+    destination_path: &str,
+    error_code: &str,
+    error_message: &str,
+) -> Result<()> {
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+
+    conn.execute(
+        "UPDATE run_items SET status = 'error', destination_path = ?1, error_message = ?2,
+         error_code = ?3, failed_at = ?4 WHERE id = ?5",
+        rusqlite::params![destination_path, error_message, error_code, now, id],
+    )
+    .context("Failed to record run item failure")?;
+
+    Ok(())
+}
+
+/// Records a failed rollback attempt on a run item. Its status is left as it
+/// was, so the rollback can be retried.
+pub fn set_run_item_error(
+    conn: &Connection,
+    id: &str,
+    error_code: &str,
+    error_message: &str,
+) -> Result<()> {
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+
+    conn.execute(
+        "UPDATE run_items SET error_message = ?1, error_code = ?2, failed_at = ?3 WHERE id = ?4",
+        rusqlite::params![error_message, error_code, now, id],
+    )
+    .context("Failed to record run item error")?;
+
+    Ok(())
+}
+
+/// Lists the IDs of runs in the given status, oldest first.
+pub fn list_run_ids_by_status(conn: &Connection, status: &str) -> Result<Vec<String>> {
+    let mut stmt = conn
+        .prepare("SELECT id FROM runs WHERE status = ?1 ORDER BY started_at ASC")
+        .context("Failed to prepare list_run_ids_by_status query")?;
+
+    let rows = stmt
+        .query_map([status], |row| row.get(0))
+        .context("Failed to execute list_run_ids_by_status query")?;
+
+    let mut ids = Vec::new();
+    for row in rows {
+        ids.push(row.context("Failed to read run id")?);
+    }
+    Ok(ids)
+}
+
+/// Lists `(id, status, started_at, undoable)` of runs that still have their
+/// items, newest first. A run is undoable while it has completed items a
+/// rollback could restore. Runs in progress are left out.
+pub fn list_prunable_runs(conn: &Connection) -> Result<Vec<(String, String, String, bool)>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT r.id, r.status, r.started_at,
+                    EXISTS (SELECT 1 FROM run_items i
+                            WHERE i.run_id = r.id AND i.status = 'completed')
+             FROM runs r
+             WHERE r.pruned_at IS NULL AND r.status != 'running'
+             ORDER BY r.started_at DESC",
+        )
+        .context("Failed to prepare list_prunable_runs query")?;
+
+    let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+        .context("Failed to execute list_prunable_runs query")?;
+
+    let mut runs = Vec::new();
+    for row in rows {
+        runs.push(row.context("Failed to read run row")?);
+    }
+    Ok(runs)
+}
+
+/// Deletes a run's items and marks it pruned, in one transaction. The run
+/// itself stays in the history with its counters. The records of links its
+/// items created go too: the links stay on disk, but nothing can remove them
+/// anymore. Returns how many items were removed.
+pub fn prune_run(conn: &Connection, id: &str) -> Result<u32> {
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let tx = conn
+        .unchecked_transaction()
+        .context("Failed to start prune transaction")?;
+    tx.execute(
+        "DELETE FROM shortcuts WHERE shortcut_type = 'link'
+         AND run_item_id IN (SELECT id FROM run_items WHERE run_id = ?1)",
+        [id],
+    )
+    .context("Failed to delete pruned link shortcuts")?;
+    let removed = tx
+        .execute("DELETE FROM run_items WHERE run_id = ?1", [id])
+        .context("Failed to delete pruned run items")?;
+    tx.execute(
+        "UPDATE runs SET pruned_at = ?1 WHERE id = ?2",
+        rusqlite::params![now, id],
+    )
+    .context("Failed to mark run as pruned")?;
+    tx.commit().context("Failed to commit prune transaction")?;
+
+    Ok(removed as u32)
+}
+
+/// Counts a run's failed items by error code, most frequent first. Items
+/// failed before codes were recorded count as `other`.
+pub fn count_run_errors(conn: &Connection, run_id: &str) -> Result<Vec<(String, u32)>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT COALESCE(error_code, 'other'), COUNT(*) FROM run_items
+             WHERE run_id = ?1 AND status = 'error'
+             GROUP BY 1 ORDER BY 2 DESC, 1",
+        )
+        .context("Failed to prepare count_run_errors query")?;
+
+    let rows = stmt
+        .query_map([run_id], |row| Ok((row.get(0)?, row.get(1)?)))
+        .context("Failed to execute count_run_errors query")?;
+
+    let mut counts = Vec::new();
+    for row in rows {
+        counts.push(row.context("Failed to read error count")?);
+    }
+    Ok(counts)
+}
+
+/// Sets or clears a run's error message.
+pub fn set_run_error(conn: &Connection, id: &str, error_message: Option<&str>) -> Result<()> {
+    conn.execute(
+        "UPDATE runs SET error_message = ?1 WHERE id = ?2",
+        rusqlite::params![error_message, id],
+    )
+    .context("Failed to set run error")?;
+
+    Ok(())
+}
+
+/// Counts a run's items by final status: `(completed, skipped, error)`.
+pub fn count_run_items(conn: &Connection, run_id: &str) -> Result<(i32, i32, i32)> {
+    conn.query_row(
+        "SELECT COALESCE(SUM(status = 'completed'), 0),
+                COALESCE(SUM(status = 'skipped'), 0),
+                COALESCE(SUM(status = 'error'), 0)
+         FROM run_items WHERE run_id = ?1",
+        [run_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )
+    .context("Failed to count run items")
+}
+
+/// Records the entry name an `archive` item was stored under inside its `.zip`.
+pub fn set_run_item_archive_entry(conn: &Connection, id: &str, entry: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE run_items SET archive_entry = ?1 WHERE id = ?2",
+        rusqlite::params![entry, id],
+    )
+    .context("Failed to update run item archive entry")?;
+
+    Ok(())
+}
+
+/// Stores the SHA-256 of a verified copy on a run item.
+pub fn set_run_item_checksum(conn: &Connection, id: &str, checksum: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE run_items SET checksum = ?1 WHERE id = ?2",
+        rusqlite::params![checksum, id],
+    )
+    .context("Failed to update run item checksum")?;
+
+    Ok(())
+}
+
+/// Records where the file replaced by a run item was moved aside.
+pub fn set_run_item_replaced_path(conn: &Connection, id: &str, replaced_path: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE run_items SET replaced_path = ?1 WHERE id = ?2",
+        rusqlite::params![replaced_path, id],
+    )
+    .context("Failed to update run item replaced path")?;
+
+    Ok(())
+}
+
+/// Records the modification time of the file a run item left at its destination.
+pub fn set_run_item_dest_mtime(conn: &Connection, id: &str, dest_mtime: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE run_items SET dest_mtime = ?1 WHERE id = ?2",
+        rusqlite::params![dest_mtime, id],
+    )
+    .context("Failed to update run item destination mtime")?;
+
+    Ok(())
+}
+
+/// Records what rollback did with a run item.
+pub fn set_run_item_rollback(
+    conn: &Connection,
+    id: &str,
+    resolution: &str,
+    restored_path: Option<&str>,
+    dest_modified: bool,
+) -> Result<()> {
+    conn.execute(
+        "UPDATE run_items SET rollback_resolution = ?1, restored_path = ?2, dest_modified = ?3
+         WHERE id = ?4",
+        rusqlite::params![resolution, restored_path, dest_modified, id],
+    )
+    .context("Failed to update run item rollback")?;
+
+    Ok(())
+}
+
 /// Updates the status of a run item.
 pub fn update_run_item_status(conn: &Connection, id: &str, status: &str) -> Result<()> {
     let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
@@ -220,10 +838,365 @@ pub fn update_run_item_status(conn: &Connection, id: &str, status: &str) -> Resu
     };
 
     conn.execute(
-        "UPDATE run_items SET status = ?1, rolled_back_at = COALESCE(?2, rolled_back_at) WHERE id = ?3",
+        "UPDATE run_items SET status = ?1, rolled_back_at = COALESCE(?2, rolled_back_at),
+         error_message = CASE WHEN ?2 IS NULL THEN error_message END,
+         error_code = CASE WHEN ?2 IS NULL THEN error_code END
+         WHERE id = ?3",
         rusqlite::params![status, rolled_back_at, id],
     )
     .context("Failed to update run item status")?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::SimulationItem;
+    use crate::db::test_support::{self, item};
+
+    #[test]
+    fn test_run_keeps_its_plan_once_finished() {
+        let conn = test_support::conn();
+        let run = test_support::run(&conn, "/in");
+
+        // Two actions on a.pdf count its bytes once; b.pdf is skipped later
+        let a = test_support::file_entry("/in/a.pdf", 100);
+        let b = test_support::file_entry("/in/b.pdf", 50);
+        let mut plan = test_support::simulation(vec![
+            item(a.clone(), "/out/a.pdf"),
+            SimulationItem { action_type: "copy".to_string(), ..item(a, "/bak/a.pdf") },
+            item(b, "/out/b.pdf"),
+        ]);
+        plan.total_files = 5;
+        plan.matched_files = 2;
+        plan.unmatched_files = 3;
+        set_run_plan(&conn, &run.id, &plan).unwrap();
+
+        update_run_status(&conn, &run.id, "cancelled", 1, 0, 0).unwrap();
+        add_run_duration(&conn, &run.id, 40).unwrap();
+        add_run_duration(&conn, &run.id, 2).unwrap();
+
+        let run = get_run(&conn, &run.id).unwrap().unwrap();
+        assert_eq!(
+            (run.scanned_files, run.matched_files, run.unmatched_files),
+            (5, 2, 3)
+        );
+        assert_eq!((run.total_files, run.moved_files), (3, 1));
+        assert_eq!(run.total_bytes, 150);
+        assert_eq!(run.duration_ms, Some(42));
+        assert!(run.completed_at.is_some());
+    }
+
+    /// Starts a run, then backdates it and sets its final status.
+    fn dated_run(
+        conn: &Connection,
+        profile_id: &str,
+        run_type: &str,
+        status: &str,
+        folder: &str,
+        started_at: &str,
+    ) -> String {
+        let run = create_run(conn, profile_id, run_type, folder).unwrap();
+        conn.execute(
+            "UPDATE runs SET status = ?1, started_at = ?2 WHERE id = ?3",
+            rusqlite::params![status, started_at, run.id],
+        )
+        .unwrap();
+        run.id
+    }
+
+    /// A run item executed on `executed_at`.
+    fn dated_item(
+        conn: &Connection,
+        run_id: &str,
+        rule_id: Option<&str>,
+        destination: &str,
+        size: i64,
+        status: &str,
+        executed_at: &str,
+    ) -> String {
+        let name = Path::new(destination)
+            .file_name()
+            .unwrap()
+            .to_string_lossy();
+        let item = create_run_item(
+            conn,
+            run_id,
+            rule_id,
+            &format!("/in/{}", name),
+            destination,
+            size,
+            "move_to_folder",
+            status,
+            "suffix",
+        )
+        .unwrap();
+        conn.execute(
+            "UPDATE run_items SET executed_at = ?1 WHERE id = ?2",
+            rusqlite::params![executed_at, item.id],
+        )
+        .unwrap();
+        item.id
+    }
+
+    #[test]
+    fn test_search_runs_filters() {
+        let conn = test_support::conn();
+        conn.execute("INSERT INTO profiles (id, name) VALUES ('p2', 'Other')", [])
+            .unwrap();
+        let january = dated_run(
+            &conn,
+            "p1",
+            "manual",
+            "completed",
+            "/home/Downloads",
+            "2025-01-10 09:00:00",
+        );
+        let february = dated_run(
+            &conn,
+            "p1",
+            "scheduled",
+            "completed_with_errors",
+            "/home/Downloads/Sub",
+            "2025-02-15 23:30:00",
+        );
+        let march = dated_run(
+            &conn,
+            "p2",
+            "watcher",
+            "completed",
+            "/home/Downloads2",
+            "2025-03-01 08:00:00",
+        );
+
+        let ids = |filter: RunFilter| -> Vec<String> {
+            search_runs(&conn, &filter)
+                .unwrap()
+                .into_iter()
+                .map(|r| r.id)
+                .collect()
+        };
+        let some = |value: &str| Some(value.to_string());
+
+        assert_eq!(
+            ids(RunFilter::default()),
+            [march.as_str(), february.as_str(), january.as_str()]
+        );
+        let by_profile = RunFilter {
+            profile_id: some("p1"),
+            ..Default::default()
+        };
+        assert_eq!(ids(by_profile), [february.as_str(), january.as_str()]);
+        let by_type = RunFilter {
+            run_type: some("scheduled"),
+            ..Default::default()
+        };
+        assert_eq!(ids(by_type), [february.as_str()]);
+        let by_status = RunFilter {
+            status: some("completed"),
+            ..Default::default()
+        };
+        assert_eq!(ids(by_status), [march.as_str(), january.as_str()]);
+
+        // Both ends of the date range are whole days and inclusive
+        let from = RunFilter {
+            from: some("2025-02-15"),
+            ..Default::default()
+        };
+        assert_eq!(ids(from), [march.as_str(), february.as_str()]);
+        let to = RunFilter {
+            to: some("2025-02-15"),
+            ..Default::default()
+        };
+        assert_eq!(ids(to), [february.as_str(), january.as_str()]);
+        let day = RunFilter {
+            from: some("2025-02-15"),
+            to: some("2025-02-15"),
+            ..Default::default()
+        };
+        assert_eq!(ids(day), [february.as_str()]);
+
+        // A folder picks its subfolders, not siblings sharing its prefix
+        let folder = RunFilter {
+            source_folder: some("/home/Downloads/"),
+            ..Default::default()
+        };
+        assert_eq!(ids(folder), [february.as_str(), january.as_str()]);
+
+        let combined = RunFilter {
+            profile_id: some("p1"),
+            source_folder: some("/home/Downloads"),
+            status: some("completed"),
+            ..Default::default()
+        };
+        assert_eq!(ids(combined), [january.as_str()]);
+
+        let page = RunFilter {
+            limit: Some(1),
+            offset: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(ids(page), [february.as_str()]);
+    }
+
+    #[test]
+    fn test_search_run_items_with_short_and_long_queries() {
+        let conn = test_support::conn();
+        let run = test_support::run(&conn, "/in");
+        let at = "2025-01-10 09:00:00";
+        let report = dated_item(
+            &conn,
+            &run.id,
+            None,
+            "/out/Docs/Relatório.pdf",
+            10,
+            "completed",
+            at,
+        );
+        let notes = dated_item(&conn, &run.id, None, "/out/ab.txt", 10, "completed", at);
+        set_run_item_rollback(
+            &conn,
+            &notes,
+            "restored_folder",
+            Some("/in/Restored/ab.txt"),
+            false,
+        )
+        .unwrap();
+
+        let found = |query: &str| -> Vec<String> {
+            search_run_items(&conn, query, 10)
+                .unwrap()
+                .into_iter()
+                .map(|i| i.id)
+                .collect()
+        };
+
+        // Three characters or more go through the trigram index, ignoring case
+        assert_eq!(found("relat"), [report.as_str()]);
+        assert_eq!(found("DOCS"), [report.as_str()]);
+        assert_eq!(found("restored"), [notes.as_str()]);
+        assert!(found("missing").is_empty());
+        // Quotes are searched for, not parsed as FTS syntax
+        assert!(found("a\"b OR c").is_empty());
+
+        // Shorter queries fall back to a substring match, also ignoring case
+        assert_eq!(found("ab"), [notes.as_str()]);
+        assert_eq!(found("AB"), [notes.as_str()]);
+        assert!(found("zq").is_empty());
+        assert_eq!(found("/").len(), 2);
+    }
+
+    #[test]
+    fn test_run_stats_buckets() {
+        let conn = test_support::conn();
+        conn.execute("INSERT INTO profiles (id, name) VALUES ('p2', 'Other')", [])
+            .unwrap();
+        test_support::rule(&conn, "r1", "Docs");
+        let first = dated_run(
+            &conn,
+            "p1",
+            "manual",
+            "completed",
+            "/in",
+            "2025-01-10 09:00:00",
+        );
+        let second = dated_run(
+            &conn,
+            "p2",
+            "manual",
+            "completed",
+            "/in",
+            "2025-03-01 09:00:00",
+        );
+
+        let day = |d: &str| format!("{} 10:00:00", d);
+        dated_item(
+            &conn,
+            &first,
+            Some("r1"),
+            "/out/Docs/a.pdf",
+            100,
+            "completed",
+            &day("2025-01-10"),
+        );
+        dated_item(
+            &conn,
+            &first,
+            None,
+            "/out/Misc/b.txt",
+            50,
+            "completed",
+            &day("2025-01-11"),
+        );
+        // Failed and rolled back items don't count
+        dated_item(
+            &conn,
+            &first,
+            Some("r1"),
+            "/out/Docs/c.pdf",
+            999,
+            "failed",
+            &day("2025-01-10"),
+        );
+        dated_item(
+            &conn,
+            &first,
+            None,
+            "/out/Misc/d.txt",
+            999,
+            "rolled_back",
+            &day("2025-01-10"),
+        );
+        dated_item(
+            &conn,
+            &second,
+            Some("r1"),
+            "/out/Docs/e.pdf",
+            30,
+            "completed",
+            &day("2025-03-01"),
+        );
+
+        let summary = |buckets: &[StatBucket]| -> Vec<(String, u32, u64)> {
+            buckets
+                .iter()
+                .map(|b| (b.name.clone(), b.files, b.bytes))
+                .collect()
+        };
+        let bucket = |name: &str, files: u32, bytes: u64| (name.to_string(), files, bytes);
+
+        let stats = run_stats(&conn, &RunFilter::default()).unwrap();
+        assert_eq!((stats.files, stats.bytes), (3, 180));
+        assert_eq!(
+            summary(&stats.by_day),
+            [
+                bucket("2025-01-10", 1, 100),
+                bucket("2025-01-11", 1, 50),
+                bucket("2025-03-01", 1, 30),
+            ]
+        );
+        assert_eq!(
+            summary(&stats.by_rule),
+            [bucket("Docs", 2, 130), bucket("", 1, 50)]
+        );
+        assert_eq!(
+            summary(&stats.by_destination),
+            [bucket("/out/Docs", 2, 130), bucket("/out/Misc", 1, 50)]
+        );
+
+        // The filter applies to the runs, and limit and offset are ignored
+        let filter = RunFilter {
+            profile_id: Some("p1".to_string()),
+            limit: Some(1),
+            offset: Some(5),
+            ..Default::default()
+        };
+        let stats = run_stats(&conn, &filter).unwrap();
+        assert_eq!((stats.files, stats.bytes), (2, 150));
+        assert_eq!(
+            summary(&stats.by_rule),
+            [bucket("Docs", 1, 100), bucket("", 1, 50)]
+        );
+    }
+}
@@ -18,6 +18,14 @@ pub fn get_setting(conn: &Connection, key: &str) -> Result<Option<String>> {
     }
 }
 
+/// Gets a boolean setting, stored as `1`/`true` or `0`/`false`. Falls back to
+/// `default` when the key does not exist.
+pub fn get_bool_setting(conn: &Connection, key: &str, default: bool) -> Result<bool> {
+    Ok(get_setting(conn, key)?
+        .map(|value| value == "1" || value == "true")
+        .unwrap_or(default))
+}
+
 /// Sets a setting value. Creates the key if it does not exist.
 pub fn set_setting(conn: &Connection, key: &str, value: &str) -> Result<()> {
     let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
@@ -0,0 +1,75 @@
+use anyhow::{Context, Result};
+use rusqlite::Connection;
+use uuid::Uuid;
+
+use crate::db::models::Shortcut;
+
+/// Records a link created by a `link` or `move_and_link` action.
+pub fn create_link_shortcut(
+    conn: &Connection,
+    run_item_id: &str,
+    link_path: &str,
+    target: &str,
+) -> Result<Shortcut> {
+    let id = Uuid::new_v4().to_string();
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let name = std::path::Path::new(link_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    conn.execute(
+        "INSERT INTO shortcuts (id, name, shortcut_type, target, link_path, run_item_id, created_at)
+         VALUES (?1, ?2, 'link', ?3, ?4, ?5, ?6)",
+        rusqlite::params![id, name, target, link_path, run_item_id, now],
+    )
+    .context("Failed to create link shortcut")?;
+
+    Ok(Shortcut {
+        id,
+        name,
+        shortcut_type: "link".to_string(),
+        target: target.to_string(),
+        hotkey: String::new(),
+        sort_order: 0,
+        created_at: now,
+        link_path: link_path.to_string(),
+        run_item_id: Some(run_item_id.to_string()),
+    })
+}
+
+/// Gets the link created by a run item, if any.
+pub fn get_link_for_run_item(conn: &Connection, run_item_id: &str) -> Result<Option<Shortcut>> {
+    let result = conn.query_row(
+        "SELECT id, name, shortcut_type, target, hotkey, sort_order, created_at, link_path, run_item_id
+         FROM shortcuts WHERE run_item_id = ?1 AND shortcut_type = 'link'",
+        [run_item_id],
+        |row| {
+            Ok(Shortcut {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                shortcut_type: row.get(2)?,
+                target: row.get(3)?,
+                hotkey: row.get(4)?,
+                sort_order: row.get(5)?,
+                created_at: row.get(6)?,
+                link_path: row.get(7)?,
+                run_item_id: row.get(8)?,
+            })
+        },
+    );
+
+    match result {
+        Ok(shortcut) => Ok(Some(shortcut)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(anyhow::anyhow!(e).context("Failed to get link shortcut")),
+    }
+}
+
+/// Deletes a shortcut record.
+pub fn delete_shortcut(conn: &Connection, id: &str) -> Result<()> {
+    conn.execute("DELETE FROM shortcuts WHERE id = ?1", [id])
+        .context("Failed to delete shortcut")?;
+
+    Ok(())
+}
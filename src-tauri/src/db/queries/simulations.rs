@@ -0,0 +1,80 @@
+use anyhow::{Context, Result};
+use rusqlite::Connection;
+use uuid::Uuid;
+
+use crate::db::models::{SimulationResult, StoredSimulation};
+
+/// Simulations older than this are discarded when a new one is stored.
+const SIMULATION_TTL: &str = "-1 day";
+
+/// Stores a simulation and returns its ID. Expired simulations are pruned.
+pub fn save_simulation(
+    conn: &Connection,
+    profile_id: &str,
+    source_folder: &str,
+    result: &SimulationResult,
+) -> Result<String> {
+    let id = Uuid::new_v4().to_string();
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let json = serde_json::to_string(result).context("Failed to serialize simulation")?;
+
+    conn.execute(
+        "DELETE FROM simulations WHERE created_at < datetime('now', ?1)",
+        [SIMULATION_TTL],
+    )
+    .context("Failed to prune expired simulations")?;
+
+    conn.execute(
+        "INSERT INTO simulations (id, profile_id, source_folder, result_json, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        rusqlite::params![id, profile_id, source_folder, json, now],
+    )
+    .context("Failed to insert simulation")?;
+
+    Ok(id)
+}
+
+/// Gets a stored simulation by ID.
+pub fn get_simulation(conn: &Connection, id: &str) -> Result<Option<StoredSimulation>> {
+    let result = conn.query_row(
+        "SELECT id, profile_id, source_folder, result_json, created_at
+         FROM simulations WHERE id = ?1",
+        [id],
+        |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        },
+    );
+
+    let (id, profile_id, source_folder, json, created_at) = match result {
+        Ok(row) => row,
+        Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+        Err(e) => return Err(anyhow::anyhow!(e).context("Failed to get simulation")),
+    };
+
+    let mut result: SimulationResult =
+        serde_json::from_str(&json).context("Stored simulation is corrupted")?;
+    result.simulation_id = Some(id.clone());
+
+    Ok(Some(StoredSimulation {
+        id,
+        profile_id,
+        source_folder,
+        result,
+        created_at,
+    }))
+}
+
+/// Deletes a stored simulation. Called once it has been executed so the same
+/// plan cannot be replayed.
+pub fn delete_simulation(conn: &Connection, id: &str) -> Result<()> {
+    conn.execute("DELETE FROM simulations WHERE id = ?1", [id])
+        .context("Failed to delete simulation")?;
+
+    Ok(())
+}
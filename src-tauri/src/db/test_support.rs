@@ -0,0 +1,94 @@
+//! Setup shared by the unit tests that need a database, scratch files or
+//! planned items.
+
+use rusqlite::Connection;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::db::migrations;
+use crate::db::models::{FileEntry, Run, SimulationItem, SimulationResult};
+use crate::db::queries::runs;
+
+/// The profile every connection from [`conn`] comes with.
+pub const PROFILE_ID: &str = "p1";
+
+/// An in-memory database with foreign keys on, every migration applied and
+/// profile [`PROFILE_ID`].
+pub fn conn() -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch("PRAGMA foreign_keys=ON;").unwrap();
+    migrations::run_migrations(&conn).unwrap();
+    conn.execute(
+        "INSERT INTO profiles (id, name) VALUES (?1, 'Test')",
+        [PROFILE_ID],
+    )
+    .unwrap();
+    conn
+}
+
+/// Inserts a bare rule, enough for run items to reference it.
+pub fn rule(conn: &Connection, id: &str, name: &str) {
+    conn.execute("INSERT INTO rules (id, name) VALUES (?1, ?2)", [id, name])
+        .unwrap();
+}
+
+/// Starts a manual run of [`PROFILE_ID`] over `folder`.
+pub fn run(conn: &Connection, folder: &str) -> Run {
+    runs::create_run(conn, PROFILE_ID, "manual", folder).unwrap()
+}
+
+/// An empty scratch folder for one test, named after it and the process.
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("deskcraft-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// An entry for a file that does not need to exist.
+pub fn file_entry(path: &str, size: u64) -> FileEntry {
+    let path_ref = Path::new(path);
+    let part = |part: Option<&std::ffi::OsStr>| {
+        part.map(|p| p.to_string_lossy().to_string()).unwrap_or_default()
+    };
+    FileEntry {
+        path: path.to_string(),
+        name: part(path_ref.file_name()),
+        extension: part(path_ref.extension()),
+        size,
+        created_at: String::new(),
+        modified_at: String::new(),
+    }
+}
+
+/// A planned `move_to_folder` of `file` to `destination`, with the global
+/// conflict strategy. Other fields can be set with struct update syntax.
+pub fn item(file: FileEntry, destination: &str) -> SimulationItem {
+    SimulationItem {
+        file,
+        rule_id: String::new(),
+        rule_name: String::new(),
+        action_type: "move_to_folder".to_string(),
+        destination: destination.to_string(),
+        conflict: false,
+        error: None,
+        post_action: "keep".to_string(),
+        post_destination: String::new(),
+        conflict_strategy: String::new(),
+        final_path: String::new(),
+        skip_reason: None,
+        journal_id: None,
+    }
+}
+
+/// A plan made of `items`, each counted as a matched file.
+pub fn simulation(items: Vec<SimulationItem>) -> SimulationResult {
+    let count = items.len() as u32;
+    SimulationResult {
+        items,
+        total_files: count,
+        matched_files: count,
+        unmatched_files: 0,
+        simulation_id: None,
+    }
+}
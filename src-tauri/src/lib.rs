@@ -7,7 +7,8 @@ pub mod rules;
 pub mod tips;
 pub mod watcher;
 
-use std::sync::Mutex;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
 
 use commands::help_commands;
 use commands::history_commands;
@@ -24,6 +25,11 @@ use commands::watched_folder_commands;
 pub struct AppState {
     pub db: Mutex<rusqlite::Connection>,
     pub app_data_dir: String,
+    /// Set by `cancel_execution`; checked between files by the executor.
+    pub cancel_requested: AtomicBool,
+    /// Held while a run moves files, on a connection of its own, so runs
+    /// started from the UI and by the scheduler never overlap.
+    pub run_lock: Arc<Mutex<()>>,
 }
 
 pub fn run() {
@@ -34,10 +40,20 @@ pub fn run() {
     let conn = db::connection::init(&app_data_dir)
         .expect("Failed to initialize database");
 
+    // Runs left `running` by a crash are marked `interrupted` before anything
+    // else touches the database
+    match organizer::recovery::reconcile_interrupted_runs(&conn) {
+        Ok(0) => {}
+        Ok(n) => log::warn!("Found {} interrupted run(s) from a previous session", n),
+        Err(e) => log::error!("Failed to reconcile interrupted runs: {}", e),
+    }
+
     // Spawn background scheduler thread with its own DB connection
+    let run_lock = Arc::new(Mutex::new(()));
     let scheduler_data_dir = app_data_dir.clone();
+    let scheduler_run_lock = Arc::clone(&run_lock);
     std::thread::spawn(move || {
-        scheduler_loop(&scheduler_data_dir);
+        scheduler_loop(&scheduler_data_dir, &scheduler_run_lock);
     });
 
     tauri::Builder::default()
@@ -46,13 +62,27 @@ pub fn run() {
         .manage(AppState {
             db: Mutex::new(conn),
             app_data_dir: app_data_dir.clone(),
+            cancel_requested: AtomicBool::new(false),
+            run_lock,
         })
         .invoke_handler(tauri::generate_handler![
             // Organizer commands
             organizer_commands::scan_folder,
             organizer_commands::simulate_folder,
+            organizer_commands::preflight_simulation,
             organizer_commands::execute_simulation,
+            organizer_commands::export_simulation,
             organizer_commands::rollback_run,
+            organizer_commands::rollback_run_items,
+            organizer_commands::rollback_run_rule,
+            organizer_commands::get_rollback_dependencies,
+            organizer_commands::simulate_rollback,
+            organizer_commands::resume_run,
+            organizer_commands::retry_run_errors,
+            organizer_commands::cancel_execution,
+            organizer_commands::ignore_file,
+            organizer_commands::list_ignored_files,
+            organizer_commands::unignore_file,
             organizer_commands::select_folder,
             // Rule commands
             rule_commands::list_rules,
@@ -66,6 +96,7 @@ pub fn run() {
             rule_commands::get_rule_actions,
             rule_commands::add_rule_action,
             rule_commands::delete_rule_action,
+            rule_commands::test_rule,
             // Profile commands
             profile_commands::list_profiles,
             profile_commands::create_profile,
@@ -97,8 +128,17 @@ pub fn run() {
             tips_commands::dismiss_tip,
             // History commands
             history_commands::list_runs,
+            history_commands::search_runs,
+            history_commands::search_run_items,
+            history_commands::get_run_stats,
+            history_commands::prune_history,
+            history_commands::get_database_size,
+            history_commands::vacuum_database,
+            history_commands::locate_file,
+            history_commands::open_containing_folder,
             history_commands::get_run,
             history_commands::list_run_items,
+            history_commands::export_run,
             // Watched folder commands
             watched_folder_commands::list_watched_folders,
             watched_folder_commands::add_watched_folder,
@@ -121,7 +161,9 @@ pub fn run() {
 
 /// Background scheduler loop.
 /// Opens its own DB connection and checks for due schedules every 30 seconds.
-fn scheduler_loop(app_data_dir: &str) {
+/// Also applies the history retention policy once a day.
+/// Each scheduled run holds `run_lock` (see [`AppState::run_lock`]).
+fn scheduler_loop(app_data_dir: &str, run_lock: &Mutex<()>) {
     // Wait a few seconds for the app to fully start
     std::thread::sleep(std::time::Duration::from_secs(5));
 
@@ -138,6 +180,11 @@ fn scheduler_loop(app_data_dir: &str) {
     loop {
         std::thread::sleep(std::time::Duration::from_secs(30));
 
+        // History retention runs at most once a day
+        if let Err(e) = organizer::retention::prune_if_due(&conn) {
+            log::warn!("Scheduler: failed to prune run history: {}", e);
+        }
+
         let due = match db::queries::schedules::get_due_schedules(&conn) {
             Ok(d) => d,
             Err(e) => {
@@ -175,7 +222,13 @@ fn scheduler_loop(app_data_dir: &str) {
                 schedule.id, schedule.profile_id, folder_path
             );
 
-            match run_scheduled_organization(&conn, &schedule.profile_id, &folder_path) {
+            let outcome = match run_lock.lock() {
+                Ok(_running) => {
+                    run_scheduled_organization(&conn, &schedule.profile_id, &folder_path)
+                }
+                Err(e) => Err(anyhow::anyhow!("Failed to wait for the run in progress: {}", e)),
+            };
+            match outcome {
                 Ok((moved, skipped, errors)) => {
                     log::info!(
                         "Scheduler: schedule {} done — moved={}, skipped={}, errors={}",
@@ -218,21 +271,58 @@ fn run_scheduled_organization(
         actions_map.insert(rule.id.clone(), db::queries::rules::get_actions(conn, &rule.id)?);
     }
 
-    let files = organizer::scanner::scan_folder(folder_path, false)?;
-    if files.is_empty() {
-        return Ok((0, 0, 0));
-    }
-
-    let simulation = organizer::simulator::simulate(&files, &profile_rules, &conditions_map, &actions_map);
-    if simulation.matched_files == 0 {
-        return Ok((0, simulation.unmatched_files, 0));
-    }
+    let ignored = db::queries::ignored_files::ignored_paths(conn)?;
+    let mut files = organizer::scanner::scan_folder(folder_path, false)?;
+    files.retain(|f| !ignored.contains(&f.path));
 
     let conflict_strategy = db::queries::settings::get_setting(conn, "conflict_strategy")?
         .unwrap_or_else(|| "suffix".to_string());
 
+    let simulation = organizer::simulator::simulate(
+        &files,
+        &profile_rules,
+        &conditions_map,
+        &actions_map,
+        &conflict_strategy,
+    );
+
+    // Every firing is recorded, even with nothing to do, so that the history
+    // shows the schedule ran and why it did nothing
     let run = db::queries::runs::create_run(conn, profile_id, "scheduled", folder_path)?;
-    let result = organizer::executor::execute(conn, &simulation, &run.id, &conflict_strategy)?;
+    db::queries::runs::set_run_plan(conn, &run.id, &simulation)?;
+
+    if simulation.items.is_empty() {
+        db::queries::runs::finish_idle_run(conn, &run.id)?;
+        return Ok((0, simulation.unmatched_files, 0));
+    }
+
+    let verify = db::queries::settings::get_bool_setting(conn, "verify_moves", false)?;
+
+    let report = organizer::preflight::preflight(&simulation);
+    if !report.go {
+        let reason = format!(
+            "Preflight failed for {}: {}",
+            folder_path,
+            organizer::preflight::describe_blockers(&report)
+        );
+        db::queries::runs::update_run_status(conn, &run.id, "error", 0, 0, 0)?;
+        db::queries::runs::set_run_error(conn, &run.id, Some(&reason))?;
+        anyhow::bail!(reason);
+    }
+
+    let result = organizer::executor::execute(
+        conn,
+        &simulation,
+        &run.id,
+        &conflict_strategy,
+        verify,
+        &organizer::progress::RunControl::none(),
+    )
+    .inspect_err(|e| {
+        // The run stays `running` for recovery to pick up; the reason is kept
+        let reason = format!("{:#}", e);
+        db::queries::runs::set_run_error(conn, &run.id, Some(&reason)).ok();
+    })?;
 
     Ok((result.moved, result.skipped, result.errors))
 }
@@ -262,3 +352,40 @@ fn dirs_next_app_data() -> String {
     std::fs::create_dir_all(&fallback).ok();
     fallback.to_string_lossy().to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support;
+    use std::fs;
+
+    #[test]
+    fn test_scheduled_run_with_nothing_to_do_is_recorded_idle() {
+        let conn = test_support::conn();
+        test_support::rule(&conn, "r1", "Docs");
+        db::queries::rules::add_condition(&conn, "r1", "extension", "equals", "pdf", "AND")
+            .unwrap();
+        db::queries::profiles::add_rule_to_profile(&conn, test_support::PROFILE_ID, "r1")
+            .unwrap();
+
+        let dir = test_support::temp_dir("scheduled-idle");
+        fs::write(dir.join("notes.txt"), b"notes").unwrap();
+        fs::write(dir.join("photo.jpg"), b"photo").unwrap();
+        let folder = dir.to_string_lossy().to_string();
+
+        let counts =
+            run_scheduled_organization(&conn, test_support::PROFILE_ID, &folder).unwrap();
+        assert_eq!(counts, (0, 2, 0));
+
+        let runs = db::queries::runs::list_runs(&conn, 10, 0).unwrap();
+        assert_eq!(runs.len(), 1);
+        let run = &runs[0];
+        assert_eq!((run.run_type.as_str(), run.status.as_str()), ("scheduled", "idle"));
+        assert_eq!((run.scanned_files, run.matched_files, run.unmatched_files), (2, 0, 2));
+        assert_eq!((run.total_files, run.total_bytes), (0, 0));
+        assert_eq!(run.duration_ms, Some(0));
+        assert!(run.completed_at.is_some());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,651 @@
+use anyhow::{bail, Context, Result};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::organizer::{conflict, integrity};
+use crate::rules::formats::{archive_kind, ArchiveKind};
+use crate::rules::sanitize::sanitize_relative;
+
+/// Maximum number of entries accepted in a single archive.
+const MAX_ENTRIES: u64 = 50_000;
+
+/// Maximum number of bytes written while extracting a single archive (8 GiB).
+const MAX_EXTRACTED_BYTES: u64 = 8 * 1024 * 1024 * 1024;
+
+/// Maximum ratio between extracted bytes and the archive's size on disk.
+const MAX_COMPRESSION_RATIO: u64 = 200;
+
+/// The ratio check only kicks in past this many extracted bytes, so small but
+/// highly compressible archives (e.g. text files) still extract.
+const RATIO_CHECK_THRESHOLD: u64 = 64 * 1024 * 1024;
+
+/// Extracts an archive into `dest_dir`.
+///
+/// Entry paths are validated (no absolute paths or `..`, zip-slip) and each
+/// segment is sanitized. The number of entries, the total bytes written and
+/// the compression ratio are capped to protect against zip bombs; sizes
+/// declared in the archive headers are not trusted, bytes are counted while
+/// writing.
+///
+/// Files that already exist in the destination are handled with the given
+/// conflict strategy. `on_file` is called after each file is completely
+/// written, with its final path and size, so the caller can record it; an
+/// error from it stops the extraction.
+/// Returns the number of files extracted.
+pub fn extract_archive(
+    archive: &Path,
+    dest_dir: &Path,
+    conflict_strategy: &str,
+    on_file: &mut dyn FnMut(&Path, u64) -> Result<()>,
+) -> Result<u32> {
+    let kind = archive_kind(archive)
+        .ok_or_else(|| anyhow::anyhow!("Unsupported archive format: {}", archive.display()))?;
+
+    let archive_size = fs::metadata(archive)
+        .with_context(|| format!("Failed to read archive metadata: {}", archive.display()))?
+        .len();
+
+    fs::create_dir_all(dest_dir)
+        .with_context(|| format!("Failed to create directory {}", dest_dir.display()))?;
+
+    let mut budget = ExtractionBudget::new(archive_size);
+    let file = File::open(archive)
+        .with_context(|| format!("Failed to open archive {}", archive.display()))?;
+
+    match kind {
+        ArchiveKind::Zip => extract_zip(file, dest_dir, conflict_strategy, &mut budget, on_file),
+        ArchiveKind::Tar => extract_tar(file, dest_dir, conflict_strategy, &mut budget, on_file),
+        ArchiveKind::TarGz => extract_tar(
+            flate2::read::GzDecoder::new(file),
+            dest_dir,
+            conflict_strategy,
+            &mut budget,
+            on_file,
+        ),
+    }
+}
+
+fn extract_zip(
+    file: File,
+    dest_dir: &Path,
+    conflict_strategy: &str,
+    budget: &mut ExtractionBudget,
+    on_file: &mut dyn FnMut(&Path, u64) -> Result<()>,
+) -> Result<u32> {
+    let mut zip = zip::ZipArchive::new(file).context("Failed to read zip archive")?;
+    let mut extracted = 0u32;
+
+    for index in 0..zip.len() {
+        budget.count_entry()?;
+
+        let mut entry = zip
+            .by_index(index)
+            .with_context(|| format!("Failed to read zip entry #{}", index))?;
+
+        let relative = safe_entry_path(entry.name())?;
+
+        if entry.is_dir() {
+            fs::create_dir_all(dest_dir.join(&relative))
+                .with_context(|| format!("Failed to create directory for entry {}", entry.name()))?;
+            continue;
+        }
+        if entry.is_symlink() {
+            log::warn!("Skipping symlink entry in zip: {}", entry.name());
+            continue;
+        }
+
+        if let Some((path, size)) =
+            write_entry(&mut entry, dest_dir, &relative, conflict_strategy, budget)?
+        {
+            on_file(&path, size)?;
+            extracted += 1;
+        }
+    }
+
+    Ok(extracted)
+}
+
+fn extract_tar<R: Read>(
+    reader: R,
+    dest_dir: &Path,
+    conflict_strategy: &str,
+    budget: &mut ExtractionBudget,
+    on_file: &mut dyn FnMut(&Path, u64) -> Result<()>,
+) -> Result<u32> {
+    let mut tar = tar::Archive::new(reader);
+    let mut extracted = 0u32;
+
+    for entry_result in tar.entries().context("Failed to read tar archive")? {
+        budget.count_entry()?;
+
+        let mut entry = entry_result.context("Failed to read tar entry")?;
+        let raw_path = entry
+            .path()
+            .context("Invalid tar entry path")?
+            .to_string_lossy()
+            .to_string();
+
+        let relative = safe_entry_path(&raw_path)?;
+        let entry_type = entry.header().entry_type();
+
+        if entry_type.is_dir() {
+            fs::create_dir_all(dest_dir.join(&relative))
+                .with_context(|| format!("Failed to create directory for entry {}", raw_path))?;
+            continue;
+        }
+        if !entry_type.is_file() {
+            log::warn!("Skipping non-regular tar entry: {}", raw_path);
+            continue;
+        }
+
+        if let Some((path, size)) =
+            write_entry(&mut entry, dest_dir, &relative, conflict_strategy, budget)?
+        {
+            on_file(&path, size)?;
+            extracted += 1;
+        }
+    }
+
+    Ok(extracted)
+}
+
+/// Writes a single entry to disk, resolving conflicts first. Returns `None`
+/// when the entry was skipped because of the `skip` conflict strategy.
+fn write_entry(
+    reader: &mut dyn Read,
+    dest_dir: &Path,
+    relative: &Path,
+    conflict_strategy: &str,
+    budget: &mut ExtractionBudget,
+) -> Result<Option<(PathBuf, u64)>> {
+    let mut target = dest_dir.join(relative);
+
+    if target.exists() {
+        if conflict_strategy == "skip" {
+            log::info!("Skipping existing file during extraction: {}", target.display());
+            return Ok(None);
+        }
+        target = conflict::resolve_conflict(&target, conflict_strategy);
+    }
+
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+    }
+
+    let mut out = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&target)
+        .with_context(|| format!("Failed to create {}", target.display()))?;
+
+    match budget.copy(reader, &mut out) {
+        Ok(size) => Ok(Some((target, size))),
+        Err(e) => {
+            drop(out);
+            fs::remove_file(&target).ok();
+            Err(e.context(format!("Failed to extract {}", relative.display())))
+        }
+    }
+}
+
+/// Validates an entry name from an archive and turns it into a safe relative
+/// path. Absolute paths, drive prefixes and `..` segments are rejected.
+fn safe_entry_path(name: &str) -> Result<PathBuf> {
+    sanitize_relative(name).with_context(|| format!("Unsafe archive entry '{}'", name))
+}
+
+/// Adds `files` (each with its conflict strategy) to the zip archive at
+/// `archive_path`, creating the archive (and its folder) when it does not
+/// exist yet and appending otherwise.
+///
+/// Each file is stored under its name; if that name is already taken inside
+/// the archive a numeric suffix is added, or the file is left out when its
+/// strategy is `skip`. The archive is never written in place: all the entries
+/// are appended to one temporary copy, read back and their size and SHA-256
+/// compared with the sources, and only then does the copy replace the
+/// archive, so a failure leaves the archive as it was and the caller can
+/// safely delete the originals once this returns.
+///
+/// Returns, for each file, its entry name, `None` when it was skipped, or the
+/// error that kept it out (a source that can't be read). An error writing the
+/// archive fails every file.
+pub fn add_to_archive(
+    archive_path: &Path,
+    files: &[(&Path, &str)],
+) -> Result<Vec<Result<Option<String>>>> {
+    let mut names: Vec<String> = if archive_path.exists() {
+        let zip = zip::ZipArchive::new(File::open(archive_path)?)
+            .with_context(|| format!("Failed to read archive {}", archive_path.display()))?;
+        zip.file_names().map(|n| n.to_string()).collect()
+    } else {
+        Vec::new()
+    };
+    let existing = names.len();
+
+    let mut added: Vec<NewEntry> = Vec::new();
+    let mut results = Vec::new();
+    for &(source, conflict_strategy) in files {
+        let file_name = match source.file_name() {
+            Some(name) => name.to_string_lossy().to_string(),
+            None => {
+                results.push(Err(anyhow::anyhow!("Invalid source file: {}", source.display())));
+                continue;
+            }
+        };
+        if names.contains(&file_name) && conflict_strategy == "skip" {
+            results.push(Ok(None));
+            continue;
+        }
+        let (hash, size) = match integrity::sha256_file(source) {
+            Ok(digest) => digest,
+            Err(e) => {
+                results.push(Err(e));
+                continue;
+            }
+        };
+        let name = unique_entry_name(&names, &file_name);
+        names.push(name.clone());
+        results.push(Ok(Some(name.clone())));
+        added.push(NewEntry { source, name, hash, size });
+    }
+    if added.is_empty() {
+        return Ok(results);
+    }
+
+    if let Some(parent) = archive_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+    }
+    let tmp_path = archive_path.with_extension("zip.tmp");
+    if let Err(e) = append_entries(archive_path, &tmp_path, &added, existing) {
+        fs::remove_file(&tmp_path).ok();
+        return Err(e);
+    }
+    if let Err(e) = fs::rename(&tmp_path, archive_path) {
+        fs::remove_file(&tmp_path).ok();
+        return Err(anyhow::anyhow!(e)
+            .context(format!("Failed to replace archive {}", archive_path.display())));
+    }
+
+    Ok(results)
+}
+
+/// A file on its way into an archive, with the digest it must be stored with.
+struct NewEntry<'a> {
+    source: &'a Path,
+    name: String,
+    hash: String,
+    size: u64,
+}
+
+/// Writes `tmp_path` as a copy of the archive at `archive_path` (if any) with
+/// `entries` added, then checks the copy holds the `existing` entries plus the
+/// new ones, stored intact.
+fn append_entries(
+    archive_path: &Path,
+    tmp_path: &Path,
+    entries: &[NewEntry],
+    existing: usize,
+) -> Result<()> {
+    let mut writer = if archive_path.exists() {
+        fs::copy(archive_path, tmp_path)
+            .with_context(|| format!("Failed to copy archive {}", archive_path.display()))?;
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(tmp_path)
+            .with_context(|| format!("Failed to open {}", tmp_path.display()))?;
+        zip::ZipWriter::new_append(file)
+            .with_context(|| format!("Failed to append to archive {}", archive_path.display()))?
+    } else {
+        let file = File::create(tmp_path)
+            .with_context(|| format!("Failed to create {}", tmp_path.display()))?;
+        zip::ZipWriter::new(file)
+    };
+
+    for entry in entries {
+        let mut options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated)
+            .large_file(entry.size > u32::MAX as u64);
+        if let Some(mtime) = zip_mtime(entry.source) {
+            options = options.last_modified_time(mtime);
+        }
+
+        writer
+            .start_file(entry.name.as_str(), options)
+            .with_context(|| format!("Failed to add entry {}", entry.name))?;
+        let mut input = File::open(entry.source)
+            .with_context(|| format!("Failed to open {}", entry.source.display()))?;
+        std::io::copy(&mut input, &mut writer)
+            .with_context(|| format!("Failed to compress {}", entry.source.display()))?;
+    }
+    writer
+        .finish()
+        .with_context(|| format!("Failed to finalize archive {}", tmp_path.display()))?
+        .sync_all()
+        .with_context(|| format!("Failed to flush archive {}", tmp_path.display()))?;
+
+    // Verify the copy before it replaces the archive
+    let mut zip = zip::ZipArchive::new(File::open(tmp_path)?)
+        .with_context(|| format!("Failed to reopen archive {}", tmp_path.display()))?;
+    let expected = existing + entries.len();
+    if zip.len() != expected {
+        bail!(
+            "Archive verification failed for {}: expected {} entries, found {}",
+            archive_path.display(),
+            expected,
+            zip.len()
+        );
+    }
+    for entry in entries {
+        let (stored_hash, stored_size) = {
+            let mut stored = zip
+                .by_name(&entry.name)
+                .with_context(|| format!("Entry {} missing after write", entry.name))?;
+            integrity::sha256_reader(&mut stored)?
+        };
+        if stored_hash != entry.hash || stored_size != entry.size {
+            bail!(
+                "Archive verification failed for {} in {}",
+                entry.name,
+                archive_path.display()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Restores a single entry from a zip archive to `dest`, then removes the
+/// entry from the archive. The archive itself is deleted once empty.
+pub fn restore_from_archive(archive_path: &Path, entry_name: &str, dest: &Path) -> Result<()> {
+    {
+        let mut zip = zip::ZipArchive::new(File::open(archive_path)?)
+            .with_context(|| format!("Failed to read archive {}", archive_path.display()))?;
+        let mut entry = zip
+            .by_name(entry_name)
+            .with_context(|| format!("Entry {} not found in {}", entry_name, archive_path.display()))?;
+
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+        }
+        let mut out = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(dest)
+            .with_context(|| format!("Failed to create {}", dest.display()))?;
+        if let Err(e) = std::io::copy(&mut entry, &mut out) {
+            drop(out);
+            fs::remove_file(dest).ok();
+            return Err(anyhow::anyhow!(e).context(format!("Failed to restore {}", entry_name)));
+        }
+    }
+
+    remove_entry(archive_path, entry_name)
+}
+
+/// Rewrites a zip archive without the given entry. Other entries are copied
+/// without recompression. Deletes the archive when no entries remain.
+fn remove_entry(archive_path: &Path, entry_name: &str) -> Result<()> {
+    let mut zip = zip::ZipArchive::new(File::open(archive_path)?)
+        .with_context(|| format!("Failed to read archive {}", archive_path.display()))?;
+
+    let tmp_path = archive_path.with_extension("zip.tmp");
+    let mut writer = zip::ZipWriter::new(
+        File::create(&tmp_path)
+            .with_context(|| format!("Failed to create {}", tmp_path.display()))?,
+    );
+
+    let mut remaining = 0usize;
+    for index in 0..zip.len() {
+        let entry = zip.by_index_raw(index)?;
+        if entry.name() == entry_name {
+            continue;
+        }
+        writer.raw_copy_file(entry)?;
+        remaining += 1;
+    }
+    writer.finish()?;
+    drop(zip);
+
+    if remaining == 0 {
+        fs::remove_file(&tmp_path).ok();
+        fs::remove_file(archive_path)
+            .with_context(|| format!("Failed to remove empty archive {}", archive_path.display()))?;
+    } else {
+        fs::rename(&tmp_path, archive_path)
+            .with_context(|| format!("Failed to replace archive {}", archive_path.display()))?;
+    }
+
+    Ok(())
+}
+
+/// Returns `name`, or `stem_1.ext`, `stem_2.ext`, ... if it is already taken.
+fn unique_entry_name(existing: &[String], name: &str) -> String {
+    if !existing.contains(&name.to_string()) {
+        return name.to_string();
+    }
+
+    let path = Path::new(name);
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    let ext = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+
+    let mut counter = 1u32;
+    loop {
+        let candidate = format!("{}_{}{}", stem, counter, ext);
+        if !existing.contains(&candidate) {
+            return candidate;
+        }
+        counter += 1;
+    }
+}
+
+/// Converts a file's modification time into a zip timestamp (local time).
+fn zip_mtime(path: &Path) -> Option<zip::DateTime> {
+    use chrono::{Datelike, Timelike};
+
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    let dt: chrono::DateTime<chrono::Local> = modified.into();
+    zip::DateTime::from_date_and_time(
+        u16::try_from(dt.year()).ok()?,
+        dt.month() as u8,
+        dt.day() as u8,
+        dt.hour() as u8,
+        dt.minute() as u8,
+        dt.second() as u8,
+    )
+    .ok()
+}
+
+/// Tracks how much an extraction has produced so far and enforces the
+/// zip-bomb limits.
+struct ExtractionBudget {
+    archive_size: u64,
+    entries: u64,
+    written: u64,
+}
+
+impl ExtractionBudget {
+    fn new(archive_size: u64) -> Self {
+        ExtractionBudget {
+            archive_size,
+            entries: 0,
+            written: 0,
+        }
+    }
+
+    fn count_entry(&mut self) -> Result<()> {
+        self.entries += 1;
+        if self.entries > MAX_ENTRIES {
+            bail!("Archive has more than {} entries", MAX_ENTRIES);
+        }
+        Ok(())
+    }
+
+    /// Copies `reader` into `writer`, counting bytes against the limits.
+    fn copy(&mut self, reader: &mut dyn Read, writer: &mut dyn Write) -> Result<u64> {
+        let mut buf = [0u8; 64 * 1024];
+        let mut copied = 0u64;
+
+        loop {
+            let n = reader.read(&mut buf).context("Failed to read archive entry")?;
+            if n == 0 {
+                break;
+            }
+
+            self.written += n as u64;
+            copied += n as u64;
+
+            if self.written > MAX_EXTRACTED_BYTES {
+                bail!(
+                    "Archive expands beyond the {} GiB limit",
+                    MAX_EXTRACTED_BYTES / (1024 * 1024 * 1024)
+                );
+            }
+            if self.written > RATIO_CHECK_THRESHOLD
+                && self.written > self.archive_size.saturating_mul(MAX_COMPRESSION_RATIO)
+            {
+                bail!(
+                    "Archive compression ratio exceeds {}:1, refusing to extract",
+                    MAX_COMPRESSION_RATIO
+                );
+            }
+
+            writer.write_all(&buf[..n]).context("Failed to write extracted file")?;
+        }
+
+        Ok(copied)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_zip(path: &Path, entries: &[(&str, &[u8])]) {
+        let file = File::create(path).unwrap();
+        let mut zip = zip::ZipWriter::new(file);
+        let options = zip::write::SimpleFileOptions::default();
+        for (name, data) in entries {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn test_safe_entry_path_rejects_zip_slip() {
+        assert!(safe_entry_path("../evil.txt").is_err());
+        assert!(safe_entry_path("a/../../evil.txt").is_err());
+        assert!(safe_entry_path("/etc/passwd").is_err());
+        assert!(safe_entry_path("C:\\Windows\\x.dll").is_err());
+        assert_eq!(
+            safe_entry_path("docs/./a.txt").unwrap(),
+            PathBuf::from("docs").join("a.txt")
+        );
+    }
+
+    #[test]
+    fn test_extract_zip_reports_files() {
+        let temp = temp_dir("deskcraft_extract_zip_test");
+        let archive = temp.join("bundle.zip");
+        write_zip(&archive, &[("a.txt", b"hello"), ("sub/b.txt", b"world")]);
+
+        let dest = temp.join("out");
+        let mut seen = Vec::new();
+        let count = extract_archive(&archive, &dest, "suffix", &mut |p, size| {
+            seen.push((p.to_path_buf(), size));
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(count, 2);
+        assert_eq!(fs::read_to_string(dest.join("a.txt")).unwrap(), "hello");
+        assert_eq!(fs::read_to_string(dest.join("sub").join("b.txt")).unwrap(), "world");
+        assert_eq!(seen.len(), 2);
+
+        let _ = fs::remove_dir_all(&temp);
+    }
+
+    #[test]
+    fn test_extract_rejects_zip_slip_entry() {
+        let temp = temp_dir("deskcraft_extract_slip_test");
+        let archive = temp.join("evil.zip");
+        write_zip(&archive, &[("../escaped.txt", b"x")]);
+
+        let dest = temp.join("out");
+        assert!(extract_archive(&archive, &dest, "suffix", &mut |_, _| Ok(())).is_err());
+        assert!(!temp.join("escaped.txt").exists());
+
+        let _ = fs::remove_dir_all(&temp);
+    }
+
+    #[test]
+    fn test_add_to_archive_appends_and_restores() {
+        let temp = temp_dir("deskcraft_add_archive_test");
+        let a = temp.join("a.txt");
+        let b = temp.join("b.txt");
+        fs::write(&a, "first").unwrap();
+        fs::write(&b, "second").unwrap();
+        let missing = temp.join("missing.txt");
+
+        let archive = temp.join("Arquivo").join("2025-01.zip");
+        let names = |results: Vec<Result<Option<String>>>| -> Vec<Option<String>> {
+            results.into_iter().map(|r| r.unwrap()).collect()
+        };
+        let some = |name: &str| Some(name.to_string());
+
+        // One pass adds several files; an unreadable one is left out alone
+        let results =
+            add_to_archive(&archive, &[(&a, "suffix"), (&missing, "suffix"), (&b, "suffix")])
+                .unwrap();
+        assert!(results[1].is_err());
+        assert_eq!(
+            names(results.into_iter().filter(|r| r.is_ok()).collect()),
+            [some("a.txt"), some("b.txt")]
+        );
+        // Same name again gets a suffix inside the archive, also within a pass
+        let results = add_to_archive(&archive, &[(&a, "suffix"), (&a, "suffix"), (&a, "skip")]);
+        assert_eq!(names(results.unwrap()), [some("a_1.txt"), some("a_2.txt"), None]);
+
+        // A failed write leaves the archive untouched and no copy behind
+        let before = fs::read(&archive).unwrap();
+        fs::create_dir_all(archive.with_extension("zip.tmp")).unwrap();
+        assert!(add_to_archive(&archive, &[(&b, "suffix")]).is_err());
+        assert_eq!(fs::read(&archive).unwrap(), before);
+        fs::remove_dir_all(archive.with_extension("zip.tmp")).unwrap();
+
+        let restored = temp.join("restored.txt");
+        restore_from_archive(&archive, "b.txt", &restored).unwrap();
+        assert_eq!(fs::read_to_string(&restored).unwrap(), "second");
+
+        let zip = zip::ZipArchive::new(File::open(&archive).unwrap()).unwrap();
+        let names: Vec<&str> = zip.file_names().collect();
+        assert_eq!(names.len(), 3);
+        assert!(!names.contains(&"b.txt"));
+
+        let _ = fs::remove_dir_all(&temp);
+    }
+
+    #[test]
+    fn test_budget_enforces_ratio() {
+        let mut budget = ExtractionBudget::new(1);
+        let data = vec![0u8; (RATIO_CHECK_THRESHOLD + 1) as usize];
+        let mut sink = Vec::new();
+        assert!(budget.copy(&mut Cursor::new(data), &mut sink).is_err());
+    }
+}
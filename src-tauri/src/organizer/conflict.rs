@@ -1,4 +1,21 @@
+use anyhow::Result;
+use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::organizer::integrity;
+
+/// What to do with a file whose destination is already taken.
+#[derive(Debug, PartialEq)]
+pub enum Resolution {
+    /// Move the file to this (free) path instead.
+    Use(PathBuf),
+    /// Replace the existing file. It is moved aside to `backup` first so the
+    /// replacement can be rolled back.
+    Replace { backup: PathBuf },
+    /// Leave the file where it is.
+    Skip(Option<String>),
+}
 
 /// Resolves a file conflict at the given path based on the specified strategy.
 ///
@@ -6,21 +23,127 @@ use std::path::{Path, PathBuf};
 /// - `"suffix"`: Appends `_1`, `_2`, etc. to the filename until no conflict exists.
 /// - `"conflict_folder"`: Moves the file into a `Conflicts/` subfolder in the same directory.
 /// - `"skip"`: Returns the original path unchanged (caller should check and skip).
+///
+/// The strategies that compare the incoming file with the existing one (see
+/// [`resolve`]) fall back to `suffix` here, where there is no incoming file.
 pub fn resolve_conflict(path: &Path, strategy: &str) -> PathBuf {
+    resolve_path(path, strategy, &|p: &Path| p.exists())
+}
+
+/// [`resolve_conflict`] against any view of which paths are taken.
+pub(crate) fn resolve_path(
+    path: &Path,
+    strategy: &str,
+    exists: &dyn Fn(&Path) -> bool,
+) -> PathBuf {
     match strategy {
-        "suffix" => resolve_suffix(path),
-        "conflict_folder" => resolve_conflict_folder(path),
+        "suffix" => resolve_suffix(path, exists),
+        "conflict_folder" => resolve_conflict_folder(path, exists),
         "skip" => path.to_path_buf(),
+        "overwrite_if_newer" | "overwrite_if_larger" | "keep_both_by_date" | "skip_if_identical" => {
+            resolve_suffix(path, exists)
+        }
         _ => {
             log::warn!("Unknown conflict strategy '{}', defaulting to suffix", strategy);
-            resolve_suffix(path)
+            resolve_suffix(path, exists)
         }
     }
 }
 
+/// Resolves the conflict between `source` and the existing file at `dest`.
+///
+/// On top of the path-only strategies of [`resolve_conflict`]:
+/// - `"overwrite_if_newer"`: Replaces the existing file if the source was modified later, else skips.
+/// - `"overwrite_if_larger"`: Replaces the existing file if the source is larger, else skips.
+/// - `"keep_both_by_date"`: Keeps both, naming the source after its modification date
+///   (`report_2025-03-14.pdf`).
+/// - `"skip_if_identical"`: Skips when both files have the same SHA-256, else suffixes.
+///
+/// Fails only if the files cannot be read for comparison.
+pub fn resolve(source: &Path, dest: &Path, strategy: &str) -> Result<Resolution> {
+    resolve_with(source, dest, strategy, &|p: &Path| {
+        p.exists().then(|| p.to_path_buf())
+    })
+}
+
+/// [`resolve`] against a planned state of the filesystem, as the simulator
+/// sees it. `occupant` returns the real file whose content will be at a path
+/// by then, or `None` if the path will be free.
+pub fn resolve_with(
+    source: &Path,
+    dest: &Path,
+    strategy: &str,
+    occupant: &dyn Fn(&Path) -> Option<PathBuf>,
+) -> Result<Resolution> {
+    let exists = |p: &Path| occupant(p).is_some();
+    let existing = occupant(dest).unwrap_or_else(|| dest.to_path_buf());
+
+    let resolution = match strategy {
+        "skip" => Resolution::Skip(None),
+        "overwrite_if_newer" => {
+            if modified(source)? > modified(&existing)? {
+                Resolution::Replace {
+                    backup: resolve_conflict_folder(dest, &exists),
+                }
+            } else {
+                Resolution::Skip(Some("Existing file is newer or as recent".to_string()))
+            }
+        }
+        "overwrite_if_larger" => {
+            if fs::metadata(source)?.len() > fs::metadata(&existing)?.len() {
+                Resolution::Replace {
+                    backup: resolve_conflict_folder(dest, &exists),
+                }
+            } else {
+                Resolution::Skip(Some("Existing file is larger or the same size".to_string()))
+            }
+        }
+        "keep_both_by_date" => Resolution::Use(resolve_by_date(dest, modified(source)?, &exists)),
+        "skip_if_identical" => {
+            let same_size = fs::metadata(source)?.len() == fs::metadata(&existing)?.len();
+            if same_size
+                && integrity::sha256_file(source)?.0 == integrity::sha256_file(&existing)?.0
+            {
+                Resolution::Skip(Some("Identical file already at destination".to_string()))
+            } else {
+                Resolution::Use(resolve_suffix(dest, &exists))
+            }
+        }
+        _ => Resolution::Use(resolve_path(dest, strategy, &exists)),
+    };
+    Ok(resolution)
+}
+
+fn modified(path: &Path) -> Result<SystemTime> {
+    Ok(fs::metadata(path)?.modified()?)
+}
+
+/// Appends a date (`_2025-03-14`) to the file stem, then a numeric suffix if
+/// that name is taken as well.
+fn resolve_by_date(path: &Path, date: SystemTime, exists: &dyn Fn(&Path) -> bool) -> PathBuf {
+    let parent = path.parent().unwrap_or_else(|| Path::new("."));
+    let stem = path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let ext = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    let date = chrono::DateTime::<chrono::Local>::from(date).format("%Y-%m-%d");
+
+    let candidate = parent.join(format!("{}_{}{}", stem, date, ext));
+    if exists(&candidate) {
+        resolve_suffix(&candidate, exists)
+    } else {
+        candidate
+    }
+}
+
 /// Adds a numeric suffix (`_1`, `_2`, ...) to the file stem until no file with
 /// that name exists at the target location.
-fn resolve_suffix(path: &Path) -> PathBuf {
+fn resolve_suffix(path: &Path, exists: &dyn Fn(&Path) -> bool) -> PathBuf {
     let parent = path.parent().unwrap_or_else(|| Path::new("."));
     let stem = path
         .file_stem()
@@ -36,7 +159,7 @@ fn resolve_suffix(path: &Path) -> PathBuf {
     loop {
         let new_name = format!("{}_{}{}", stem, counter, ext);
         let candidate = parent.join(&new_name);
-        if !candidate.exists() {
+        if !exists(&candidate) {
             return candidate;
         }
         counter += 1;
@@ -50,16 +173,12 @@ fn resolve_suffix(path: &Path) -> PathBuf {
 
 /// Moves the file into a `Conflicts` subfolder within the same parent directory.
 /// If a file with the same name already exists in the Conflicts folder, applies
-/// suffix resolution inside it.
-fn resolve_conflict_folder(path: &Path) -> PathBuf {
+/// suffix resolution inside it. The folder itself is created by whoever moves
+/// the file there.
+fn resolve_conflict_folder(path: &Path, exists: &dyn Fn(&Path) -> bool) -> PathBuf {
     let parent = path.parent().unwrap_or_else(|| Path::new("."));
     let conflicts_dir = parent.join("Conflicts");
 
-    // Create the Conflicts directory if needed
-    if !conflicts_dir.exists() {
-        std::fs::create_dir_all(&conflicts_dir).ok();
-    }
-
     let file_name = path
         .file_name()
         .unwrap_or_default()
@@ -68,9 +187,9 @@ fn resolve_conflict_folder(path: &Path) -> PathBuf {
 
     let candidate = conflicts_dir.join(&file_name);
 
-    if candidate.exists() {
+    if exists(&candidate) {
         // Apply suffix within the Conflicts folder
-        resolve_suffix(&candidate)
+        resolve_suffix(&candidate, exists)
     } else {
         candidate
     }
@@ -79,7 +198,7 @@ fn resolve_conflict_folder(path: &Path) -> PathBuf {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs;
+    use crate::db::test_support;
 
     #[test]
     fn test_suffix_strategy_no_conflict() {
@@ -90,7 +209,7 @@ mod tests {
         let file = temp.join("test.txt");
         fs::write(&file, "hello").unwrap();
 
-        let resolved = resolve_suffix(&file);
+        let resolved = resolve_suffix(&file, &|p: &Path| p.exists());
         assert_eq!(resolved, temp.join("test_1.txt"));
 
         let _ = fs::remove_dir_all(&temp);
@@ -102,4 +221,45 @@ mod tests {
         let resolved = resolve_conflict(path, "skip");
         assert_eq!(resolved, path.to_path_buf());
     }
+
+    #[test]
+    fn test_content_aware_strategies() {
+        let temp = test_support::temp_dir("resolve");
+        fs::create_dir_all(temp.join("in")).unwrap();
+        fs::create_dir_all(temp.join("out")).unwrap();
+
+        let source = temp.join("in").join("invoice.pdf");
+        let dest = temp.join("out").join("invoice.pdf");
+        fs::write(&source, "same content").unwrap();
+        fs::write(&dest, "same content").unwrap();
+
+        assert!(matches!(
+            resolve(&source, &dest, "skip_if_identical").unwrap(),
+            Resolution::Skip(Some(_))
+        ));
+        assert!(matches!(
+            resolve(&source, &dest, "overwrite_if_larger").unwrap(),
+            Resolution::Skip(Some(_))
+        ));
+
+        fs::write(&source, "a longer, different content").unwrap();
+        assert_eq!(
+            resolve(&source, &dest, "skip_if_identical").unwrap(),
+            Resolution::Use(temp.join("out").join("invoice_1.pdf"))
+        );
+        assert_eq!(
+            resolve(&source, &dest, "overwrite_if_larger").unwrap(),
+            Resolution::Replace {
+                backup: temp.join("out").join("Conflicts").join("invoice.pdf")
+            }
+        );
+
+        let date = chrono::Local::now().format("%Y-%m-%d");
+        assert_eq!(
+            resolve(&source, &dest, "keep_both_by_date").unwrap(),
+            Resolution::Use(temp.join("out").join(format!("invoice_{}.pdf", date)))
+        );
+
+        let _ = fs::remove_dir_all(&temp);
+    }
 }
@@ -0,0 +1,99 @@
+use std::io::{self, ErrorKind};
+
+/// Categories recorded as `error_code` on failed run items.
+pub const PERMISSION_DENIED: &str = "permission_denied";
+pub const NOT_FOUND: &str = "not_found";
+pub const DISK_FULL: &str = "disk_full";
+pub const CROSS_DEVICE: &str = "cross_device";
+pub const LOCKED: &str = "locked";
+pub const PATH_TOO_LONG: &str = "path_too_long";
+/// The simulation rejected the destination, so the item never ran.
+pub const UNSAFE_DESTINATION: &str = "unsafe_destination";
+pub const OTHER: &str = "other";
+
+/// Categorizes a failure by the first I/O error in its chain of causes.
+pub fn classify(err: &anyhow::Error) -> &'static str {
+    err.chain()
+        .find_map(|cause| cause.downcast_ref::<io::Error>())
+        .map(classify_io)
+        .unwrap_or(OTHER)
+}
+
+/// Categorizes an I/O error by its OS error code, or by its kind.
+pub fn classify_io(err: &io::Error) -> &'static str {
+    if let Some(code) = err.raw_os_error().and_then(classify_os_error) {
+        return code;
+    }
+    match err.kind() {
+        ErrorKind::PermissionDenied | ErrorKind::ReadOnlyFilesystem => PERMISSION_DENIED,
+        ErrorKind::NotFound => NOT_FOUND,
+        ErrorKind::StorageFull | ErrorKind::QuotaExceeded | ErrorKind::FileTooLarge => DISK_FULL,
+        ErrorKind::CrossesDevices => CROSS_DEVICE,
+        ErrorKind::ResourceBusy | ErrorKind::ExecutableFileBusy => LOCKED,
+        ErrorKind::InvalidFilename => PATH_TOO_LONG,
+        _ => OTHER,
+    }
+}
+
+/// Windows codes the standard library has no specific kind for.
+#[cfg(target_os = "windows")]
+fn classify_os_error(code: i32) -> Option<&'static str> {
+    match code {
+        // ERROR_SHARING_VIOLATION, ERROR_LOCK_VIOLATION
+        32 | 33 => Some(LOCKED),
+        // ERROR_HANDLE_DISK_FULL, ERROR_DISK_FULL
+        39 | 112 => Some(DISK_FULL),
+        // ERROR_NOT_SAME_DEVICE
+        17 => Some(CROSS_DEVICE),
+        // ERROR_FILENAME_EXCED_RANGE
+        206 => Some(PATH_TOO_LONG),
+        _ => None,
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn classify_os_error(_code: i32) -> Option<&'static str> {
+    None
+}
+
+/// One-line summary of a run's failures, e.g.
+/// `3 item(s) failed: permission_denied (2), not_found (1)`.
+pub fn summarize(counts: &[(String, u32)]) -> Option<String> {
+    let total: u32 = counts.iter().map(|(_, n)| n).sum();
+    if total == 0 {
+        return None;
+    }
+    let parts: Vec<String> = counts
+        .iter()
+        .map(|(code, n)| format!("{} ({})", code, n))
+        .collect();
+    Some(format!("{} item(s) failed: {}", total, parts.join(", ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_classify_follows_the_cause_chain() {
+        let missing = std::fs::read("/nonexistent/deskcraft/file.txt")
+            .context("Failed to read file")
+            .unwrap_err();
+        assert_eq!(classify(&missing), NOT_FOUND);
+
+        let denied = io::Error::from(ErrorKind::PermissionDenied);
+        assert_eq!(classify(&anyhow::Error::new(denied).context("move")), PERMISSION_DENIED);
+        assert_eq!(classify(&anyhow::anyhow!("checksum mismatch")), OTHER);
+    }
+
+    #[test]
+    fn test_summarize_counts_failures() {
+        let counts = vec![("locked".to_string(), 2), ("not_found".to_string(), 1)];
+        assert_eq!(
+            summarize(&counts).unwrap(),
+            "3 item(s) failed: locked (2), not_found (1)"
+        );
+        assert_eq!(summarize(&[]), None);
+    }
+}
@@ -1,172 +1,936 @@
 use anyhow::{Context, Result};
 use rusqlite::Connection;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
-use crate::db::models::{ExecutionResult, SimulationResult};
-use crate::db::queries::runs;
-use crate::organizer::conflict;
+use crate::db::models::{
+    DriftedItem, ExecutionResult, FileEntry, OrganizerProgress, SimulationItem, SimulationResult,
+};
+use crate::db::queries::{runs, shortcuts};
+use crate::organizer::{
+    archive,
+    conflict::{self, Resolution},
+    errors, integrity, link,
+    parallel::{self, WorkerPool},
+    progress::RunControl,
+    scanner,
+};
+
+/// Result of executing a single simulation item.
+enum Outcome {
+    Done,
+    Skipped,
+    Failed(String),
+    Drifted(DriftedItem),
+}
+
+/// A journaled move whose filesystem part can run on any thread.
+struct MoveJob {
+    journal_id: String,
+    action_type: String,
+    source: PathBuf,
+    dest: PathBuf,
+    verify: bool,
+    /// Where the existing file at `dest` goes before it is replaced.
+    replace: Option<PathBuf>,
+}
+
+/// Where a move ended up, the link it left behind (link path, target) and the
+/// checksum of the copy if it was verified.
+struct Performed {
+    dest: PathBuf,
+    link: Option<(PathBuf, PathBuf)>,
+    checksum: Option<String>,
+}
+
+/// What planning an item produced.
+enum Prepared {
+    /// The item was settled without a move (skipped, failed, extracted, ...).
+    Finished(Outcome),
+    /// The item was journaled and its move can be performed.
+    Ready(MoveJob),
+    /// The destination is claimed by a move still in flight.
+    Blocked,
+}
 
 /// Executes a simulation result, actually moving files and recording each action
 /// in the database as run items.
+///
+/// Run items double as a write-ahead journal: an item is recorded as `pending`
+/// before the filesystem is touched and finished as `completed` or `error`
+/// afterwards. Failing to write the journal aborts the run instead of moving
+/// files the database doesn't know about. Runs left `running` by a crash are
+/// reconciled at startup (see [`crate::organizer::recovery`]).
+///
+/// Cross-device moves that don't interact with other items are copied on a
+/// small worker pool (see [`crate::organizer::parallel`]); everything else
+/// runs in order. The calling thread stays the only database writer, and the
+/// result is the same as running every item sequentially.
+///
+/// With `verify`, files copied across devices are compared with the original
+/// by size and SHA-256 before the original is removed; the checksum is stored
+/// on the run item. A copy that doesn't match is discarded and the item fails.
+///
+/// Progress is reported through `control` after each item. A cancel request is
+/// honoured between items: the remaining ones are left untouched and the run
+/// ends `cancelled`, with everything done so far still rollbackable.
 pub fn execute(
     conn: &Connection,
     simulation: &SimulationResult,
     run_id: &str,
     conflict_strategy: &str,
+    verify: bool,
+    control: &RunControl,
 ) -> Result<ExecutionResult> {
+    let parallel = parallel::eligible_items(&simulation.items, parallel::is_cross_device);
+    execute_planned(conn, simulation, run_id, conflict_strategy, verify, control, &parallel)
+}
+
+/// Executes the items, running the ones flagged in `parallel` on the pool.
+#[allow(clippy::too_many_arguments)]
+fn execute_planned(
+    conn: &Connection,
+    simulation: &SimulationResult,
+    run_id: &str,
+    conflict_strategy: &str,
+    verify: bool,
+    control: &RunControl,
+    parallel: &[bool],
+) -> Result<ExecutionResult> {
+    let started = Instant::now();
+    let items = &simulation.items;
+    let mut outcomes: Vec<Option<Outcome>> = items.iter().map(|_| None).collect();
+    let archive_batches = archive_batches(items);
+    let mut cancelled = false;
+
+    let mut progress = OrganizerProgress {
+        run_id: run_id.to_string(),
+        phase: "execute".to_string(),
+        done: 0,
+        total: items.len() as u32,
+        bytes_done: 0,
+        bytes_total: items.iter().map(|i| i.file.size).sum(),
+        current_file: String::new(),
+    };
+
+    std::thread::scope(|scope| -> Result<()> {
+        let mut pool = parallel
+            .iter()
+            .any(|p| *p)
+            .then(|| WorkerPool::start(scope, parallel::MAX_WORKERS, perform));
+        // Destinations of moves still in flight, which later items must not take
+        let mut claimed: HashSet<PathBuf> = HashSet::new();
+
+        for (index, item) in items.iter().enumerate() {
+            // Settled with an earlier item's archive batch
+            if outcomes[index].is_some() {
+                continue;
+            }
+            if control.is_cancelled() {
+                log::info!("Run {} cancelled after {} item(s)", run_id, progress.done);
+                cancelled = true;
+                break;
+            }
+
+            progress.current_file = item.file.path.clone();
+            control.report(&progress);
+
+            let pooled = if parallel[index] { pool.as_mut() } else { None };
+            match pooled {
+                Some(pool) => {
+                    let prepared = loop {
+                        match prepare_item(conn, run_id, item, conflict_strategy, verify, &claimed)? {
+                            Prepared::Blocked => settle_moves(
+                                conn,
+                                items,
+                                pool.wait_all(),
+                                &mut outcomes,
+                                &mut claimed,
+                                &mut progress,
+                            )?,
+                            prepared => break prepared,
+                        }
+                    };
+                    match prepared {
+                        Prepared::Ready(job) => {
+                            claimed.insert(job.dest.clone());
+                            pool.submit(index, job);
+                        }
+                        Prepared::Finished(outcome) => {
+                            outcomes[index] = Some(outcome);
+                            progress.done += 1;
+                            progress.bytes_done += item.file.size;
+                        }
+                        Prepared::Blocked => unreachable!("blocked items are retried above"),
+                    }
+                    settle_moves(
+                        conn,
+                        items,
+                        pool.finished(),
+                        &mut outcomes,
+                        &mut claimed,
+                        &mut progress,
+                    )?;
+                }
+                None => {
+                    // Sequential items see the filesystem exactly as a fully
+                    // sequential run would, so earlier moves land first
+                    if let Some(pool) = pool.as_mut() {
+                        settle_moves(
+                            conn,
+                            items,
+                            pool.wait_all(),
+                            &mut outcomes,
+                            &mut claimed,
+                            &mut progress,
+                        )?;
+                    }
+                    if let Some(batch) = archive_batches.get(&index) {
+                        let batch_items: Vec<&SimulationItem> =
+                            batch.iter().map(|&i| &items[i]).collect();
+                        let settled =
+                            execute_archive(conn, run_id, &batch_items, conflict_strategy)?;
+                        for (&i, outcome) in batch.iter().zip(settled) {
+                            outcomes[i] = Some(outcome);
+                            progress.done += 1;
+                            progress.bytes_done += items[i].file.size;
+                        }
+                        continue;
+                    }
+                    outcomes[index] =
+                        Some(execute_item(conn, run_id, item, conflict_strategy, verify)?);
+                    progress.done += 1;
+                    progress.bytes_done += item.file.size;
+                }
+            }
+        }
+
+        // Moves already started always finish, even after a cancel
+        if let Some(pool) = pool.as_mut() {
+            settle_moves(
+                conn,
+                items,
+                pool.wait_all(),
+                &mut outcomes,
+                &mut claimed,
+                &mut progress,
+            )?;
+        }
+        Ok(())
+    })?;
+
+    progress.current_file.clear();
+    control.report(&progress);
+
+    // Fold outcomes in item order so counts and messages match a sequential run
     let mut moved: u32 = 0;
     let mut skipped: u32 = 0;
     let mut errors: u32 = 0;
     let mut error_messages: Vec<String> = Vec::new();
+    let mut drifted: Vec<DriftedItem> = Vec::new();
 
-    for item in &simulation.items {
-        if item.destination.is_empty() {
-            skipped += 1;
-            runs::create_run_item(
-                conn,
-                run_id,
-                Some(&item.rule_id),
-                &item.file.path,
-                "",
-                item.file.size as i64,
-                &item.action_type,
-                "skipped",
-                conflict_strategy,
-            )
-            .ok();
-            continue;
+    for outcome in outcomes.into_iter().flatten() {
+        match outcome {
+            Outcome::Done => moved += 1,
+            Outcome::Skipped => skipped += 1,
+            Outcome::Drifted(d) => {
+                log::warn!("Skipped {}: {}", d.path, d.reason);
+                drifted.push(d);
+                skipped += 1;
+            }
+            Outcome::Failed(msg) => {
+                log::error!("{}", msg);
+                error_messages.push(msg);
+                errors += 1;
+            }
         }
+    }
 
-        let source = Path::new(&item.file.path);
-        let mut dest = std::path::PathBuf::from(&item.destination);
+    // Update the run record with final counts
+    let status = if cancelled {
+        "cancelled"
+    } else {
+        run_status(moved, errors)
+    };
+    runs::update_run_status(conn, run_id, status, moved as i32, skipped as i32, errors as i32)?;
+    record_run_errors(conn, run_id)?;
+    runs::add_run_duration(conn, run_id, started.elapsed().as_millis() as i64)?;
 
-        // If the destination is a directory, place the file inside it
-        if dest.is_dir() || item.destination.ends_with('/') || item.destination.ends_with('\\') {
-            dest = dest.join(&item.file.name);
+    Ok(ExecutionResult {
+        run_id: run_id.to_string(),
+        total: moved + skipped + errors,
+        moved,
+        skipped,
+        errors,
+        error_messages,
+        drifted,
+        cancelled,
+    })
+}
+
+/// Stores a summary of the run's failed items, by error code, as the run's
+/// error message. Clears it when no item failed.
+pub fn record_run_errors(conn: &Connection, run_id: &str) -> Result<()> {
+    let counts = runs::count_run_errors(conn, run_id)?;
+    runs::set_run_error(conn, run_id, errors::summarize(&counts).as_deref())
+}
+
+/// Final status of a run given how many items succeeded and failed.
+pub fn run_status(moved: u32, errors: u32) -> &'static str {
+    if errors > 0 && moved == 0 {
+        "error"
+    } else if errors > 0 {
+        "completed_with_errors"
+    } else {
+        "completed"
+    }
+}
+
+/// Executes a single item. Filesystem failures are recorded on the item and
+/// returned as [`Outcome::Failed`]; only journal (database) failures are errors.
+fn execute_item(
+    conn: &Connection,
+    run_id: &str,
+    item: &SimulationItem,
+    conflict_strategy: &str,
+    verify: bool,
+) -> Result<Outcome> {
+    match prepare_item(conn, run_id, item, conflict_strategy, verify, &HashSet::new())? {
+        Prepared::Finished(outcome) => Ok(outcome),
+        Prepared::Ready(job) => {
+            let result = perform(&job);
+            complete_move(conn, item, &job, result)
         }
+        Prepared::Blocked => unreachable!("nothing is claimed when running sequentially"),
+    }
+}
 
-        // Handle conflicts
-        if dest.exists() {
-            dest = conflict::resolve_conflict(&dest, conflict_strategy);
-            if conflict_strategy == "skip" && dest.exists() {
-                skipped += 1;
-                runs::create_run_item(
+/// Plans an item up to the point where only the move itself is left: the
+/// destination is resolved, journaled and its folder created. Items that need
+/// no move, or are not plain moves (extract, archive), are settled here.
+/// Returns [`Prepared::Blocked`] without journaling if the destination is one
+/// of the `claimed` paths still being written.
+fn prepare_item(
+    conn: &Connection,
+    run_id: &str,
+    item: &SimulationItem,
+    conflict_strategy: &str,
+    verify: bool,
+    claimed: &HashSet<PathBuf>,
+) -> Result<Prepared> {
+    // Archive items run their own checks, as they usually go in batches
+    if item.action_type == "archive" {
+        let mut settled = execute_archive(conn, run_id, &[item], conflict_strategy)?;
+        return Ok(Prepared::Finished(settled.remove(0)));
+    }
+
+    let conflict_strategy = strategy_for(item, conflict_strategy);
+    if let Some(outcome) = settle_unrunnable(conn, run_id, item, conflict_strategy)? {
+        return Ok(Prepared::Finished(outcome));
+    }
+
+    if item.action_type == "extract" {
+        return execute_extract(conn, run_id, item, conflict_strategy, verify)
+            .map(Prepared::Finished);
+    }
+
+    let mut dest = PathBuf::from(&item.destination);
+
+    // If the destination is a directory, place the file inside it
+    if dest.is_dir() || item.destination.ends_with('/') || item.destination.ends_with('\\') {
+        dest = dest.join(&item.file.name);
+    }
+
+    // Handle conflicts. A path still being written counts as taken, so wait
+    // for it instead of resolving against a half-finished file.
+    if claimed.contains(&dest) {
+        return Ok(Prepared::Blocked);
+    }
+    let mut replace = None;
+    if dest.exists() {
+        let source = Path::new(&item.file.path);
+        let resolution = match conflict::resolve(source, &dest, conflict_strategy) {
+            Ok(resolution) => resolution,
+            Err(e) => {
+                let msg = format!(
+                    "Failed to compare {} with {}: {:#}",
+                    item.file.path,
+                    dest.display(),
+                    e
+                );
+                let dest = dest.to_string_lossy();
+                let code = errors::classify(&e);
+                fail(conn, run_id, item, &dest, code, &msg, conflict_strategy)?;
+                return Ok(Prepared::Finished(Outcome::Failed(msg)));
+            }
+        };
+        match resolution {
+            Resolution::Use(path) => {
+                dest = path;
+                if claimed.contains(&dest) {
+                    return Ok(Prepared::Blocked);
+                }
+            }
+            Resolution::Replace { backup } => replace = Some(backup),
+            Resolution::Skip(reason) => {
+                record(
                     conn,
                     run_id,
-                    Some(&item.rule_id),
-                    &item.file.path,
+                    item,
                     &dest.to_string_lossy(),
-                    item.file.size as i64,
-                    &item.action_type,
                     "skipped",
+                    reason.as_deref(),
                     conflict_strategy,
-                )
-                .ok();
-                continue;
+                )?;
+                return Ok(Prepared::Finished(Outcome::Skipped));
             }
         }
+    }
 
-        // Ensure destination directory exists
-        if let Some(parent) = dest.parent() {
-            if let Err(e) = fs::create_dir_all(parent) {
-                let msg = format!(
-                    "Failed to create directory {}: {}",
-                    parent.display(),
-                    e
-                );
-                log::error!("{}", msg);
-                error_messages.push(msg);
-                errors += 1;
+    // Journal the resolved destination before touching the filesystem
+    let journal_id = journal(
+        conn,
+        run_id,
+        item,
+        &item.action_type,
+        &dest.to_string_lossy(),
+        conflict_strategy,
+    )?;
+    if let Some(backup) = &replace {
+        runs::set_run_item_replaced_path(conn, &journal_id, &backup.to_string_lossy())?;
+    }
 
-                runs::create_run_item(
+    // Ensure destination directory exists
+    if let Some(parent) = dest.parent() {
+        if let Err(e) = fs::create_dir_all(parent) {
+            let msg = format!("Failed to create directory {}: {}", parent.display(), e);
+            let code = errors::classify_io(&e);
+            runs::fail_run_item(conn, &journal_id, &dest.to_string_lossy(), code, &msg)?;
+            return Ok(Prepared::Finished(Outcome::Failed(msg)));
+        }
+    }
+
+    Ok(Prepared::Ready(MoveJob {
+        journal_id,
+        action_type: item.action_type.clone(),
+        source: PathBuf::from(&item.file.path),
+        dest,
+        verify,
+        replace,
+    }))
+}
+
+/// Moves the file, or links it. Touches only the filesystem, so it is safe to
+/// run on a worker thread. `link` records the link as destination;
+/// `move_and_link` records the moved file and tracks the link left behind.
+fn perform(job: &MoveJob) -> Result<Performed> {
+    if let Some(backup) = &job.replace {
+        set_aside(&job.dest, backup)?;
+    }
+
+    let result = match job.action_type.as_str() {
+        "link" => link::create_link(&job.source, &job.dest).map(|created| Performed {
+            dest: created.clone(),
+            link: Some((created, job.source.clone())),
+            checksum: None,
+        }),
+        "move_and_link" => {
+            move_and_link(&job.source, &job.dest, job.verify).map(|(created, checksum)| Performed {
+                dest: job.dest.clone(),
+                link: Some((created, job.dest.clone())),
+                checksum,
+            })
+        }
+        _ => move_file(&job.source, &job.dest, job.verify).map(|checksum| Performed {
+            dest: job.dest.clone(),
+            link: None,
+            checksum,
+        }),
+    };
+
+    // A failed move must not lose the file it was going to replace
+    if let (Err(_), Some(backup)) = (&result, &job.replace) {
+        if let Err(e) = fs::rename(backup, &job.dest) {
+            log::error!(
+                "Failed to put back replaced file {} -> {}: {}",
+                backup.display(),
+                job.dest.display(),
+                e
+            );
+        }
+    }
+    result
+}
+
+/// Moves the file about to be replaced out of the way, keeping it for rollback.
+fn set_aside(existing: &Path, backup: &Path) -> Result<()> {
+    if let Some(parent) = backup.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+    }
+    fs::rename(existing, backup).with_context(|| {
+        format!("Failed to move replaced file {} to {}", existing.display(), backup.display())
+    })
+}
+
+/// The conflict strategy for an item: its action's own, or the run's default.
+fn strategy_for<'a>(item: &'a SimulationItem, default: &'a str) -> &'a str {
+    if item.conflict_strategy.is_empty() {
+        default
+    } else {
+        &item.conflict_strategy
+    }
+}
+
+/// Finishes the journal entry of a performed move.
+fn complete_move(
+    conn: &Connection,
+    item: &SimulationItem,
+    job: &MoveJob,
+    result: Result<Performed>,
+) -> Result<Outcome> {
+    match result {
+        Ok(performed) => {
+            if let Some((link_path, target)) = performed.link {
+                shortcuts::create_link_shortcut(
                     conn,
-                    run_id,
-                    Some(&item.rule_id),
-                    &item.file.path,
-                    &dest.to_string_lossy(),
-                    item.file.size as i64,
-                    &item.action_type,
-                    "error",
-                    conflict_strategy,
-                )
-                .ok();
-                continue;
+                    &job.journal_id,
+                    &link_path.to_string_lossy(),
+                    &target.to_string_lossy(),
+                )?;
+            }
+            if let Some(checksum) = &performed.checksum {
+                runs::set_run_item_checksum(conn, &job.journal_id, checksum)?;
             }
+            // Lets rollback notice the file was edited after the run
+            if item.action_type != "link" {
+                if let Ok(stamp) = integrity::mtime_stamp(&performed.dest) {
+                    runs::set_run_item_dest_mtime(conn, &job.journal_id, &stamp)?;
+                }
+            }
+            runs::finish_run_item(
+                conn,
+                &job.journal_id,
+                "completed",
+                &performed.dest.to_string_lossy(),
+                None,
+            )?;
+            Ok(Outcome::Done)
         }
+        Err(e) => {
+            let msg = format!(
+                "Failed to {} {} -> {}: {:#}",
+                if item.action_type == "link" { "link" } else { "move" },
+                job.source.display(),
+                job.dest.display(),
+                e
+            );
+            runs::fail_run_item(
+                conn,
+                &job.journal_id,
+                &job.dest.to_string_lossy(),
+                errors::classify(&e),
+                &msg,
+            )?;
+            Ok(Outcome::Failed(msg))
+        }
+    }
+}
 
-        // Move the file
-        match move_file(source, &dest) {
-            Ok(()) => {
-                moved += 1;
-                runs::create_run_item(
-                    conn,
-                    run_id,
-                    Some(&item.rule_id),
-                    &item.file.path,
-                    &dest.to_string_lossy(),
-                    item.file.size as i64,
-                    &item.action_type,
-                    "completed",
-                    conflict_strategy,
-                )
-                .ok();
+/// Records moves that finished on the pool. Their journal entries are
+/// finished together in one transaction.
+fn settle_moves(
+    conn: &Connection,
+    items: &[SimulationItem],
+    finished: Vec<(usize, MoveJob, Result<Performed>)>,
+    outcomes: &mut [Option<Outcome>],
+    claimed: &mut HashSet<PathBuf>,
+    progress: &mut OrganizerProgress,
+) -> Result<()> {
+    if finished.is_empty() {
+        return Ok(());
+    }
+
+    let tx = conn
+        .unchecked_transaction()
+        .context("Failed to start journal transaction")?;
+    for (index, job, result) in finished {
+        claimed.remove(&job.dest);
+        let item = &items[index];
+        outcomes[index] = Some(complete_move(&tx, item, &job, result)?);
+        progress.done += 1;
+        progress.bytes_done += item.file.size;
+    }
+    tx.commit().context("Failed to commit journal transaction")
+}
+
+/// Compares a file with the size and mtime it had when it was simulated.
+/// Returns why the file no longer matches the plan, if it doesn't.
+fn drift_reason(snapshot: &FileEntry) -> Option<String> {
+    match scanner::file_entry_for_path(Path::new(&snapshot.path)) {
+        Err(_) => Some("File no longer exists".to_string()),
+        Ok(current) if current.size != snapshot.size => Some(format!(
+            "Size changed from {} to {} bytes since the simulation",
+            snapshot.size, current.size
+        )),
+        Ok(current) if current.modified_at != snapshot.modified_at => Some(format!(
+            "Modified at {} after the simulation ({})",
+            current.modified_at, snapshot.modified_at
+        )),
+        Ok(_) => None,
+    }
+}
+
+/// Writes a `pending` journal entry for an item and returns its ID.
+fn journal(
+    conn: &Connection,
+    run_id: &str,
+    item: &SimulationItem,
+    action_type: &str,
+    destination: &str,
+    conflict_strategy: &str,
+) -> Result<String> {
+    // A resumed item continues its own entry; post-extract steps get new ones
+    if let Some(id) = item.journal_id.as_deref().filter(|_| action_type == item.action_type) {
+        return runs::replan_run_item(conn, id, destination, conflict_strategy)
+            .map(|()| id.to_string())
+            .with_context(|| format!("Failed to journal {}", item.file.path));
+    }
+    runs::create_run_item(
+        conn,
+        run_id,
+        rule_ref(item),
+        &item.file.path,
+        destination,
+        item.file.size as i64,
+        action_type,
+        "pending",
+        conflict_strategy,
+    )
+    .map(|run_item| run_item.id)
+    .with_context(|| format!("Failed to journal {}", item.file.path))
+}
+
+/// The item's rule as journaled. Resumed and retried items may belong to a
+/// rule that was deleted in the meantime, which leaves `rule_id` empty.
+fn rule_ref(item: &SimulationItem) -> Option<&str> {
+    (!item.rule_id.is_empty()).then_some(item.rule_id.as_str())
+}
+
+/// Records an item that never touched the filesystem with its final status.
+fn record(
+    conn: &Connection,
+    run_id: &str,
+    item: &SimulationItem,
+    destination: &str,
+    status: &str,
+    error_message: Option<&str>,
+    conflict_strategy: &str,
+) -> Result<()> {
+    let id = journal(conn, run_id, item, &item.action_type, destination, conflict_strategy)?;
+    runs::finish_run_item(conn, &id, status, destination, error_message)
+}
+
+/// Records an item that failed before or instead of its move.
+fn fail(
+    conn: &Connection,
+    run_id: &str,
+    item: &SimulationItem,
+    destination: &str,
+    error_code: &str,
+    error_message: &str,
+    conflict_strategy: &str,
+) -> Result<()> {
+    let id = journal(conn, run_id, item, &item.action_type, destination, conflict_strategy)?;
+    runs::fail_run_item(conn, &id, destination, error_code, error_message)
+}
+
+/// Settles an item that must not run: its destination was rejected during
+/// simulation, it has none, or its file changed since it was simulated.
+/// Returns `None` for items that can go ahead.
+fn settle_unrunnable(
+    conn: &Connection,
+    run_id: &str,
+    item: &SimulationItem,
+    conflict_strategy: &str,
+) -> Result<Option<Outcome>> {
+    // Items whose destination was rejected during simulation are never executed
+    if let Some(reason) = &item.error {
+        let msg = format!("Skipped unsafe destination for {}: {}", item.file.path, reason);
+        fail(conn, run_id, item, "", errors::UNSAFE_DESTINATION, &msg, conflict_strategy)?;
+        return Ok(Some(Outcome::Failed(msg)));
+    }
+
+    if item.destination.is_empty() {
+        record(conn, run_id, item, "", "skipped", None, conflict_strategy)?;
+        return Ok(Some(Outcome::Skipped));
+    }
+
+    // Never act on a file that changed since it was simulated
+    if let Some(reason) = drift_reason(&item.file) {
+        record(conn, run_id, item, "", "skipped", Some(&reason), conflict_strategy)?;
+        return Ok(Some(Outcome::Drifted(DriftedItem {
+            path: item.file.path.clone(),
+            reason,
+        })));
+    }
+
+    Ok(None)
+}
+
+/// Groups the archive items by destination `.zip`, so each archive is
+/// rewritten once per run rather than once per file. Returns each batch under
+/// the index of its first item, where it runs. Items whose file has other
+/// actions in the plan go alone, keeping their place among those actions.
+fn archive_batches(items: &[SimulationItem]) -> HashMap<usize, Vec<usize>> {
+    let mut actions: HashMap<&str, usize> = HashMap::new();
+    for item in items {
+        *actions.entry(item.file.path.as_str()).or_default() += 1;
+    }
+
+    let mut batches: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut open: HashMap<&str, usize> = HashMap::new();
+    for (index, item) in items.iter().enumerate() {
+        if item.action_type != "archive" {
+            continue;
+        }
+        let lead = if actions[item.file.path.as_str()] == 1 {
+            *open.entry(item.destination.as_str()).or_insert(index)
+        } else {
+            index
+        };
+        batches.entry(lead).or_default().push(index);
+    }
+    batches
+}
+
+/// Extracts an archive item into its destination folder. Every extracted file
+/// is recorded as an `extract` run item as soon as it is written, so a failure
+/// halfway through still leaves rollback able to remove what was created.
+/// Afterwards the archive is kept, deleted or moved according to the item's
+/// `post_action`.
+fn execute_extract(
+    conn: &Connection,
+    run_id: &str,
+    item: &SimulationItem,
+    conflict_strategy: &str,
+    verify: bool,
+) -> Result<Outcome> {
+    let source = Path::new(&item.file.path);
+    let dest_dir = Path::new(&item.destination);
+
+    let mut journal_failed = false;
+    let result = archive::extract_archive(source, dest_dir, conflict_strategy, &mut |path, size| {
+        runs::create_run_item(
+            conn,
+            run_id,
+            rule_ref(item),
+            &item.file.path,
+            &path.to_string_lossy(),
+            size as i64,
+            "extract",
+            "completed",
+            conflict_strategy,
+        )
+        .and_then(|run_item| {
+            // Lets rollback notice the extracted file was edited afterwards
+            let stamp = integrity::mtime_stamp(path)?;
+            runs::set_run_item_dest_mtime(conn, &run_item.id, &stamp)
+        })
+        .inspect_err(|_| journal_failed = true)
+    });
+
+    match result {
+        Ok(count) => log::info!("Extracted {} file(s) from {}", count, item.file.path),
+        Err(e) if journal_failed => return Err(e),
+        Err(e) => {
+            let msg = format!("Failed to extract {}: {:#}", item.file.path, e);
+            let code = errors::classify(&e);
+            fail(conn, run_id, item, &item.destination, code, &msg, conflict_strategy)?;
+            return Ok(Outcome::Failed(msg));
+        }
+    }
+
+    match item.post_action.as_str() {
+        "delete" => {
+            let journal_id = journal(conn, run_id, item, "delete", "", conflict_strategy)?;
+            if let Err(e) = fs::remove_file(source) {
+                let msg = format!("Failed to delete archive {}: {}", source.display(), e);
+                runs::fail_run_item(conn, &journal_id, "", errors::classify_io(&e), &msg)?;
+                return Ok(Outcome::Failed(msg));
             }
-            Err(e) => {
-                let msg = format!(
-                    "Failed to move {} -> {}: {}",
-                    source.display(),
-                    dest.display(),
-                    e
-                );
-                log::error!("{}", msg);
-                error_messages.push(msg);
-                errors += 1;
+            runs::finish_run_item(conn, &journal_id, "completed", "", None)?;
+        }
+        "move" => {
+            let mut dest = Path::new(&item.post_destination).join(&item.file.name);
+            if dest.exists() {
+                dest = conflict::resolve_conflict(&dest, conflict_strategy);
+            }
+            if !dest.exists() {
+                let dest_str = dest.to_string_lossy().to_string();
+                let journal_id = journal(conn, run_id, item, "move", &dest_str, conflict_strategy)?;
+                let moved = match dest.parent() {
+                    Some(parent) => fs::create_dir_all(parent)
+                        .with_context(|| format!("Failed to create directory {}", parent.display())),
+                    None => Ok(()),
+                }
+                .and_then(|()| move_file(source, &dest, verify));
 
-                runs::create_run_item(
-                    conn,
-                    run_id,
-                    Some(&item.rule_id),
-                    &item.file.path,
-                    &dest.to_string_lossy(),
-                    item.file.size as i64,
-                    &item.action_type,
-                    "error",
-                    conflict_strategy,
-                )
-                .ok();
+                match moved {
+                    Ok(checksum) => {
+                        if let Some(checksum) = checksum {
+                            runs::set_run_item_checksum(conn, &journal_id, &checksum)?;
+                        }
+                    }
+                    Err(e) => {
+                        let msg = format!("Failed to move archive {}: {:#}", source.display(), e);
+                        let code = errors::classify(&e);
+                        runs::fail_run_item(conn, &journal_id, &dest_str, code, &msg)?;
+                        return Ok(Outcome::Failed(msg));
+                    }
+                }
+                runs::finish_run_item(conn, &journal_id, "completed", &dest_str, None)?;
             }
         }
+        _ => {}
     }
 
-    // Update the run record with final counts
-    let status = if errors > 0 && moved == 0 {
-        "error"
-    } else if errors > 0 {
-        "completed_with_errors"
-    } else {
-        "completed"
+    Ok(Outcome::Done)
+}
+
+/// Adds files to their shared destination `.zip` in one pass (see
+/// [`archive::add_to_archive`]) and removes each original once its stored
+/// entry has been verified. A file is skipped when an entry with the same name
+/// exists and its strategy is `skip`. Returns the outcome of each item.
+fn execute_archive(
+    conn: &Connection,
+    run_id: &str,
+    items: &[&SimulationItem],
+    conflict_strategy: &str,
+) -> Result<Vec<Outcome>> {
+    let mut outcomes: Vec<Option<Outcome>> = Vec::new();
+    // Journal IDs of the items that go into the archive, by position
+    let mut journaled: Vec<(usize, String)> = Vec::new();
+    for (index, item) in items.iter().enumerate() {
+        let strategy = strategy_for(item, conflict_strategy);
+        match settle_unrunnable(conn, run_id, item, strategy)? {
+            Some(outcome) => outcomes.push(Some(outcome)),
+            None => {
+                let journal_id =
+                    journal(conn, run_id, item, "archive", &item.destination, strategy)?;
+                journaled.push((index, journal_id));
+                outcomes.push(None);
+            }
+        }
+    }
+    let archive_path = match journaled.first() {
+        Some((first, _)) => Path::new(&items[*first].destination),
+        None => return Ok(outcomes.into_iter().flatten().collect()),
     };
+    let files: Vec<(&Path, &str)> = journaled
+        .iter()
+        .map(|(index, _)| {
+            let item = items[*index];
+            (Path::new(&item.file.path), strategy_for(item, conflict_strategy))
+        })
+        .collect();
+    let added = archive::add_to_archive(archive_path, &files);
 
-    runs::update_run_status(conn, run_id, status, moved as i32, skipped as i32, errors as i32)
-        .ok();
+    for (position, (index, journal_id)) in journaled.iter().enumerate() {
+        let item = items[*index];
+        let stored = match &added {
+            Ok(results) => results[position].as_ref().map(Option::clone),
+            Err(e) => Err(e),
+        };
+        outcomes[*index] = Some(match stored {
+            Ok(Some(entry)) => archived(conn, item, journal_id, &entry)?,
+            Ok(None) => {
+                runs::finish_run_item(conn, journal_id, "skipped", &item.destination, None)?;
+                Outcome::Skipped
+            }
+            Err(e) => {
+                let msg = format!(
+                    "Failed to archive {} into {}: {:#}",
+                    item.file.path, item.destination, e
+                );
+                let code = errors::classify(e);
+                runs::fail_run_item(conn, journal_id, &item.destination, code, &msg)?;
+                Outcome::Failed(msg)
+            }
+        });
+    }
 
-    Ok(ExecutionResult {
-        run_id: run_id.to_string(),
-        total: moved + skipped + errors,
-        moved,
-        skipped,
-        errors,
-        error_messages,
-    })
+    Ok(outcomes.into_iter().flatten().collect())
+}
+
+/// Finishes an item whose file is stored in its archive as `entry`: removes
+/// the original.
+fn archived(
+    conn: &Connection,
+    item: &SimulationItem,
+    journal_id: &str,
+    entry: &str,
+) -> Result<Outcome> {
+    // The entry name is needed by rollback, so it is stored before the
+    // original goes away
+    runs::set_run_item_archive_entry(conn, journal_id, entry)?;
+
+    let source = Path::new(&item.file.path);
+    if let Err(e) = fs::remove_file(source) {
+        let msg = format!("Archived but failed to remove original {}: {}", source.display(), e);
+        let code = errors::classify_io(&e);
+        runs::fail_run_item(conn, journal_id, &item.destination, code, &msg)?;
+        return Ok(Outcome::Failed(msg));
+    }
+
+    runs::finish_run_item(conn, journal_id, "completed", &item.destination, None)?;
+    Ok(Outcome::Done)
+}
+
+/// Moves a file and leaves a link to its new location at the original path.
+/// If the link cannot be created the move is undone, so the file never
+/// silently disappears from where the user expects it. Returns the link path
+/// and the checksum of a verified copy.
+fn move_and_link(source: &Path, dest: &Path, verify: bool) -> Result<(PathBuf, Option<String>)> {
+    let checksum = move_file(source, dest, verify)?;
+
+    match link::create_link(dest, source) {
+        Ok(created) => Ok((created, checksum)),
+        Err(e) => {
+            if let Err(undo) = move_file(dest, source, verify) {
+                log::error!(
+                    "Failed to undo move {} -> {}: {}",
+                    dest.display(),
+                    source.display(),
+                    undo
+                );
+            }
+            Err(e)
+        }
+    }
 }
 
 /// Moves a file from source to destination. Tries `fs::rename` first (fast, same
-/// filesystem). If that fails (cross-device), falls back to copy + delete.
-fn move_file(source: &Path, dest: &Path) -> Result<()> {
+/// filesystem). Only when that fails because source and destination are on
+/// different devices does it fall back to copy + delete; any other error is
+/// returned as is.
+///
+/// With `verify`, the copy is checked against the source before the source is
+/// deleted, and its SHA-256 is returned. A copy that fails the check is
+/// removed and the source is kept.
+pub(crate) fn move_file(source: &Path, dest: &Path, verify: bool) -> Result<Option<String>> {
     match fs::rename(source, dest) {
-        Ok(()) => Ok(()),
-        Err(_rename_err) => {
+        Ok(()) => Ok(None),
+        Err(e) if errors::classify_io(&e) != errors::CROSS_DEVICE => {
+            Err(anyhow::anyhow!(e).context(format!(
+                "Failed to move {} to {}",
+                source.display(),
+                dest.display()
+            )))
+        }
+        Err(_) => {
             // Cross-device move: copy then delete
             fs::copy(source, dest)
                 .with_context(|| {
@@ -176,9 +940,268 @@ fn move_file(source: &Path, dest: &Path) -> Result<()> {
                         dest.display()
                     )
                 })?;
+            let checksum = if verify {
+                match integrity::verify_copy(source, dest) {
+                    Ok(checksum) => Some(checksum),
+                    Err(e) => {
+                        fs::remove_file(dest).ok();
+                        return Err(e.context("Copy verification failed, source kept"));
+                    }
+                }
+            } else {
+                None
+            };
             fs::remove_file(source)
                 .with_context(|| format!("Failed to remove source file {}", source.display()))?;
-            Ok(())
+            Ok(checksum)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::{self, item};
+
+    #[test]
+    fn test_drifted_items_are_skipped() {
+        let conn = test_support::conn();
+        let run = test_support::run(&conn, "/tmp");
+
+        let dir = test_support::temp_dir("drift");
+        let source = dir.join("report.pdf");
+        fs::write(&source, b"simulated").unwrap();
+
+        let file = scanner::file_entry_for_path(&source).unwrap();
+        let destination = dir.join("Docs").join("report.pdf");
+        let simulation =
+            test_support::simulation(vec![item(file, &destination.to_string_lossy())]);
+
+        runs::set_run_plan(&conn, &run.id, &simulation).unwrap();
+
+        // The file grows between the simulation and the execution
+        fs::write(&source, b"changed after the preview").unwrap();
+
+        let result = execute(&conn, &simulation, &run.id, "suffix", false, &RunControl::none()).unwrap();
+        assert_eq!(result.moved, 0);
+        assert_eq!(result.skipped, 1);
+        assert_eq!(result.drifted.len(), 1);
+        assert!(source.exists());
+        assert!(!dir.join("Docs").exists());
+
+        // The run keeps what was planned, not what changed since
+        let run = runs::get_run(&conn, &run.id).unwrap().unwrap();
+        assert_eq!((run.scanned_files, run.matched_files, run.total_files), (1, 1, 1));
+        assert_eq!(run.total_bytes, 9);
+        assert!(run.duration_ms.is_some());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_cancelled_run_leaves_remaining_items_untouched() {
+        let conn = test_support::conn();
+        let run = test_support::run(&conn, "/tmp");
+
+        let file = test_support::file_entry("/nonexistent/report.pdf", 10);
+        let simulation =
+            test_support::simulation(vec![item(file, "/nonexistent/Docs/report.pdf")]);
+        let cancel = std::sync::atomic::AtomicBool::new(true);
+        let reported = std::cell::Cell::new(0);
+        let on_progress = |_: &OrganizerProgress| reported.set(reported.get() + 1);
+        let control = RunControl::new(&on_progress, &cancel);
+
+        let result = execute(&conn, &simulation, &run.id, "suffix", false, &control).unwrap();
+        assert!(result.cancelled);
+        assert_eq!(result.total, 0);
+        assert!(runs::get_run_items(&conn, &run.id).unwrap().is_empty());
+        assert_eq!(reported.get(), 1);
+        assert_eq!(runs::get_run(&conn, &run.id).unwrap().unwrap().status, "cancelled");
+    }
+
+    #[test]
+    fn test_parallel_execution_matches_sequential() {
+        fn run_batch(name: &str, parallel: bool) -> (ExecutionResult, Vec<String>) {
+            let conn = test_support::conn();
+            let run = test_support::run(&conn, "/tmp");
+
+            let dir = test_support::temp_dir(&format!("parallel-{}", name));
+            fs::create_dir_all(dir.join("Docs")).unwrap();
+            // Already taken: the first report lands on a suffixed name
+            fs::write(dir.join("Docs").join("report-0.pdf"), b"existing").unwrap();
+
+            let mut items = Vec::new();
+            for i in 0..6 {
+                let source = dir.join(format!("report-{}.pdf", i));
+                fs::write(&source, format!("report {}", i)).unwrap();
+                let file = scanner::file_entry_for_path(&source).unwrap();
+                items.push(item(file, &format!("{}/", dir.join("Docs").display())));
+            }
+            // A rename in place stays sequential between the pooled moves
+            let renamed = dir.join("notes.txt");
+            fs::write(&renamed, b"notes").unwrap();
+            let file = scanner::file_entry_for_path(&renamed).unwrap();
+            let destination = dir.join("notes-2025.txt").to_string_lossy().to_string();
+            items.insert(3, SimulationItem {
+                action_type: "rename".to_string(),
+                ..item(file, &destination)
+            });
+            // Its source is gone by execution time
+            let missing = dir.join("missing.pdf").to_string_lossy().to_string();
+            items.push(SimulationItem {
+                file: test_support::file_entry(&missing, 1),
+                ..items[0].clone()
+            });
+
+            let flags: Vec<bool> = items
+                .iter()
+                .map(|i| parallel && i.action_type == "move_to_folder")
+                .collect();
+            let simulation = test_support::simulation(items);
+
+            let control = RunControl::none();
+            let mut result =
+                execute_planned(&conn, &simulation, &run.id, "suffix", true, &control, &flags)
+                    .unwrap();
+            result.run_id.clear();
+            let root = dir.to_string_lossy().to_string();
+            for d in &mut result.drifted {
+                d.path = d.path.replace(&root, "");
+            }
+
+            let mut layout: Vec<String> = runs::get_run_items(&conn, &run.id)
+                .unwrap()
+                .iter()
+                .map(|i| {
+                    format!(
+                        "{} {} -> {}",
+                        i.status,
+                        i.original_path.replace(&root, ""),
+                        i.destination_path.replace(&root, "")
+                    )
+                })
+                .collect();
+            layout.sort();
+
+            fs::remove_dir_all(&dir).ok();
+            (result, layout)
         }
+
+        let (sequential, sequential_layout) = run_batch("seq", false);
+        let (parallel, parallel_layout) = run_batch("par", true);
+
+        assert_eq!(sequential.moved, 7);
+        assert_eq!(sequential.skipped, 1);
+        assert_eq!(
+            serde_json::to_value(&parallel).unwrap(),
+            serde_json::to_value(&sequential).unwrap()
+        );
+        assert_eq!(parallel_layout, sequential_layout);
+    }
+
+    #[test]
+    fn test_archive_items_share_one_pass_per_archive() {
+        let conn = test_support::conn();
+        let run = test_support::run(&conn, "/tmp");
+
+        let dir = test_support::temp_dir("archive-batch");
+        let zip_path = dir.join("Arquivo").join("2025-01.zip");
+        let zip = zip_path.to_string_lossy().to_string();
+        for name in ["a.txt", "b.txt", "c.txt", "d.txt", "moved.txt"] {
+            fs::write(dir.join(name), name).unwrap();
+        }
+        let file = |name: &str| scanner::file_entry_for_path(&dir.join(name)).unwrap();
+        let archive = |name: &str| SimulationItem {
+            action_type: "archive".to_string(),
+            ..item(file(name), &zip)
+        };
+        let simulation = test_support::simulation(vec![
+            archive("a.txt"),
+            item(file("moved.txt"), &format!("{}/", dir.join("out").display())),
+            archive("b.txt"),
+            // A file with another action keeps its own place in the order
+            SimulationItem {
+                action_type: "link".to_string(),
+                ..item(file("c.txt"), &format!("{}/", dir.join("links").display()))
+            },
+            archive("c.txt"),
+            archive("d.txt"),
+        ]);
+
+        let batches = archive_batches(&simulation.items);
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[&0], [0, 2, 5]);
+        assert_eq!(batches[&4], [4]);
+
+        let result = execute(&conn, &simulation, &run.id, "suffix", false, &RunControl::none())
+            .unwrap();
+        assert_eq!((result.moved, result.errors), (6, 0));
+        assert!(dir.join("links").join("c.txt").symlink_metadata().is_ok());
+        for name in ["a.txt", "b.txt", "c.txt", "d.txt"] {
+            assert!(!dir.join(name).exists());
+        }
+
+        let stored = zip::ZipArchive::new(fs::File::open(&zip_path).unwrap()).unwrap();
+        assert_eq!(stored.len(), 4);
+        let entries: Vec<Option<String>> = runs::get_run_items(&conn, &run.id)
+            .unwrap()
+            .into_iter()
+            .filter(|i| i.action_type == "archive")
+            .map(|i| i.archive_entry)
+            .collect();
+        assert_eq!(entries.len(), 4);
+        assert!(entries.iter().all(|e| e.is_some()));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_action_conflict_strategy_overrides_default() {
+        let conn = test_support::conn();
+        let run = test_support::run(&conn, "/tmp");
+
+        let dir = test_support::temp_dir("strategy");
+        fs::create_dir_all(dir.join("in")).unwrap();
+        fs::create_dir_all(dir.join("out")).unwrap();
+        // A re-downloaded duplicate and a larger version of an existing photo
+        fs::write(dir.join("in").join("invoice.pdf"), b"invoice").unwrap();
+        fs::write(dir.join("out").join("invoice.pdf"), b"invoice").unwrap();
+        fs::write(dir.join("in").join("photo.jpg"), b"full resolution").unwrap();
+        fs::write(dir.join("out").join("photo.jpg"), b"thumbnail").unwrap();
+
+        let clash = |name: &str, strategy: &str| SimulationItem {
+            conflict: true,
+            conflict_strategy: strategy.to_string(),
+            ..item(
+                scanner::file_entry_for_path(&dir.join("in").join(name)).unwrap(),
+                &dir.join("out").join(name).to_string_lossy(),
+            )
+        };
+        let simulation = test_support::simulation(vec![
+            clash("invoice.pdf", "skip_if_identical"),
+            clash("photo.jpg", "overwrite_if_larger"),
+        ]);
+
+        let result = execute(&conn, &simulation, &run.id, "suffix", false, &RunControl::none()).unwrap();
+        assert_eq!((result.moved, result.skipped), (1, 1));
+        assert!(dir.join("in").join("invoice.pdf").exists());
+        assert!(!dir.join("out").join("invoice_1.pdf").exists());
+        assert_eq!(fs::read(dir.join("out").join("photo.jpg")).unwrap(), b"full resolution");
+
+        // Rolling back brings the replaced file back
+        crate::organizer::rollback::rollback_run(
+            &conn,
+            &run.id,
+            crate::organizer::rollback::RollbackScope::All,
+            "suffix",
+            crate::organizer::rollback::ChainMode::Cascade,
+            false,
+            &RunControl::none(),
+        )
+        .unwrap();
+        assert_eq!(fs::read(dir.join("out").join("photo.jpg")).unwrap(), b"thumbnail");
+        assert_eq!(fs::read(dir.join("in").join("photo.jpg")).unwrap(), b"full resolution");
+
+        fs::remove_dir_all(&dir).ok();
     }
 }
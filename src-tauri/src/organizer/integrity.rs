@@ -0,0 +1,132 @@
+use anyhow::{bail, Context, Result};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+
+/// Computes the SHA-256 of everything `reader` yields. Returns the lowercase
+/// hex digest and the number of bytes read.
+pub fn sha256_reader(reader: &mut dyn Read) -> Result<(String, u64)> {
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    let mut total = 0u64;
+
+    loop {
+        let n = reader.read(&mut buf).context("Failed to read data for hashing")?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        total += n as u64;
+    }
+
+    let digest = hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+    Ok((digest, total))
+}
+
+/// Computes the SHA-256 of a file. Returns the lowercase hex digest and the
+/// file size.
+pub fn sha256_file(path: &Path) -> Result<(String, u64)> {
+    let mut file =
+        File::open(path).with_context(|| format!("Failed to open {} for hashing", path.display()))?;
+    sha256_reader(&mut file)
+}
+
+/// Checks that `copy` is identical to `original` by size and SHA-256.
+/// Returns the digest when they match.
+pub fn verify_copy(original: &Path, copy: &Path) -> Result<String> {
+    let (original_hash, original_size) = sha256_file(original)?;
+    let (copy_hash, copy_size) = sha256_file(copy)?;
+
+    if copy_size != original_size {
+        bail!(
+            "Copy of {} has {} bytes, expected {}",
+            original.display(),
+            copy_size,
+            original_size
+        );
+    }
+    if copy_hash != original_hash {
+        bail!("Checksum of the copy of {} does not match", original.display());
+    }
+    Ok(original_hash)
+}
+
+/// Modification time of a file, precise enough to notice any later write.
+pub fn mtime_stamp(path: &Path) -> Result<String> {
+    let modified = fs::metadata(path)
+        .and_then(|m| m.modified())
+        .with_context(|| format!("Failed to read modification time of {}", path.display()))?;
+    Ok(chrono::DateTime::<chrono::Utc>::from(modified)
+        .to_rfc3339_opts(chrono::SecondsFormat::Nanos, true))
+}
+
+/// Whether the file at `path` is no longer the one a run left there. Checks
+/// the size, then the modification time; when only the time moved and the
+/// checksum is known, the content decides.
+pub fn changed_since(
+    path: &Path,
+    size: u64,
+    mtime: Option<&str>,
+    checksum: Option<&str>,
+) -> Result<bool> {
+    let len = fs::metadata(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?
+        .len();
+    if len != size {
+        return Ok(true);
+    }
+    let mtime = match mtime {
+        Some(mtime) => mtime,
+        None => return Ok(false),
+    };
+    if mtime_stamp(path)? == mtime {
+        return Ok(false);
+    }
+    match checksum {
+        Some(checksum) => Ok(sha256_file(path)?.0 != checksum),
+        None => Ok(true),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_sha256_reader_known_value() {
+        let (hash, size) = sha256_reader(&mut Cursor::new(b"abc")).unwrap();
+        assert_eq!(
+            hash,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(size, 3);
+    }
+
+    #[test]
+    fn test_verify_copy() {
+        let dir = test_support::temp_dir("integrity");
+        let original = dir.join("a.bin");
+        let copy = dir.join("b.bin");
+        std::fs::write(&original, b"abc").unwrap();
+
+        std::fs::write(&copy, b"abc").unwrap();
+        assert_eq!(
+            verify_copy(&original, &copy).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+
+        std::fs::write(&copy, b"abd").unwrap();
+        assert!(verify_copy(&original, &copy).is_err());
+        std::fs::write(&copy, b"ab").unwrap();
+        assert!(verify_copy(&original, &copy).is_err());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
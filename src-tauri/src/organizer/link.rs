@@ -0,0 +1,198 @@
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Extension of the link file written when symlinks are not available.
+#[cfg(target_os = "windows")]
+const LINK_FILE_EXTENSION: &str = "url";
+#[cfg(not(target_os = "windows"))]
+const LINK_FILE_EXTENSION: &str = "desktop";
+
+/// Creates a link at `link_path` pointing to `target`.
+///
+/// A symlink is tried first. When the platform or filesystem refuses it
+/// (Windows without Developer Mode, FAT/exFAT drives, ...), a `.url` file on
+/// Windows or a `.desktop` file elsewhere is written next to `link_path`
+/// instead. Returns the path that was actually created.
+pub fn create_link(target: &Path, link_path: &Path) -> Result<PathBuf> {
+    if link_path.symlink_metadata().is_ok() {
+        bail!("Link path already exists: {}", link_path.display());
+    }
+
+    match symlink(target, link_path) {
+        Ok(()) => return Ok(link_path.to_path_buf()),
+        Err(e) => log::warn!(
+            "Symlink {} -> {} failed ({}), writing a link file instead",
+            link_path.display(),
+            target.display(),
+            e
+        ),
+    }
+
+    let link_file = link_file_path(link_path);
+    if link_file.symlink_metadata().is_ok() {
+        bail!("Link file already exists: {}", link_file.display());
+    }
+
+    fs::write(&link_file, link_file_contents(target, link_path))
+        .with_context(|| format!("Failed to write link file {}", link_file.display()))?;
+
+    Ok(link_file)
+}
+
+/// Removes a link previously created by [`create_link`]. Regular files are
+/// never removed, and neither are link files pointing somewhere other than
+/// `target`, so rollback cannot delete user data. A link that no longer
+/// exists is not an error.
+pub fn remove_link(link_path: &Path, target: &Path) -> Result<()> {
+    let metadata = match link_path.symlink_metadata() {
+        Ok(m) => m,
+        Err(_) => return Ok(()),
+    };
+
+    if metadata.file_type().is_symlink() {
+        return fs::remove_file(link_path)
+            .with_context(|| format!("Failed to remove link {}", link_path.display()));
+    }
+
+    let is_link_file = link_path
+        .extension()
+        .map(|e| e.eq_ignore_ascii_case(LINK_FILE_EXTENSION))
+        .unwrap_or(false);
+    let points_to_target = fs::read_to_string(link_path)
+        .map(|contents| contents.contains(&file_url(target)))
+        .unwrap_or(false);
+
+    if !is_link_file || !points_to_target {
+        bail!(
+            "{} is not a link to {}, leaving it in place",
+            link_path.display(),
+            target.display()
+        );
+    }
+
+    fs::remove_file(link_path)
+        .with_context(|| format!("Failed to remove link file {}", link_path.display()))
+}
+
+/// Finds the link [`create_link`] made for `link_path`: either the symlink
+/// itself or the `.url`/`.desktop` file written next to it.
+pub fn find_link(link_path: &Path) -> Option<PathBuf> {
+    let is_symlink = link_path
+        .symlink_metadata()
+        .map(|m| m.file_type().is_symlink())
+        .unwrap_or(false);
+    if is_symlink {
+        return Some(link_path.to_path_buf());
+    }
+
+    let link_file = link_file_path(link_path);
+    link_file.is_file().then_some(link_file)
+}
+
+#[cfg(unix)]
+fn symlink(target: &Path, link_path: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, link_path)
+}
+
+#[cfg(windows)]
+fn symlink(target: &Path, link_path: &Path) -> std::io::Result<()> {
+    if target.is_dir() {
+        std::os::windows::fs::symlink_dir(target, link_path)
+    } else {
+        std::os::windows::fs::symlink_file(target, link_path)
+    }
+}
+
+/// Appends the link file extension, keeping the original name visible
+/// (`report.pdf` -> `report.pdf.desktop`).
+fn link_file_path(link_path: &Path) -> PathBuf {
+    let mut name = link_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(LINK_FILE_EXTENSION);
+    link_path.with_file_name(name)
+}
+
+#[cfg(target_os = "windows")]
+fn link_file_contents(target: &Path, _link_path: &Path) -> String {
+    format!("[InternetShortcut]\r\nURL={}\r\n", file_url(target))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn link_file_contents(target: &Path, link_path: &Path) -> String {
+    let name = link_path.file_name().unwrap_or_default().to_string_lossy();
+    format!(
+        "[Desktop Entry]\nType=Link\nName={}\nURL={}\n",
+        name,
+        file_url(target)
+    )
+}
+
+/// Builds a `file://` URL for a path, percent-encoding everything outside the
+/// unreserved set. Backslashes become `/` so Windows paths produce valid URLs.
+fn file_url(path: &Path) -> String {
+    let raw = path.to_string_lossy().replace('\\', "/");
+    let mut url = String::from("file://");
+    if !raw.starts_with('/') {
+        url.push('/');
+    }
+    for byte in raw.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                url.push(byte as char)
+            }
+            _ => url.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::temp_dir;
+
+    #[test]
+    fn test_create_and_remove_link() {
+        let dir = temp_dir("link-create");
+        let target = dir.join("report.pdf");
+        fs::write(&target, b"data").unwrap();
+
+        let link = create_link(&target, &dir.join("atalho.pdf")).unwrap();
+        assert!(link.symlink_metadata().is_ok());
+        assert_eq!(find_link(&dir.join("atalho.pdf")), Some(link.clone()));
+        assert!(create_link(&target, &link).is_err());
+
+        remove_link(&link, &target).unwrap();
+        assert!(link.symlink_metadata().is_err());
+        assert!(target.exists());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_remove_link_keeps_regular_files() {
+        let dir = temp_dir("link-regular");
+        let target = dir.join("report.pdf");
+        let other = dir.join("other.pdf");
+        fs::write(&target, b"data").unwrap();
+        fs::write(&other, b"user data").unwrap();
+
+        assert!(remove_link(&other, &target).is_err());
+        assert!(other.exists());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_file_url_encoding() {
+        assert_eq!(
+            file_url(Path::new("/home/ana/Meus Docs/relatório.pdf")),
+            "file:///home/ana/Meus%20Docs/relat%C3%B3rio.pdf"
+        );
+        assert_eq!(
+            file_url(Path::new("C:\\Users\\ana\\a b.txt")),
+            "file:///C:/Users/ana/a%20b.txt"
+        );
+    }
+}
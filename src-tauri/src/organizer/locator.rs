@@ -0,0 +1,209 @@
+use anyhow::{bail, Context, Result};
+use rusqlite::Connection;
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::db::models::{FileLocation, RunItem};
+use crate::db::queries::{rules, runs};
+use crate::organizer::{integrity, rollback};
+
+/// Answers "where did my file go?": finds the files whose original path
+/// contains `query`, follows each through the later runs that moved it again
+/// and checks where it is on disk now. Most recently moved first.
+pub fn locate(conn: &Connection, query: &str, limit: usize) -> Result<Vec<FileLocation>> {
+    let needle = query.to_lowercase();
+    // The search also matches destinations, so ask for more than needed
+    let mut matches: Vec<RunItem> = runs::search_run_items(conn, query, (limit * 4) as i32)?
+        .into_iter()
+        .filter(|i| i.status == "completed" || i.status == "rolled_back")
+        .filter(|i| i.original_path.to_lowercase().contains(&needle))
+        .collect();
+    // Oldest first, so a file's first move claims the moves that followed it
+    matches.sort_by(|a, b| a.executed_at.cmp(&b.executed_at));
+
+    let mut followed: HashSet<String> = HashSet::new();
+    let mut locations = Vec::new();
+    for item in &matches {
+        if followed.contains(&item.id) {
+            continue;
+        }
+        let steps = if item.status == "completed" {
+            rollback::trace_chain(conn, item)?
+        } else {
+            Vec::new()
+        };
+        followed.extend(steps.iter().map(|s| s.id.clone()));
+        locations.push(locate_item(conn, item, &steps)?);
+    }
+
+    locations.sort_by(|a, b| b.moved_at.cmp(&a.moved_at));
+    locations.truncate(limit);
+    Ok(locations)
+}
+
+/// Works out where one item's file is, given the later moves of it.
+fn locate_item(conn: &Connection, item: &RunItem, steps: &[RunItem]) -> Result<FileLocation> {
+    let last = steps.last().unwrap_or(item);
+
+    let mut path_history = vec![item.original_path.clone()];
+    for moved in std::iter::once(item).chain(steps) {
+        if !moved.destination_path.is_empty() && moved.action_type != "link" {
+            path_history.push(moved.destination_path.clone());
+        }
+    }
+
+    let (current_path, status) = if item.status == "rolled_back" {
+        let back = item.restored_path.clone().unwrap_or_else(|| item.original_path.clone());
+        path_history.push(back.clone());
+        at_or_near(&back, last, "restored")
+    } else {
+        match last.action_type.as_str() {
+            "delete" => (None, "deleted"),
+            // Links and extractions leave the file itself where it was
+            "link" | "extract" => at_or_near(&last.original_path, last, "found"),
+            _ => {
+                let checksum = std::iter::once(item)
+                    .chain(steps)
+                    .rev()
+                    .find_map(|i| i.checksum.as_deref());
+                let dest = Path::new(&last.destination_path);
+                if dest.exists() {
+                    (Some(last.destination_path.clone()), "found")
+                } else {
+                    match checksum.and_then(|c| find_by_content(dest, c, last.file_size)) {
+                        Some(renamed) => (Some(renamed.to_string_lossy().to_string()), "renamed"),
+                        None => (None, "missing"),
+                    }
+                }
+            }
+        }
+    };
+
+    let rule_name = match &last.rule_id {
+        Some(rule_id) => rules::get_rule(conn, rule_id)?.map(|r| r.name),
+        None => None,
+    };
+
+    Ok(FileLocation {
+        run_item_id: item.id.clone(),
+        run_id: last.run_id.clone(),
+        original_path: item.original_path.clone(),
+        path_history,
+        current_path,
+        status: status.to_string(),
+        rule_id: last.rule_id.clone(),
+        rule_name,
+        moved_at: last.executed_at.clone(),
+    })
+}
+
+fn at_or_near(
+    path: &str,
+    item: &RunItem,
+    status: &'static str,
+) -> (Option<String>, &'static str) {
+    if Path::new(path).exists() {
+        return (Some(path.to_string()), status);
+    }
+    let checksum = item.checksum.as_deref();
+    match checksum.and_then(|c| find_by_content(Path::new(path), c, item.file_size)) {
+        Some(renamed) => (Some(renamed.to_string_lossy().to_string()), "renamed"),
+        None => (None, "missing"),
+    }
+}
+
+/// Looks next to `expected` for a file with the same size and checksum, in
+/// case it was renamed after the run.
+fn find_by_content(expected: &Path, checksum: &str, size: i64) -> Option<PathBuf> {
+    let entries = fs::read_dir(expected.parent()?).ok()?;
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .metadata()
+                .is_ok_and(|m| m.is_file() && m.len() == size.max(0) as u64)
+        })
+        .map(|entry| entry.path())
+        .find(|path| integrity::sha256_file(path).is_ok_and(|(digest, _)| digest == checksum))
+}
+
+/// Opens the folder holding `path` in the system file manager, selecting the
+/// file where the platform supports it.
+pub fn open_containing_folder(path: &Path) -> Result<()> {
+    let folder = if path.is_dir() { path } else { path.parent().unwrap_or(path) };
+    if !folder.exists() {
+        bail!("Folder does not exist: {}", folder.display());
+    }
+
+    let select = path.is_file();
+    let (program, args): (&str, Vec<OsString>) = if cfg!(target_os = "windows") {
+        let arg = if select {
+            format!("/select,{}", path.display())
+        } else {
+            folder.display().to_string()
+        };
+        ("explorer", vec![arg.into()])
+    } else if cfg!(target_os = "macos") && select {
+        ("open", vec!["-R".into(), path.into()])
+    } else if cfg!(target_os = "macos") {
+        ("open", vec![folder.into()])
+    } else {
+        ("xdg-open", vec![folder.into()])
+    };
+
+    Command::new(program)
+        .args(&args)
+        .spawn()
+        .with_context(|| format!("Failed to open {}", folder.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support;
+
+    #[test]
+    fn test_locate_follows_moves_and_renames() {
+        let dir = test_support::temp_dir("locate");
+        fs::create_dir_all(dir.join("out")).unwrap();
+        let at = |path: &str| dir.join(path).to_string_lossy().to_string();
+
+        let conn = test_support::conn();
+        test_support::rule(&conn, "r1", "Reports");
+
+        // Moved twice, then renamed by the user
+        fs::write(dir.join("out").join("final.pdf"), b"quarterly").unwrap();
+        let (checksum, _) = integrity::sha256_file(&dir.join("out").join("final.pdf")).unwrap();
+        let moves = [("in/Report.pdf", "mid/Report.pdf"), ("mid/Report.pdf", "out/Report.pdf")];
+        for (from, to) in moves {
+            let run = test_support::run(&conn, &at(""));
+            let item = runs::create_run_item(
+                &conn,
+                &run.id,
+                Some("r1"),
+                &at(from),
+                &at(to),
+                9,
+                "move_to_folder",
+                "completed",
+                "suffix",
+            )
+            .unwrap();
+            runs::set_run_item_checksum(&conn, &item.id, &checksum).unwrap();
+        }
+
+        let found = locate(&conn, "report", 10).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].original_path, at("in/Report.pdf"));
+        assert_eq!(found[0].path_history.len(), 3);
+        assert_eq!(found[0].status, "renamed");
+        assert_eq!(found[0].current_path.as_deref(), Some(at("out/final.pdf").as_str()));
+        assert_eq!(found[0].rule_name.as_deref(), Some("Reports"));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
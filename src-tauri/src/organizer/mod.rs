@@ -1,5 +1,17 @@
+pub mod archive;
 pub mod conflict;
+pub mod errors;
 pub mod executor;
+pub mod integrity;
+pub mod link;
+pub mod locator;
+pub mod parallel;
+pub mod preflight;
+pub mod progress;
+pub mod recovery;
+pub mod report;
+pub mod retention;
+pub mod retry;
 pub mod rollback;
 pub mod scanner;
 pub mod simulator;
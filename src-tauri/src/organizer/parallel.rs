@@ -0,0 +1,238 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::Scope;
+
+use crate::db::models::SimulationItem;
+
+/// Upper bound on concurrent cross-device copies. Copies are disk-bound, so
+/// more workers mostly add seek contention.
+pub const MAX_WORKERS: usize = 4;
+
+/// Action types that are plain moves and may run on the worker pool.
+const PARALLEL_ACTIONS: &[&str] = &["move", "copy", "move_to_folder", "move_to_subfolder"];
+
+/// Flags the items that can be moved concurrently without changing the
+/// outcome of a sequential run: plain cross-device moves whose target is not
+/// shared with, or the source of, any other item in the batch. Same-directory
+/// renames and every other action stay sequential.
+pub fn eligible_items(
+    items: &[SimulationItem],
+    is_cross_device: impl Fn(&Path, &Path) -> bool,
+) -> Vec<bool> {
+    let targets: Vec<Option<PathBuf>> = items.iter().map(planned_target).collect();
+
+    let mut target_counts: HashMap<&Path, usize> = HashMap::new();
+    for target in targets.iter().flatten() {
+        *target_counts.entry(target.as_path()).or_default() += 1;
+    }
+    let sources: HashSet<&Path> = items.iter().map(|i| Path::new(&i.file.path)).collect();
+
+    items
+        .iter()
+        .zip(&targets)
+        .map(|(item, target)| {
+            let target = match target {
+                Some(t) => t,
+                None => return false,
+            };
+            let source = Path::new(&item.file.path);
+
+            PARALLEL_ACTIONS.contains(&item.action_type.as_str())
+                && item.error.is_none()
+                && source.parent() != target.parent()
+                && target_counts.get(target.as_path()) == Some(&1)
+                && !sources.contains(target.as_path())
+                && !target_counts.contains_key(source)
+                && is_cross_device(source, target)
+        })
+        .collect()
+}
+
+/// The path an item is planned to land on, before conflict resolution.
+/// Mirrors how the executor places a file inside a directory destination.
+pub fn planned_target(item: &SimulationItem) -> Option<PathBuf> {
+    if item.destination.is_empty() {
+        return None;
+    }
+    let dest = PathBuf::from(&item.destination);
+    if dest.is_dir() || item.destination.ends_with('/') || item.destination.ends_with('\\') {
+        Some(dest.join(&item.file.name))
+    } else {
+        Some(dest)
+    }
+}
+
+/// Whether moving `source` to `dest` crosses a filesystem boundary, i.e.
+/// `fs::rename` will fail and the file has to be copied.
+#[cfg(unix)]
+pub fn is_cross_device(source: &Path, dest: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    let source_dev = match source.metadata() {
+        Ok(m) => m.dev(),
+        Err(_) => return false,
+    };
+    // The destination folder may not exist yet: use its closest existing ancestor
+    dest.ancestors()
+        .skip(1)
+        .find_map(|dir| dir.metadata().ok())
+        .map(|m| m.dev() != source_dev)
+        .unwrap_or(false)
+}
+
+/// Whether moving `source` to `dest` crosses a filesystem boundary. Without a
+/// portable device ID, different drive prefixes are treated as different
+/// volumes.
+#[cfg(not(unix))]
+pub fn is_cross_device(source: &Path, dest: &Path) -> bool {
+    use std::path::Component;
+
+    let prefix = |p: &Path| match p.components().next() {
+        Some(Component::Prefix(prefix)) => Some(prefix.as_os_str().to_ascii_lowercase()),
+        _ => None,
+    };
+    prefix(source) != prefix(dest)
+}
+
+/// A fixed set of scoped worker threads running `work` on submitted jobs.
+/// Results come back tagged with the index they were submitted with; the
+/// caller stays the only thread touching the database.
+pub struct WorkerPool<J, R> {
+    jobs: Option<Sender<(usize, J)>>,
+    results: Receiver<(usize, J, R)>,
+    in_flight: usize,
+}
+
+impl<J: Send, R: Send> WorkerPool<J, R> {
+    pub fn start<'scope, 'env>(
+        scope: &'scope Scope<'scope, 'env>,
+        workers: usize,
+        work: fn(&J) -> R,
+    ) -> Self
+    where
+        J: 'scope,
+        R: 'scope,
+    {
+        let (job_tx, job_rx) = mpsc::channel::<(usize, J)>();
+        let (result_tx, result_rx) = mpsc::channel();
+        let job_rx = Arc::new(Mutex::new(job_rx));
+
+        for _ in 0..workers.max(1) {
+            let job_rx = Arc::clone(&job_rx);
+            let result_tx = result_tx.clone();
+            scope.spawn(move || loop {
+                let next = match job_rx.lock() {
+                    Ok(rx) => rx.recv(),
+                    Err(_) => break,
+                };
+                let (index, job) = match next {
+                    Ok(job) => job,
+                    // The pool was dropped: no more jobs
+                    Err(_) => break,
+                };
+                let result = work(&job);
+                if result_tx.send((index, job, result)).is_err() {
+                    break;
+                }
+            });
+        }
+
+        Self {
+            jobs: Some(job_tx),
+            results: result_rx,
+            in_flight: 0,
+        }
+    }
+
+    pub fn submit(&mut self, index: usize, job: J) {
+        if let Some(jobs) = &self.jobs {
+            if jobs.send((index, job)).is_ok() {
+                self.in_flight += 1;
+            }
+        }
+    }
+
+    /// Results that are already available, without blocking.
+    pub fn finished(&mut self) -> Vec<(usize, J, R)> {
+        let mut done = Vec::new();
+        while let Ok(result) = self.results.try_recv() {
+            self.in_flight -= 1;
+            done.push(result);
+        }
+        done
+    }
+
+    /// Blocks until every submitted job has finished.
+    pub fn wait_all(&mut self) -> Vec<(usize, J, R)> {
+        let mut done = Vec::new();
+        while self.in_flight > 0 {
+            match self.results.recv() {
+                Ok(result) => {
+                    self.in_flight -= 1;
+                    done.push(result);
+                }
+                Err(_) => break,
+            }
+        }
+        done
+    }
+}
+
+impl<J, R> Drop for WorkerPool<J, R> {
+    fn drop(&mut self) {
+        // Closing the job channel lets the workers exit so the scope can join them
+        self.jobs.take();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support;
+
+    fn make_item(path: &str, destination: &str, action_type: &str) -> SimulationItem {
+        SimulationItem {
+            action_type: action_type.to_string(),
+            ..test_support::item(test_support::file_entry(path, 1), destination)
+        }
+    }
+
+    #[test]
+    fn test_eligible_items() {
+        let items = vec![
+            make_item("/src/a.pdf", "/mnt/usb/Docs/a.pdf", "move_to_folder"),
+            // Same target as the next item: order decides the suffix
+            make_item("/src/b.pdf", "/mnt/usb/Docs/b.pdf", "move_to_folder"),
+            make_item("/src/other/b.pdf", "/mnt/usb/Docs/b.pdf", "move_to_folder"),
+            // Same-directory rename
+            make_item("/src/c.pdf", "/src/c-2025.pdf", "rename"),
+            // Not a plain move
+            make_item("/src/d.pdf", "/mnt/usb/Links/d.pdf", "link"),
+            // Same device
+            make_item("/src/e.pdf", "/src/Docs/e.pdf", "move_to_folder"),
+        ];
+
+        let eligible = eligible_items(&items, |_, dest| dest.starts_with("/mnt/usb"));
+        assert_eq!(eligible, vec![true, false, false, false, false, false]);
+    }
+
+    #[test]
+    fn test_worker_pool_returns_every_result() {
+        let results = std::thread::scope(|scope| {
+            let mut pool = WorkerPool::start(scope, 3, |n: &u64| n * 2);
+            for i in 0..10 {
+                pool.submit(i, i as u64);
+            }
+            let mut results = pool.wait_all();
+            results.sort_by_key(|(index, _, _)| *index);
+            results
+        });
+
+        assert_eq!(results.len(), 10);
+        assert!(results
+            .iter()
+            .all(|(index, job, result)| *job == *index as u64 && *result == job * 2));
+    }
+}
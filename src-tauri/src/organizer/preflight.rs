@@ -0,0 +1,304 @@
+use fs2::FileExt;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
+
+use crate::db::models::{
+    PreflightIssue, PreflightReport, SimulationItem, SimulationResult, VolumeUsage,
+};
+use crate::organizer::parallel;
+
+/// Share of the free space a run may use before it is flagged as `low_space`.
+const LOW_SPACE_RATIO: f64 = 0.9;
+
+/// Checks that a simulation can run to the end before anything is touched.
+///
+/// - Bytes copied to each target filesystem are summed and compared with its
+///   free space. Moves within a filesystem are renames and need none.
+/// - Every destination root must be writable.
+/// - Sources must be readable and, when the action removes them, deletable.
+/// - Files locked by another process are flagged where the platform lets us
+///   see it (sharing violations on Windows, `flock` locks elsewhere).
+///
+/// Items that won't be executed (errors, skips) are not checked.
+pub fn preflight(simulation: &SimulationResult) -> PreflightReport {
+    let mut blockers = Vec::new();
+    let mut warnings = Vec::new();
+
+    // Volume key -> (an existing folder on it, bytes required)
+    let mut volumes: HashMap<String, (PathBuf, u64)> = HashMap::new();
+    let mut checked_roots: HashSet<PathBuf> = HashSet::new();
+    let mut checked_sources: HashSet<&str> = HashSet::new();
+
+    for item in simulation.items.iter().filter(|i| will_run(i)) {
+        let source = Path::new(&item.file.path);
+        let target = if item.final_path.is_empty() {
+            match parallel::planned_target(item) {
+                Some(target) => target,
+                None => continue,
+            }
+        } else {
+            PathBuf::from(&item.final_path)
+        };
+
+        let root = match existing_ancestor(&target) {
+            Some(root) => root,
+            None => {
+                blockers.push(issue(
+                    "destination_not_writable",
+                    &target,
+                    "No part of the destination path exists",
+                ));
+                continue;
+            }
+        };
+
+        if checked_roots.insert(root.clone()) && !is_writable_dir(&root) {
+            blockers.push(issue(
+                "destination_not_writable",
+                &root,
+                "Destination folder is not writable",
+            ));
+        }
+
+        if needs_copy(item) && volume_key(source) != volume_key(&root) {
+            if let Some(key) = volume_key(&root) {
+                volumes.entry(key).or_insert_with(|| (root.clone(), 0)).1 += item.file.size;
+            }
+        }
+
+        if checked_sources.insert(&item.file.path) {
+            check_source(item, &mut blockers);
+        }
+    }
+
+    let mut usage = Vec::new();
+    for (root, required) in volumes.into_values() {
+        let available = match fs2::available_space(&root) {
+            Ok(available) => available,
+            Err(e) => {
+                warnings.push(issue(
+                    "low_space",
+                    &root,
+                    &format!("Could not read the free space: {}", e),
+                ));
+                continue;
+            }
+        };
+
+        if required > available {
+            blockers.push(issue(
+                "insufficient_space",
+                &root,
+                &format!(
+                    "{} bytes are needed but only {} are available",
+                    required, available
+                ),
+            ));
+        } else if required as f64 > available as f64 * LOW_SPACE_RATIO {
+            warnings.push(issue(
+                "low_space",
+                &root,
+                &format!("The run will use {} of the {} bytes left", required, available),
+            ));
+        }
+
+        usage.push(VolumeUsage {
+            path: root.to_string_lossy().to_string(),
+            required_bytes: required,
+            available_bytes: available,
+        });
+    }
+    usage.sort_by(|a, b| a.path.cmp(&b.path));
+
+    PreflightReport {
+        go: blockers.is_empty(),
+        blockers,
+        warnings,
+        volumes: usage,
+    }
+}
+
+/// One line per blocker, for error messages and logs.
+pub fn describe_blockers(report: &PreflightReport) -> String {
+    report
+        .blockers
+        .iter()
+        .map(|b| format!("{}: {}", b.path, b.message))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Whether the executor will act on the item at all.
+fn will_run(item: &SimulationItem) -> bool {
+    item.error.is_none() && item.skip_reason.is_none() && !item.destination.is_empty()
+}
+
+/// Whether the item writes the file's bytes somewhere: everything except links.
+fn needs_copy(item: &SimulationItem) -> bool {
+    item.action_type != "link"
+}
+
+/// Whether the action removes the source once it is done.
+fn removes_source(item: &SimulationItem) -> bool {
+    match item.action_type.as_str() {
+        "link" => false,
+        "extract" => item.post_action != "keep",
+        _ => true,
+    }
+}
+
+fn check_source(item: &SimulationItem, blockers: &mut Vec<PreflightIssue>) {
+    let source = Path::new(&item.file.path);
+
+    let file = match File::open(source) {
+        Ok(file) => file,
+        Err(e) if is_sharing_violation(&e) => {
+            blockers.push(issue("file_locked", source, "File is in use by another program"));
+            return;
+        }
+        Err(e) => {
+            blockers.push(issue(
+                "source_not_readable",
+                source,
+                &format!("File cannot be read: {}", e),
+            ));
+            return;
+        }
+    };
+
+    match file.try_lock_exclusive() {
+        Ok(()) => {
+            file.unlock().ok();
+        }
+        Err(e) if e.kind() == fs2::lock_contended_error().kind() => {
+            blockers.push(issue("file_locked", source, "File is locked by another program"));
+            return;
+        }
+        // Filesystems without lock support tell us nothing either way
+        Err(_) => {}
+    }
+
+    if !removes_source(item) {
+        return;
+    }
+
+    // Deleting needs write access to the folder, and on Windows a file that
+    // isn't read-only
+    let parent_writable = source.parent().map(is_writable_dir).unwrap_or(false);
+    let read_only = cfg!(windows)
+        && fs::metadata(source)
+            .map(|m| m.permissions().readonly())
+            .unwrap_or(false);
+    if !parent_writable || read_only {
+        blockers.push(issue(
+            "source_not_deletable",
+            source,
+            if read_only {
+                "File is read-only and cannot be removed after it is moved"
+            } else {
+                "Folder does not allow removing the file after it is moved"
+            },
+        ));
+    }
+}
+
+/// Tests whether files can be created in a folder by creating one.
+fn is_writable_dir(dir: &Path) -> bool {
+    let probe = dir.join(format!(".deskcraft-preflight-{}", uuid::Uuid::new_v4()));
+    match OpenOptions::new().write(true).create_new(true).open(&probe) {
+        Ok(_) => {
+            fs::remove_file(&probe).ok();
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// The closest ancestor of `path` (itself excluded) that exists.
+fn existing_ancestor(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .skip(1)
+        .find(|dir| dir.is_dir())
+        .map(Path::to_path_buf)
+}
+
+/// Identifies the filesystem a path lives on.
+#[cfg(unix)]
+fn volume_key(path: &Path) -> Option<String> {
+    use std::os::unix::fs::MetadataExt;
+
+    path.ancestors()
+        .find_map(|p| p.metadata().ok())
+        .map(|m| m.dev().to_string())
+}
+
+/// Identifies the filesystem a path lives on by its drive or share prefix.
+#[cfg(not(unix))]
+fn volume_key(path: &Path) -> Option<String> {
+    use std::path::Component;
+
+    match path.components().next() {
+        Some(Component::Prefix(prefix)) => {
+            Some(prefix.as_os_str().to_string_lossy().to_lowercase())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(windows)]
+fn is_sharing_violation(e: &std::io::Error) -> bool {
+    // ERROR_SHARING_VIOLATION / ERROR_LOCK_VIOLATION
+    matches!(e.raw_os_error(), Some(32) | Some(33))
+}
+
+#[cfg(not(windows))]
+fn is_sharing_violation(e: &std::io::Error) -> bool {
+    e.kind() == std::io::ErrorKind::WouldBlock
+}
+
+fn issue(kind: &str, path: &Path, message: &str) -> PreflightIssue {
+    PreflightIssue {
+        kind: kind.to_string(),
+        path: path.to_string_lossy().to_string(),
+        message: message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support;
+    use crate::organizer::scanner;
+
+    fn item(source: &Path, destination: &Path) -> SimulationItem {
+        let file = scanner::file_entry_for_path(source).unwrap();
+        test_support::item(file, &destination.to_string_lossy())
+    }
+
+    #[test]
+    fn test_preflight_reports_locked_sources() {
+        let dir = test_support::temp_dir("preflight");
+        let free = dir.join("free.txt");
+        let locked = dir.join("locked.txt");
+        fs::write(&free, b"a").unwrap();
+        fs::write(&locked, b"b").unwrap();
+
+        let simulation = test_support::simulation(vec![
+            item(&free, &dir.join("out").join("free.txt")),
+            item(&locked, &dir.join("out").join("locked.txt")),
+        ]);
+
+        assert!(preflight(&simulation).go);
+
+        let holder = File::open(&locked).unwrap();
+        holder.lock_exclusive().unwrap();
+        let report = preflight(&simulation);
+        assert!(!report.go);
+        assert_eq!(report.blockers.len(), 1);
+        assert_eq!(report.blockers[0].kind, "file_locked");
+        holder.unlock().unwrap();
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,44 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::db::models::OrganizerProgress;
+
+/// Name of the Tauri event progress is emitted on.
+pub const PROGRESS_EVENT: &str = "organizer-progress";
+
+/// Progress reporting and cancellation for executions and rollbacks.
+///
+/// Both are optional so background callers (the scheduler, crash recovery)
+/// can run without a UI listening.
+#[derive(Default)]
+pub struct RunControl<'a> {
+    on_progress: Option<&'a dyn Fn(&OrganizerProgress)>,
+    cancel: Option<&'a AtomicBool>,
+}
+
+impl<'a> RunControl<'a> {
+    pub fn new(on_progress: &'a dyn Fn(&OrganizerProgress), cancel: &'a AtomicBool) -> Self {
+        Self {
+            on_progress: Some(on_progress),
+            cancel: Some(cancel),
+        }
+    }
+
+    /// A control that reports nothing and is never cancelled.
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    pub fn report(&self, progress: &OrganizerProgress) {
+        if let Some(on_progress) = self.on_progress {
+            on_progress(progress);
+        }
+    }
+
+    /// Whether a cancel was requested. Checked between files, never while one
+    /// is being moved.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel
+            .map(|flag| flag.load(Ordering::SeqCst))
+            .unwrap_or(false)
+    }
+}
@@ -0,0 +1,305 @@
+use anyhow::{bail, Result};
+use rusqlite::Connection;
+use std::fs;
+use std::path::Path;
+
+use crate::db::models::{ExecutionResult, RunItem, SimulationItem, SimulationResult};
+use crate::db::queries::{runs, shortcuts};
+use crate::organizer::progress::RunControl;
+use crate::organizer::{errors, executor, link, scanner};
+
+/// Reconciles runs left `running` by a crash or forced shutdown.
+///
+/// Every `pending` journal entry is checked against the filesystem: items
+/// whose move evidently happened are marked `completed`, items that never
+/// started stay `pending`, and items whose file can no longer be found are
+/// marked `error`. The run is then marked `interrupted`, from where it can be
+/// resumed ([`resume_run`]) or rolled back. Returns the number of runs found.
+pub fn reconcile_interrupted_runs(conn: &Connection) -> Result<u32> {
+    let run_ids = runs::list_run_ids_by_status(conn, "running")?;
+
+    for run_id in &run_ids {
+        let items = runs::get_run_items(conn, run_id)?;
+        for item in items.iter().filter(|i| i.status == "pending") {
+            reconcile_item(conn, item)?;
+        }
+
+        let (moved, skipped, errors) = runs::count_run_items(conn, run_id)?;
+        runs::update_run_status(conn, run_id, "interrupted", moved, skipped, errors)?;
+        executor::record_run_errors(conn, run_id)?;
+        log::warn!(
+            "Run {} was interrupted: {} completed, {} skipped, {} errors before the crash",
+            run_id,
+            moved,
+            skipped,
+            errors
+        );
+    }
+
+    Ok(run_ids.len() as u32)
+}
+
+/// Resumes an interrupted run by executing its `pending` items again under the
+/// same run. Items whose original file is gone are marked `error`.
+pub fn resume_run(
+    conn: &Connection,
+    run_id: &str,
+    conflict_strategy: &str,
+    verify: bool,
+    control: &RunControl,
+) -> Result<ExecutionResult> {
+    let run = runs::get_run(conn, run_id)?
+        .ok_or_else(|| anyhow::anyhow!("Run not found: {}", run_id))?;
+    if run.status != "interrupted" {
+        bail!("Run {} is not interrupted (status: {})", run_id, run.status);
+    }
+
+    let mut items = Vec::new();
+    for pending in runs::get_run_items(conn, run_id)?
+        .into_iter()
+        .filter(|i| i.status == "pending")
+    {
+        let original = Path::new(&pending.original_path);
+
+        // A post-extract delete has nothing to re-plan: just finish it
+        if pending.action_type == "delete" {
+            match fs::remove_file(original) {
+                Ok(()) => runs::finish_run_item(conn, &pending.id, "completed", "", None)?,
+                Err(e) => {
+                    let msg = format!("Failed to delete {}: {}", original.display(), e);
+                    runs::fail_run_item(conn, &pending.id, "", errors::classify_io(&e), &msg)?;
+                }
+            }
+            continue;
+        }
+
+        let file = match scanner::file_entry_for_path(original) {
+            Ok(file) => file,
+            Err(e) => {
+                let msg = format!("Original file is no longer available: {:#}", e);
+                runs::fail_run_item(
+                    conn,
+                    &pending.id,
+                    &pending.destination_path,
+                    errors::classify(&e),
+                    &msg,
+                )?;
+                continue;
+            }
+        };
+
+        // The executor re-plans the pending entry in place when it runs it,
+        // so the journal never loses track of the file
+        items.push(SimulationItem {
+            file,
+            rule_id: pending.rule_id.unwrap_or_default(),
+            rule_name: String::new(),
+            action_type: pending.action_type,
+            destination: pending.destination_path,
+            conflict: false,
+            error: None,
+            post_action: "keep".to_string(),
+            post_destination: String::new(),
+            // Resumed items keep the strategy they were journaled with
+            conflict_strategy: pending.conflict_strategy,
+            final_path: String::new(),
+            skip_reason: None,
+            journal_id: Some(pending.id),
+        });
+    }
+
+    let count = items.len() as u32;
+    let simulation = SimulationResult {
+        items,
+        total_files: count,
+        matched_files: count,
+        unmatched_files: 0,
+        simulation_id: None,
+    };
+    let mut result =
+        executor::execute(conn, &simulation, run_id, conflict_strategy, verify, control)?;
+
+    // The executor only counted the resumed items; the run covers all of them
+    let (moved, skipped, errors) = runs::count_run_items(conn, run_id)?;
+    let status = if result.cancelled {
+        "cancelled"
+    } else {
+        executor::run_status(moved as u32, errors as u32)
+    };
+    runs::update_run_status(conn, run_id, status, moved, skipped, errors)?;
+    executor::record_run_errors(conn, run_id)?;
+
+    log::info!(
+        "Resumed run {}: {} item(s) executed, {} completed overall",
+        run_id,
+        result.total,
+        moved
+    );
+
+    result.total = (moved + skipped + errors) as u32;
+    result.moved = moved as u32;
+    result.skipped = skipped as u32;
+    result.errors = errors as u32;
+    Ok(result)
+}
+
+/// Decides the real state of a `pending` journal entry from the filesystem.
+fn reconcile_item(conn: &Connection, item: &RunItem) -> Result<()> {
+    let original = Path::new(&item.original_path);
+    let dest = Path::new(&item.destination_path);
+
+    match item.action_type.as_str() {
+        "delete" => {
+            if !original.exists() {
+                runs::finish_run_item(conn, &item.id, "completed", "", None)?;
+            }
+        }
+        "archive" => {
+            // The entry name is stored only after the archived copy was
+            // verified, so the original can safely go
+            if item.archive_entry.is_some() {
+                if original.exists() {
+                    fs::remove_file(original).ok();
+                }
+                if !original.exists() {
+                    runs::finish_run_item(conn, &item.id, "completed", &item.destination_path, None)?;
+                }
+            }
+        }
+        "link" => {
+            if let Some(created) = link::find_link(dest) {
+                record_link(conn, &item.id, &created, original)?;
+                runs::finish_run_item(conn, &item.id, "completed", &created.to_string_lossy(), None)?;
+            }
+        }
+        _ => {
+            // The original counts as present only if it is a real file, not
+            // the link a `move_and_link` leaves behind
+            let original_present = original
+                .symlink_metadata()
+                .map(|m| !m.file_type().is_symlink())
+                .unwrap_or(false);
+
+            // An overwrite moves the existing file aside before the move. If
+            // the move didn't finish, the destination is put back as it was
+            // and the item stays pending.
+            if let Some(backup) = item.replaced_path.as_deref().map(Path::new) {
+                if original_present {
+                    if backup.exists() {
+                        if dest.exists() {
+                            log::warn!("Removing partial copy {}", dest.display());
+                            fs::remove_file(dest).ok();
+                        }
+                        fs::rename(backup, dest).ok();
+                    }
+                    return Ok(());
+                }
+            }
+
+            if dest.exists() && !original_present {
+                if item.action_type == "move_and_link" {
+                    if let Some(created) = link::find_link(original) {
+                        record_link(conn, &item.id, &created, dest)?;
+                    }
+                }
+                runs::finish_run_item(conn, &item.id, "completed", &item.destination_path, None)?;
+            } else if original_present && dest.exists() && dest != original {
+                // A cross-device copy was cut short. The journaled destination
+                // did not exist before the run and the original is intact, so
+                // the partial copy is removed and the item stays pending.
+                log::warn!("Removing partial copy {}", dest.display());
+                fs::remove_file(dest).ok();
+            } else if !original_present {
+                runs::fail_run_item(
+                    conn,
+                    &item.id,
+                    &item.destination_path,
+                    errors::NOT_FOUND,
+                    "File not found at its original or planned location after an interrupted run",
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Records a link found during reconciliation, unless it is already tracked.
+fn record_link(conn: &Connection, run_item_id: &str, link_path: &Path, target: &Path) -> Result<()> {
+    if shortcuts::get_link_for_run_item(conn, run_item_id)?.is_none() {
+        shortcuts::create_link_shortcut(
+            conn,
+            run_item_id,
+            &link_path.to_string_lossy(),
+            &target.to_string_lossy(),
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support;
+
+    #[test]
+    fn test_reconcile_marks_interrupted_and_completes_moved_items() {
+        let conn = test_support::conn();
+        let run_id = test_support::run(&conn, "/tmp").id;
+        let dir = test_support::temp_dir("recovery");
+
+        // Moved before the crash: only the destination exists
+        let moved_dest = dir.join("moved.txt");
+        fs::write(&moved_dest, b"a").unwrap();
+        let moved = runs::create_run_item(
+            &conn,
+            &run_id,
+            None,
+            &dir.join("src.txt").to_string_lossy(),
+            &moved_dest.to_string_lossy(),
+            1,
+            "move",
+            "pending",
+            "suffix",
+        )
+        .unwrap();
+
+        // Never started: only the original exists
+        let waiting_src = dir.join("waiting.txt");
+        fs::write(&waiting_src, b"b").unwrap();
+        let waiting = runs::create_run_item(
+            &conn,
+            &run_id,
+            None,
+            &waiting_src.to_string_lossy(),
+            &dir.join("out").join("waiting.txt").to_string_lossy(),
+            1,
+            "move",
+            "pending",
+            "suffix",
+        )
+        .unwrap();
+
+        assert_eq!(reconcile_interrupted_runs(&conn).unwrap(), 1);
+
+        let run = runs::get_run(&conn, &run_id).unwrap().unwrap();
+        assert_eq!(run.status, "interrupted");
+        let items = runs::get_run_items(&conn, &run_id).unwrap();
+        let status_of = |id: &str| items.iter().find(|i| i.id == id).unwrap().status.clone();
+        assert_eq!(status_of(&moved.id), "completed");
+        assert_eq!(status_of(&waiting.id), "pending");
+
+        let result = resume_run(&conn, &run_id, "suffix", false, &RunControl::none()).unwrap();
+        assert_eq!(result.moved, 2);
+        assert!(dir.join("out").join("waiting.txt").exists());
+        assert_eq!(runs::get_run(&conn, &run_id).unwrap().unwrap().status, "completed");
+
+        // The resumed item finished on its own journal entry
+        let items = runs::get_run_items(&conn, &run_id).unwrap();
+        assert_eq!(items.len(), 2);
+        let resumed = items.iter().find(|i| i.id == waiting.id).unwrap();
+        assert_eq!(resumed.status, "completed");
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
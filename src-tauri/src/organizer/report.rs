@@ -0,0 +1,327 @@
+use anyhow::{bail, Result};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use crate::db::models::{RunItem, SimulationResult};
+
+/// Formats a plan or run can be exported to.
+pub const FORMATS: &[&str] = &["csv", "json", "markdown", "html"];
+
+/// A plan or a finished run laid out for review outside the app.
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub title: String,
+    pub generated_at: String,
+    pub rows: Vec<ReportRow>,
+    pub by_rule: Vec<ReportTotal>,
+    pub by_folder: Vec<ReportTotal>,
+    pub total_files: u32,
+    pub total_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportRow {
+    pub source: String,
+    pub destination: String,
+    pub rule: String,
+    pub action: String,
+    pub size: u64,
+    pub conflict: bool,
+    /// `planned`, `skipped` or `error` for plans; the item status for runs.
+    pub status: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportTotal {
+    pub name: String,
+    pub files: u32,
+    pub bytes: u64,
+}
+
+/// Actions whose destination name is expected to differ from the source's.
+const RENAMING_ACTIONS: &[&str] = &["rename", "archive", "extract", "delete"];
+
+/// Builds the report for a simulation. Destinations are the paths files will
+/// actually land on once conflicts are resolved.
+pub fn from_simulation(title: &str, simulation: &SimulationResult) -> Report {
+    let rows = simulation
+        .items
+        .iter()
+        .map(|item| ReportRow {
+            source: item.file.path.clone(),
+            destination: if item.final_path.is_empty() {
+                item.destination.clone()
+            } else {
+                item.final_path.clone()
+            },
+            rule: item.rule_name.clone(),
+            action: item.action_type.clone(),
+            size: item.file.size,
+            conflict: item.conflict,
+            status: if item.error.is_some() {
+                "error".to_string()
+            } else if item.skip_reason.is_some() {
+                "skipped".to_string()
+            } else {
+                "planned".to_string()
+            },
+        })
+        .collect();
+    build(title, rows)
+}
+
+/// Builds the report for a run. `rule_names` maps rule IDs to names; rules
+/// deleted since the run show up by ID.
+///
+/// Run items don't record whether a conflict happened, so it is inferred: a
+/// file moved aside by an `overwrite_*` strategy, or a file that landed under
+/// a different name than it had without being renamed by its action.
+pub fn from_run(title: &str, items: &[RunItem], rule_names: &HashMap<String, String>) -> Report {
+    let rows = items
+        .iter()
+        .map(|item| {
+            let rule = item
+                .rule_id
+                .as_ref()
+                .map(|id| rule_names.get(id).cloned().unwrap_or_else(|| id.clone()))
+                .unwrap_or_default();
+            let renamed = Path::new(&item.original_path).file_name()
+                != Path::new(&item.destination_path).file_name();
+            ReportRow {
+                source: item.original_path.clone(),
+                destination: item.destination_path.clone(),
+                rule,
+                action: item.action_type.clone(),
+                size: item.file_size.max(0) as u64,
+                conflict: item.replaced_path.is_some()
+                    || (renamed && !RENAMING_ACTIONS.contains(&item.action_type.as_str())),
+                status: item.status.clone(),
+            }
+        })
+        .collect();
+    build(title, rows)
+}
+
+fn build(title: &str, rows: Vec<ReportRow>) -> Report {
+    let mut by_rule: BTreeMap<String, ReportTotal> = BTreeMap::new();
+    let mut by_folder: BTreeMap<String, ReportTotal> = BTreeMap::new();
+
+    for row in &rows {
+        let folder = Path::new(&row.destination)
+            .parent()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default();
+        for (totals, key) in [(&mut by_rule, &row.rule), (&mut by_folder, &folder)] {
+            let total = totals.entry(key.clone()).or_insert_with(|| ReportTotal {
+                name: key.clone(),
+                files: 0,
+                bytes: 0,
+            });
+            total.files += 1;
+            total.bytes += row.size;
+        }
+    }
+
+    Report {
+        title: title.to_string(),
+        generated_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        total_files: rows.len() as u32,
+        total_bytes: rows.iter().map(|r| r.size).sum(),
+        rows,
+        by_rule: by_rule.into_values().collect(),
+        by_folder: by_folder.into_values().collect(),
+    }
+}
+
+/// Renders a report in one of [`FORMATS`].
+pub fn render(report: &Report, format: &str) -> Result<String> {
+    match format {
+        "csv" => Ok(to_csv(report)),
+        "json" => Ok(serde_json::to_string_pretty(report)?),
+        "markdown" => Ok(to_markdown(report)),
+        "html" => Ok(to_html(report)),
+        other => bail!("Unknown report format: {}", other),
+    }
+}
+
+/// File extension for a report format.
+pub fn extension(format: &str) -> &'static str {
+    match format {
+        "csv" => "csv",
+        "json" => "json",
+        "markdown" => "md",
+        _ => "html",
+    }
+}
+
+const COLUMNS: [&str; 7] = [
+    "source", "destination", "rule", "action", "size", "conflict", "status",
+];
+
+/// Column headings for the human-readable formats.
+const LABELS: [&str; 7] = ["Origem", "Destino", "Regra", "Ação", "Tamanho", "Conflito", "Status"];
+
+const TOTAL_SECTIONS: [&str; 2] = ["Por regra", "Por pasta de destino"];
+
+fn cells(row: &ReportRow) -> [String; 7] {
+    [
+        row.source.clone(),
+        row.destination.clone(),
+        row.rule.clone(),
+        row.action.clone(),
+        row.size.to_string(),
+        if row.conflict { "yes" } else { "no" }.to_string(),
+        row.status.clone(),
+    ]
+}
+
+/// One line per item. Totals don't fit a flat table, so they are left to the
+/// other formats.
+fn to_csv(report: &Report) -> String {
+    let mut out = COLUMNS.join(",");
+    out.push_str("\r\n");
+    for row in &report.rows {
+        let line: Vec<String> = cells(row).iter().map(|c| csv_field(c)).collect();
+        out.push_str(&line.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn to_markdown(report: &Report) -> String {
+    let md = |s: &str| s.replace('|', "\\|");
+    let mut out = format!(
+        "# {}\n\nGerado em {} · {} arquivos · {} bytes\n\n",
+        report.title, report.generated_at, report.total_files, report.total_bytes
+    );
+
+    for (heading, totals) in TOTAL_SECTIONS.iter().zip([&report.by_rule, &report.by_folder]) {
+        out.push_str(&format!("## {}\n\n| Nome | Arquivos | Bytes |\n|---|---:|---:|\n", heading));
+        for total in totals {
+            out.push_str(&format!("| {} | {} | {} |\n", md(&total.name), total.files, total.bytes));
+        }
+        out.push('\n');
+    }
+
+    out.push_str("## Itens\n\n| ");
+    out.push_str(&LABELS.join(" | "));
+    out.push_str(" |\n|");
+    out.push_str(&"---|".repeat(COLUMNS.len()));
+    out.push('\n');
+    for row in &report.rows {
+        let line: Vec<String> = cells(row).iter().map(|c| md(c)).collect();
+        out.push_str(&format!("| {} |\n", line.join(" | ")));
+    }
+    out
+}
+
+fn to_html(report: &Report) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+         <style>body{{font-family:sans-serif}}table{{border-collapse:collapse;margin-bottom:2em}}\
+         td,th{{border:1px solid #ccc;padding:4px 8px;text-align:left}}</style>\n</head>\n<body>\n\
+         <h1>{title}</h1>\n<p>Gerado em {} · {} arquivos · {} bytes</p>\n",
+        html(&report.generated_at),
+        report.total_files,
+        report.total_bytes,
+        title = html(&report.title),
+    );
+
+    for (heading, totals) in TOTAL_SECTIONS.iter().zip([&report.by_rule, &report.by_folder]) {
+        out.push_str(&format!(
+            "<h2>{}</h2>\n<table>\n<tr><th>Nome</th><th>Arquivos</th><th>Bytes</th></tr>\n",
+            heading
+        ));
+        for total in totals {
+            out.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                html(&total.name),
+                total.files,
+                total.bytes
+            ));
+        }
+        out.push_str("</table>\n");
+    }
+
+    out.push_str("<h2>Itens</h2>\n<table>\n<tr>");
+    for column in LABELS {
+        out.push_str(&format!("<th>{}</th>", column));
+    }
+    out.push_str("</tr>\n");
+    for row in &report.rows {
+        out.push_str("<tr>");
+        for cell in cells(row) {
+            out.push_str(&format!("<td>{}</td>", html(&cell)));
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</table>\n</body>\n</html>\n");
+    out
+}
+
+fn html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::SimulationItem;
+    use crate::db::test_support;
+
+    fn make_item(path: &str, destination: &str, rule: &str, size: u64) -> SimulationItem {
+        SimulationItem {
+            rule_name: rule.to_string(),
+            ..test_support::item(test_support::file_entry(path, size), destination)
+        }
+    }
+
+    #[test]
+    fn test_simulation_report_totals_and_csv() {
+        let mut clash = make_item("/in/b.pdf", "/out/Docs/b.pdf", "Docs", 20);
+        clash.conflict = true;
+        clash.final_path = "/out/Docs/b (1).pdf".to_string();
+        let simulation = SimulationResult {
+            items: vec![
+                make_item("/in/a, final.pdf", "/out/Docs/a, final.pdf", "Docs", 10),
+                clash,
+                make_item("/in/c.jpg", "/out/Pics/c.jpg", "Pictures", 5),
+            ],
+            total_files: 3,
+            matched_files: 3,
+            unmatched_files: 0,
+            simulation_id: None,
+        };
+
+        let report = from_simulation("Plan", &simulation);
+        assert_eq!(report.total_bytes, 35);
+        assert_eq!(report.by_rule.len(), 2);
+        assert_eq!((report.by_rule[0].files, report.by_rule[0].bytes), (2, 30));
+        assert_eq!(report.by_folder[1].name, "/out/Pics");
+
+        let csv = render(&report, "csv").unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "source,destination,rule,action,size,conflict,status");
+        assert_eq!(
+            lines[1],
+            "\"/in/a, final.pdf\",\"/out/Docs/a, final.pdf\",Docs,move_to_folder,10,no,planned"
+        );
+        assert_eq!(lines[2], "/in/b.pdf,/out/Docs/b (1).pdf,Docs,move_to_folder,20,yes,planned");
+
+        assert!(render(&report, "html").unwrap().contains("<td>/out/Pics</td>"));
+        assert!(render(&report, "pdf").is_err());
+    }
+}
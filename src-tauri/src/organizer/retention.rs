@@ -0,0 +1,239 @@
+use anyhow::{Context, Result};
+use chrono::{Duration, NaiveDateTime, Utc};
+use rusqlite::Connection;
+
+use crate::db::models::{DatabaseSize, PruneResult};
+use crate::db::queries::{runs, settings};
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Setting holding when the scheduler last applied the retention policy.
+const LAST_PRUNED_KEY: &str = "history_last_pruned_at";
+
+/// How much run history to keep. A limit of `0` disables it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// Prune runs that started more than this many days ago.
+    pub keep_days: u32,
+    /// Prune everything but this many most recent runs.
+    pub keep_runs: u32,
+    /// Runs that can still be rolled back are kept for at least this many
+    /// days, whatever the other limits say.
+    pub keep_undoable_days: u32,
+}
+
+impl RetentionPolicy {
+    pub fn from_settings(conn: &Connection) -> Result<Self> {
+        let number = |key: &str, default: u32| -> Result<u32> {
+            Ok(settings::get_setting(conn, key)?
+                .and_then(|value| value.trim().parse().ok())
+                .unwrap_or(default))
+        };
+        Ok(Self {
+            keep_days: number("history_retention_days", 0)?,
+            keep_runs: number("history_retention_runs", 0)?,
+            keep_undoable_days: number("history_keep_undoable_days", 30)?,
+        })
+    }
+
+    fn is_unlimited(&self) -> bool {
+        self.keep_days == 0 && self.keep_runs == 0
+    }
+}
+
+fn cutoff(now: NaiveDateTime, days: u32) -> String {
+    (now - Duration::days(i64::from(days))).format(TIMESTAMP_FORMAT).to_string()
+}
+
+/// Picks the runs the policy lets go of, as of `now`.
+fn runs_to_prune(
+    conn: &Connection,
+    policy: &RetentionPolicy,
+    now: NaiveDateTime,
+) -> Result<Vec<String>> {
+    if policy.is_unlimited() {
+        return Ok(Vec::new());
+    }
+
+    let expired_before = (policy.keep_days > 0).then(|| cutoff(now, policy.keep_days));
+    let protected_since = cutoff(now, policy.keep_undoable_days);
+
+    let candidates = runs::list_prunable_runs(conn)?;
+    Ok(candidates
+        .into_iter()
+        .enumerate()
+        .filter(|(index, (_, status, started_at, undoable))| {
+            let over_count = policy.keep_runs > 0 && *index >= policy.keep_runs as usize;
+            let too_old = expired_before
+                .as_ref()
+                .is_some_and(|before| started_at.as_str() < before.as_str());
+            // Interrupted runs wait to be resumed or rolled back
+            let protected = status == "interrupted"
+                || (*undoable && started_at.as_str() >= protected_since.as_str());
+            (over_count || too_old) && !protected
+        })
+        .map(|(_, (id, _, _, _))| id)
+        .collect())
+}
+
+/// Applies the retention policy: the items of every run it lets go of are
+/// deleted and the run is marked pruned, which rules out rolling it back.
+pub fn prune_history(conn: &Connection, policy: &RetentionPolicy) -> Result<PruneResult> {
+    let now = Utc::now().naive_utc();
+    let mut result = PruneResult::default();
+
+    for run_id in runs_to_prune(conn, policy, now)? {
+        result.items_removed += runs::prune_run(conn, &run_id)?;
+        result.runs_pruned += 1;
+    }
+
+    if result.runs_pruned > 0 {
+        log::info!(
+            "Pruned {} run(s) from history, {} item(s) removed",
+            result.runs_pruned,
+            result.items_removed
+        );
+    }
+    Ok(result)
+}
+
+/// Applies the configured policy if it has not run in the last day. Called by
+/// the scheduler loop; returns `None` when it was not due.
+pub fn prune_if_due(conn: &Connection) -> Result<Option<PruneResult>> {
+    let now = Utc::now().naive_utc();
+    let last = settings::get_setting(conn, LAST_PRUNED_KEY)?
+        .and_then(|value| NaiveDateTime::parse_from_str(&value, TIMESTAMP_FORMAT).ok());
+    if last.is_some_and(|last| now - last < Duration::days(1)) {
+        return Ok(None);
+    }
+
+    let policy = RetentionPolicy::from_settings(conn)?;
+    let result = prune_history(conn, &policy)?;
+    settings::set_setting(conn, LAST_PRUNED_KEY, &now.format(TIMESTAMP_FORMAT).to_string())?;
+    Ok(Some(result))
+}
+
+/// Reports the size of the database and how much history it holds.
+pub fn database_size(conn: &Connection) -> Result<DatabaseSize> {
+    let pragma = |name: &str| -> Result<u64> {
+        conn.query_row(&format!("PRAGMA {}", name), [], |row| row.get::<_, i64>(0))
+            .map(|value| value.max(0) as u64)
+            .with_context(|| format!("Failed to read PRAGMA {}", name))
+    };
+    let page_size = pragma("page_size")?;
+
+    let (runs, pruned_runs): (u32, u32) = conn
+        .query_row(
+            "SELECT COUNT(*), COALESCE(SUM(pruned_at IS NOT NULL), 0) FROM runs",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .context("Failed to count runs")?;
+    let run_items: u32 = conn
+        .query_row("SELECT COUNT(*) FROM run_items", [], |row| row.get(0))
+        .context("Failed to count run items")?;
+
+    Ok(DatabaseSize {
+        total_bytes: pragma("page_count")? * page_size,
+        free_bytes: pragma("freelist_count")? * page_size,
+        runs,
+        pruned_runs,
+        run_items,
+    })
+}
+
+/// Rebuilds the database file to give the space of deleted rows back to the
+/// disk. Returns the size afterwards.
+pub fn vacuum(conn: &Connection) -> Result<DatabaseSize> {
+    conn.execute_batch("VACUUM").context("Failed to vacuum database")?;
+    database_size(conn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::queries::shortcuts;
+    use crate::db::test_support;
+
+    /// Creates a run that started `days_ago` days ago with one item, which
+    /// was rolled back along with the run if its status says so.
+    fn run(conn: &Connection, status: &str, days_ago: i64) -> String {
+        let run = test_support::run(conn, "/tmp");
+        let started = (Utc::now() - Duration::days(days_ago))
+            .format(TIMESTAMP_FORMAT)
+            .to_string();
+        conn.execute(
+            "UPDATE runs SET status = ?1, started_at = ?2 WHERE id = ?3",
+            rusqlite::params![status, started, run.id],
+        )
+        .unwrap();
+        let item_status = if status == "rolled_back" { "rolled_back" } else { "completed" };
+        runs::create_run_item(
+            conn, &run.id, None, "/tmp/a.txt", "/tmp/docs/a.txt", 1,
+            "move_to_folder", item_status, "suffix",
+        )
+        .unwrap();
+        run.id
+    }
+
+    #[test]
+    fn test_prune_keeps_recent_and_undoable_runs() {
+        let conn = test_support::conn();
+        let old_done = run(&conn, "rolled_back", 90);
+        let old_undoable = run(&conn, "completed", 60);
+        let recent_undoable = run(&conn, "completed", 10);
+        let recent_done = run(&conn, "rolled_back", 5);
+        let interrupted = run(&conn, "interrupted", 120);
+
+        // A link made by the old run; pruning must not leave its record behind
+        let linked = &runs::get_run_items(&conn, &old_undoable).unwrap()[0];
+        shortcuts::create_link_shortcut(&conn, &linked.id, "/tmp/a.lnk", "/tmp/docs/a.txt")
+            .unwrap();
+
+        let policy = RetentionPolicy { keep_days: 30, keep_runs: 0, keep_undoable_days: 30 };
+        let result = prune_history(&conn, &policy).unwrap();
+        assert_eq!(result.runs_pruned, 2);
+        assert_eq!(result.items_removed, 2);
+
+        for id in [&old_done, &old_undoable] {
+            let run = runs::get_run(&conn, id).unwrap().unwrap();
+            assert!(run.pruned_at.is_some());
+            assert!(runs::get_run_items(&conn, id).unwrap().is_empty());
+        }
+        for id in [&recent_undoable, &recent_done, &interrupted] {
+            assert!(runs::get_run(&conn, id).unwrap().unwrap().pruned_at.is_none());
+        }
+        let links: u32 = conn
+            .query_row("SELECT COUNT(*) FROM shortcuts", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(links, 0);
+
+        // Only the most recent run is kept, but recent undoable runs survive
+        let policy = RetentionPolicy { keep_days: 0, keep_runs: 1, keep_undoable_days: 30 };
+        assert_eq!(prune_history(&conn, &policy).unwrap().runs_pruned, 0);
+        let policy = RetentionPolicy { keep_days: 0, keep_runs: 1, keep_undoable_days: 7 };
+        assert_eq!(prune_history(&conn, &policy).unwrap().runs_pruned, 1);
+        assert!(runs::get_run(&conn, &recent_undoable).unwrap().unwrap().pruned_at.is_some());
+
+        let size = database_size(&conn).unwrap();
+        assert_eq!(size.runs, 5);
+        assert_eq!(size.pruned_runs, 3);
+        assert_eq!(size.run_items, 2);
+    }
+
+    #[test]
+    fn test_prune_keeps_runs_with_errors_that_can_be_undone() {
+        let conn = test_support::conn();
+        let newest = run(&conn, "rolled_back", 5);
+        // Partly failed, but its completed item can still be rolled back
+        let with_errors = run(&conn, "completed_with_errors", 10);
+        let rolled_back = run(&conn, "rolled_back", 20);
+
+        let policy = RetentionPolicy { keep_days: 0, keep_runs: 1, keep_undoable_days: 30 };
+        assert_eq!(prune_history(&conn, &policy).unwrap().runs_pruned, 1);
+        for id in [&newest, &with_errors] {
+            assert!(runs::get_run(&conn, id).unwrap().unwrap().pruned_at.is_none());
+        }
+        assert!(runs::get_run(&conn, &rolled_back).unwrap().unwrap().pruned_at.is_some());
+    }
+}
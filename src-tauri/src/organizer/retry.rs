@@ -0,0 +1,315 @@
+use anyhow::{bail, Result};
+use rusqlite::Connection;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::db::models::{ExecutionResult, FileEntry, RunItem, SimulationItem, SimulationResult};
+use crate::db::queries::{rules, runs};
+use crate::organizer::progress::RunControl;
+use crate::organizer::{errors, executor, preflight, scanner, simulator};
+
+/// A failed item that cannot be planned again, with why and its error code.
+struct Unplanned<'a> {
+    item: &'a RunItem,
+    reason: String,
+    code: &'static str,
+}
+
+/// Executes the failed items of a run again under a new `retry` run linked to
+/// it, and returns the new run's result.
+///
+/// Each file is planned again by its rule with the current conflict settings.
+/// Files whose rule was deleted or no longer matches them go back to the
+/// destination they failed to reach. Files no longer at their original path,
+/// or that nothing can be planned for, are recorded as failed again in the new
+/// run. Files an earlier retry already organized are left out.
+pub fn retry_run_errors(
+    conn: &Connection,
+    run_id: &str,
+    conflict_strategy: &str,
+    verify: bool,
+    control: &RunControl,
+) -> Result<ExecutionResult> {
+    let parent = runs::get_run(conn, run_id)?
+        .ok_or_else(|| anyhow::anyhow!("Run not found: {}", run_id))?;
+    if parent.status == "running" {
+        bail!("Run {} is still running", run_id);
+    }
+
+    let retried = runs::retried_paths(conn, run_id)?;
+    let items = runs::get_run_items(conn, run_id)?;
+    let mut seen = HashSet::new();
+    let failed: Vec<&RunItem> = items
+        .iter()
+        .filter(|i| i.status == "error" && !retried.contains(&i.original_path))
+        .filter(|i| seen.insert((i.original_path.as_str(), i.action_type.as_str())))
+        .collect();
+    if failed.is_empty() {
+        bail!("Run {} has no failed items left to retry", run_id);
+    }
+
+    let mut planned = Vec::new();
+    let mut unplanned = Vec::new();
+    for item in failed {
+        let file = match scanner::file_entry_for_path(Path::new(&item.original_path)) {
+            Ok(file) => file,
+            Err(e) => {
+                unplanned.push(Unplanned {
+                    item,
+                    reason: format!("Original file is no longer available: {:#}", e),
+                    code: errors::classify(&e),
+                });
+                continue;
+            }
+        };
+        match replan(conn, item, &file, conflict_strategy)? {
+            Some(planned_item) => planned.push(planned_item),
+            None => unplanned.push(Unplanned {
+                item,
+                reason: format!("Nothing left to retry {} with", item.original_path),
+                code: errors::OTHER,
+            }),
+        }
+    }
+
+    let count = planned.len() as u32;
+    let simulation = SimulationResult {
+        items: planned,
+        total_files: count + unplanned.len() as u32,
+        matched_files: count,
+        unmatched_files: 0,
+        simulation_id: None,
+    };
+    let report = preflight::preflight(&simulation);
+    if !report.go {
+        bail!("Retry blocked: {}", preflight::describe_blockers(&report));
+    }
+
+    let run = runs::create_run(conn, &parent.profile_id, "retry", &parent.source_folder)?;
+    runs::set_run_parent(conn, &run.id, run_id)?;
+    runs::set_run_plan(conn, &run.id, &simulation)?;
+    runs::add_run_total_files(conn, &run.id, unplanned.len() as i32)?;
+
+    let mut messages = Vec::new();
+    for failure in &unplanned {
+        let item = failure.item;
+        let journaled = runs::create_run_item(
+            conn,
+            &run.id,
+            item.rule_id.as_deref(),
+            &item.original_path,
+            &item.destination_path,
+            item.file_size,
+            &item.action_type,
+            "pending",
+            conflict_strategy,
+        )?;
+        runs::fail_run_item(
+            conn,
+            &journaled.id,
+            &item.destination_path,
+            failure.code,
+            &failure.reason,
+        )?;
+        messages.push(failure.reason.clone());
+    }
+
+    let mut result =
+        executor::execute(conn, &simulation, &run.id, conflict_strategy, verify, control)
+            .inspect_err(|e| {
+                // Kept on the run, which recovery marks `interrupted`
+                runs::set_run_error(conn, &run.id, Some(&format!("{:#}", e))).ok();
+            })?;
+
+    // The executor only counted the planned items; the run covers all of them
+    let (moved, skipped, errors) = runs::count_run_items(conn, &run.id)?;
+    let status = if result.cancelled {
+        "cancelled"
+    } else {
+        executor::run_status(moved as u32, errors as u32)
+    };
+    runs::update_run_status(conn, &run.id, status, moved, skipped, errors)?;
+    executor::record_run_errors(conn, &run.id)?;
+
+    log::info!(
+        "Retried {} failed item(s) of run {} as run {}: {} completed",
+        result.total as usize + unplanned.len(),
+        run_id,
+        run.id,
+        moved
+    );
+
+    result.total += unplanned.len() as u32;
+    result.errors += unplanned.len() as u32;
+    messages.append(&mut result.error_messages);
+    result.error_messages = messages;
+    Ok(result)
+}
+
+/// Plans a failed item again: by its rule when it still exists and matches
+/// the file, otherwise towards the destination it failed to reach.
+fn replan(
+    conn: &Connection,
+    item: &RunItem,
+    file: &FileEntry,
+    conflict_strategy: &str,
+) -> Result<Option<SimulationItem>> {
+    let rule = match &item.rule_id {
+        Some(rule_id) => rules::get_rule(conn, rule_id)?,
+        None => None,
+    };
+    if let Some(rule) = rule {
+        let conditions = HashMap::from([(rule.id.clone(), rules::get_conditions(conn, &rule.id)?)]);
+        let actions = HashMap::from([(rule.id.clone(), rules::get_actions(conn, &rule.id)?)]);
+        let plan = simulator::simulate(
+            std::slice::from_ref(file),
+            std::slice::from_ref(&rule),
+            &conditions,
+            &actions,
+            conflict_strategy,
+        );
+        if let Some(planned) = plan.items.into_iter().find(|p| p.action_type == item.action_type) {
+            return Ok(Some(planned));
+        }
+    }
+
+    if item.destination_path.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(SimulationItem {
+        file: file.clone(),
+        rule_id: item.rule_id.clone().unwrap_or_default(),
+        rule_name: String::new(),
+        action_type: item.action_type.clone(),
+        destination: item.destination_path.clone(),
+        conflict: false,
+        error: None,
+        post_action: "keep".to_string(),
+        post_destination: String::new(),
+        // Falls back to the current conflict setting
+        conflict_strategy: String::new(),
+        final_path: String::new(),
+        skip_reason: None,
+        journal_id: None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support;
+    use std::fs;
+
+    #[test]
+    fn test_retry_creates_linked_run_for_failed_items() {
+        let conn = test_support::conn();
+        test_support::rule(&conn, "r1", "Docs");
+
+        let dir = test_support::temp_dir("retry");
+        let docs = dir.join("docs");
+        let source = dir.join("report.pdf");
+        fs::write(&source, b"report").unwrap();
+        let gone = dir.join("gone.pdf");
+
+        rules::add_condition(&conn, "r1", "extension", "equals", "pdf", "AND").unwrap();
+        rules::add_action(
+            &conn,
+            "r1",
+            "move_to_folder",
+            &docs.to_string_lossy(),
+            "",
+            "",
+            "keep",
+            "",
+            "",
+        )
+        .unwrap();
+
+        // A run where both files failed, e.g. for lack of permission
+        let run = test_support::run(&conn, &dir.to_string_lossy());
+        for path in [&source, &gone] {
+            let item = runs::create_run_item(
+                &conn,
+                &run.id,
+                Some("r1"),
+                &path.to_string_lossy(),
+                &docs.join("x.pdf").to_string_lossy(),
+                6,
+                "move_to_folder",
+                "pending",
+                "suffix",
+            )
+            .unwrap();
+            runs::fail_run_item(&conn, &item.id, "", errors::PERMISSION_DENIED, "denied").unwrap();
+        }
+        runs::update_run_status(&conn, &run.id, "error", 0, 0, 2).unwrap();
+
+        let result =
+            retry_run_errors(&conn, &run.id, "suffix", false, &RunControl::none()).unwrap();
+        assert_eq!(result.moved, 1);
+        assert_eq!(result.errors, 1);
+        assert!(docs.join("report.pdf").exists());
+
+        let retry = runs::get_run(&conn, &result.run_id).unwrap().unwrap();
+        assert_eq!(retry.run_type, "retry");
+        assert_eq!(retry.parent_run_id.as_deref(), Some(run.id.as_str()));
+        assert_eq!(retry.status, "completed_with_errors");
+        let again = runs::get_run_items(&conn, &retry.id).unwrap();
+        assert!(again
+            .iter()
+            .any(|i| i.status == "error" && i.error_code.as_deref() == Some(errors::NOT_FOUND)));
+
+        // The moved file is not retried again; the missing one still is
+        let second =
+            retry_run_errors(&conn, &run.id, "suffix", false, &RunControl::none()).unwrap();
+        assert_eq!(second.total, 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_retry_after_rule_was_deleted() {
+        let conn = test_support::conn();
+        test_support::rule(&conn, "r1", "Archives");
+
+        let dir = test_support::temp_dir("retry-deleted-rule");
+        let archive = dir.join("bundle.zip");
+        let mut zip = zip::ZipWriter::new(fs::File::create(&archive).unwrap());
+        zip.start_file("a.txt", zip::write::SimpleFileOptions::default()).unwrap();
+        std::io::Write::write_all(&mut zip, b"hello").unwrap();
+        zip.finish().unwrap();
+        let out = dir.join("out");
+
+        let run = test_support::run(&conn, &dir.to_string_lossy());
+        let item = runs::create_run_item(
+            &conn,
+            &run.id,
+            Some("r1"),
+            &archive.to_string_lossy(),
+            &out.to_string_lossy(),
+            5,
+            "extract",
+            "pending",
+            "suffix",
+        )
+        .unwrap();
+        let dest = out.to_string_lossy();
+        runs::fail_run_item(&conn, &item.id, &dest, errors::DISK_FULL, "disk full").unwrap();
+        runs::update_run_status(&conn, &run.id, "error", 0, 0, 1).unwrap();
+
+        // The items keep no rule once it is gone
+        conn.execute("DELETE FROM rules WHERE id = 'r1'", []).unwrap();
+
+        let result =
+            retry_run_errors(&conn, &run.id, "suffix", false, &RunControl::none()).unwrap();
+        assert_eq!((result.moved, result.errors), (1, 0));
+        assert_eq!(fs::read(out.join("a.txt")).unwrap(), b"hello");
+
+        let retry = runs::get_run(&conn, &result.run_id).unwrap().unwrap();
+        assert_eq!(retry.status, "completed");
+        let extracted = runs::get_run_items(&conn, &retry.id).unwrap();
+        assert!(extracted.iter().all(|i| i.rule_id.is_none()));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,108 +1,1266 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use rusqlite::Connection;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use crate::db::queries::runs;
+use crate::db::models::{
+    ChainStep, DependentRun, FileChain, OrganizerProgress, RollbackDependencies,
+    RollbackItemReport, RollbackPreview, RollbackPreviewItem, RollbackResult, RunItem,
+};
+use crate::db::queries::{runs, shortcuts};
+use crate::organizer::progress::RunControl;
+use crate::organizer::{archive, conflict, errors, integrity, link};
 
-/// Rolls back all completed items in a run, moving files back to their original locations.
-/// Returns the number of items successfully rolled back.
-pub fn rollback_run(conn: &Connection, run_id: &str) -> Result<u32> {
+/// Where rollback puts a file back.
+#[derive(Debug, PartialEq)]
+pub enum RestoreTarget {
+    /// The original path is free.
+    Original,
+    /// The original path is taken again: restore here instead. `resolution`
+    /// is the strategy that picked the path.
+    Elsewhere {
+        resolution: &'static str,
+        path: PathBuf,
+    },
+    /// The original path is taken and the strategy is `skip`.
+    Skip,
+}
+
+/// Decides where a file goes back to without ever replacing what is now at
+/// its original path. Strategies for a taken path:
+/// - `"suffix"`: Restores beside it as `name_1.ext`, `name_2.ext`, ...
+/// - `"skip"`: Leaves the file where the run put it.
+/// - `"restored_folder"`: Restores into a `Restored/` folder next to it,
+///   suffixing there if needed.
+pub fn restore_target(
+    original: &Path,
+    strategy: &str,
+    exists: &dyn Fn(&Path) -> bool,
+) -> RestoreTarget {
+    if !exists(original) {
+        return RestoreTarget::Original;
+    }
+    match strategy {
+        "skip" => RestoreTarget::Skip,
+        "restored_folder" => {
+            let parent = original.parent().unwrap_or_else(|| Path::new("."));
+            let candidate = parent
+                .join("Restored")
+                .join(original.file_name().unwrap_or_default());
+            let path = if exists(&candidate) {
+                conflict::resolve_path(&candidate, "suffix", exists)
+            } else {
+                candidate
+            };
+            RestoreTarget::Elsewhere {
+                resolution: "restored_folder",
+                path,
+            }
+        }
+        _ => RestoreTarget::Elsewhere {
+            resolution: "suffix",
+            path: conflict::resolve_path(original, "suffix", exists),
+        },
+    }
+}
+
+/// Which items of a run to roll back.
+#[derive(Debug, Clone, Copy)]
+pub enum RollbackScope<'a> {
+    All,
+    /// These run items, by ID.
+    Items(&'a [String]),
+    /// The items a rule produced.
+    Rule(&'a str),
+}
+
+impl RollbackScope<'_> {
+    fn includes(&self, item: &RunItem) -> bool {
+        match self {
+            RollbackScope::All => true,
+            RollbackScope::Items(ids) => ids.contains(&item.id),
+            RollbackScope::Rule(rule_id) => item.rule_id.as_deref() == Some(*rule_id),
+        }
+    }
+}
+
+/// How rollback handles a file that a later run moved again.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChainMode {
+    /// Roll back the later runs' moves of the file first, latest run first.
+    Cascade,
+    /// Move the file straight back from where the last run left it. Falls
+    /// back to `Cascade` for files that can't be (see [`FileChain::direct`]).
+    Latest,
+}
+
+impl ChainMode {
+    /// `"latest"`, or anything else for `Cascade`.
+    pub fn parse(mode: &str) -> Self {
+        match mode {
+            "latest" => ChainMode::Latest,
+            _ => ChainMode::Cascade,
+        }
+    }
+}
+
+/// Whether an action leaves the file itself at its destination, where a later
+/// run may have picked it up again.
+fn places_file(action_type: &str) -> bool {
+    !matches!(action_type, "link" | "extract" | "delete")
+}
+
+/// Follows a run item's file through later runs. Each step is a completed
+/// item of a later run that moved the file on from where the previous one
+/// left it; the chain ends at the file's current location.
+pub fn trace_chain(conn: &Connection, item: &RunItem) -> Result<Vec<RunItem>> {
+    let mut steps: Vec<RunItem> = Vec::new();
+    if !places_file(&item.action_type) {
+        return Ok(steps);
+    }
+
+    let mut path = item.destination_path.clone();
+    let mut run_id = item.run_id.clone();
+    let mut since = item.executed_at.clone().unwrap_or_default();
+    while let Some(next) = runs::find_next_move(conn, &path, &run_id, &since)? {
+        // Items executed within the same second could otherwise loop
+        if steps.iter().any(|s| s.id == next.id) {
+            break;
+        }
+        path = next.destination_path.clone();
+        run_id = next.run_id.clone();
+        since = next.executed_at.clone().unwrap_or_default();
+        let last = !places_file(&next.action_type);
+        steps.push(next);
+        if last {
+            break;
+        }
+    }
+    Ok(steps)
+}
+
+/// Whether a file can be moved straight back from the end of its chain: every
+/// later step only moved it, with nothing linked, archived, deleted or set
+/// aside that its own rollback would have to undo.
+fn restorable_directly(item: &RunItem, steps: &[RunItem]) -> bool {
+    item.action_type != "archive"
+        && steps.iter().all(|step| {
+            !matches!(step.action_type.as_str(), "archive" | "delete" | "move_and_link")
+                && step.replaced_path.is_none()
+        })
+}
+
+/// Finds the later runs that moved files of a run again, so they can be
+/// shown before the run picked by `scope` is rolled back.
+pub fn rollback_dependencies(
+    conn: &Connection,
+    run_id: &str,
+    scope: RollbackScope,
+) -> Result<RollbackDependencies> {
     let items = runs::get_run_items(conn, run_id)
         .context("Failed to get run items for rollback")?;
 
-    let mut rolled_back: u32 = 0;
-    let mut errors: u32 = 0;
-
-    for item in &items {
-        // Only rollback items that were successfully completed
-        if item.status != "completed" {
+    let mut chains = Vec::new();
+    let mut counts: HashMap<String, u32> = HashMap::new();
+    for item in pending(&items, scope) {
+        let steps = trace_chain(conn, item)?;
+        if steps.is_empty() {
             continue;
         }
+        for step in &steps {
+            *counts.entry(step.run_id.clone()).or_default() += 1;
+        }
+        let last = &steps[steps.len() - 1];
+        chains.push(FileChain {
+            run_item_id: item.id.clone(),
+            original_path: item.original_path.clone(),
+            destination_path: item.destination_path.clone(),
+            latest_path: places_file(&last.action_type).then(|| last.destination_path.clone()),
+            direct: restorable_directly(item, &steps),
+            steps: steps
+                .into_iter()
+                .map(|step| ChainStep {
+                    run_id: step.run_id,
+                    run_item_id: step.id,
+                    action_type: step.action_type,
+                    from: step.original_path,
+                    to: step.destination_path,
+                    executed_at: step.executed_at,
+                })
+                .collect(),
+        });
+    }
+
+    let mut dependent_runs = Vec::new();
+    for (id, items) in counts {
+        if let Some(run) = runs::get_run(conn, &id)? {
+            dependent_runs.push(DependentRun {
+                run_id: run.id,
+                started_at: run.started_at,
+                source_folder: run.source_folder,
+                items,
+            });
+        }
+    }
+    dependent_runs.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+
+    Ok(RollbackDependencies {
+        run_id: run_id.to_string(),
+        chains,
+        dependent_runs,
+    })
+}
+
+/// Works out what rolling back the items picked by `scope` would do to each
+/// file, without moving anything. Original paths are resolved with `strategy`
+/// as [`rollback_run`] would, counting paths earlier items would take.
+pub fn simulate_rollback(
+    conn: &Connection,
+    run_id: &str,
+    scope: RollbackScope,
+    strategy: &str,
+) -> Result<RollbackPreview> {
+    let items = runs::get_run_items(conn, run_id)
+        .context("Failed to get run items for rollback")?;
+
+    let mut preview = RollbackPreview {
+        run_id: run_id.to_string(),
+        items: Vec::new(),
+        restored: 0,
+        relocated: 0,
+        kept: 0,
+        missing: 0,
+        irreversible: 0,
+        modified: 0,
+    };
+    let mut claimed: HashSet<PathBuf> = HashSet::new();
+
+    for item in pending(&items, scope) {
+        let steps = trace_chain(conn, item)?;
+        let reference = steps.last().unwrap_or(item);
+        let current = Path::new(&reference.destination_path);
+        let original = Path::new(&item.original_path);
+
+        let destination_exists = path_taken(current);
+        // The link a `move_and_link` left behind is removed before restoring
+        let own_link = item.action_type == "move_and_link"
+            && original.symlink_metadata().is_ok_and(|m| m.file_type().is_symlink());
+        let original_free = own_link || !path_taken(original);
+        let content_changed = destination_exists
+            && (places_file(&reference.action_type) || reference.action_type == "extract")
+            && integrity::changed_since(
+                current,
+                reference.file_size.max(0) as u64,
+                reference.dest_mtime.as_deref(),
+                reference.checksum.as_deref(),
+            )
+            .unwrap_or(false);
+
+        let ends_deleted = !places_file(&reference.action_type) && !steps.is_empty();
+        let (outcome, target) = match item.action_type.as_str() {
+            "delete" => ("irreversible", None),
+            // Extracted files edited since are kept, like in `rollback_run`
+            "extract" if content_changed => ("keep", None),
+            "extract" | "link" => ("remove", None),
+            _ if ends_deleted => ("irreversible", None),
+            _ if !destination_exists => ("missing", None),
+            _ => {
+                let taken = |p: &Path| {
+                    claimed.contains(p) || (!(own_link && p == original) && path_taken(p))
+                };
+                match restore_target(original, strategy, &taken) {
+                    RestoreTarget::Original => ("restore", Some(original.to_path_buf())),
+                    RestoreTarget::Elsewhere { path, .. } => ("relocate", Some(path)),
+                    RestoreTarget::Skip => ("keep", None),
+                }
+            }
+        };
+
+        match outcome {
+            "restore" => preview.restored += 1,
+            "relocate" => preview.relocated += 1,
+            "keep" => preview.kept += 1,
+            "missing" => preview.missing += 1,
+            "irreversible" => preview.irreversible += 1,
+            _ => {}
+        }
+        if content_changed {
+            preview.modified += 1;
+        }
+        if let Some(target) = &target {
+            claimed.insert(target.clone());
+        }
+
+        let mut later_runs: Vec<String> = Vec::new();
+        for step in &steps {
+            if !later_runs.contains(&step.run_id) {
+                later_runs.push(step.run_id.clone());
+            }
+        }
+        preview.items.push(RollbackPreviewItem {
+            run_item_id: item.id.clone(),
+            action_type: item.action_type.clone(),
+            original_path: item.original_path.clone(),
+            current_path: reference.destination_path.clone(),
+            destination_exists,
+            original_free,
+            content_changed,
+            later_runs,
+            outcome: outcome.to_string(),
+            target_path: target.map(|t| t.to_string_lossy().to_string()),
+        });
+    }
+
+    Ok(preview)
+}
+
+/// The items of a run a rollback picked by `scope` would act on.
+fn pending<'a>(items: &'a [RunItem], scope: RollbackScope) -> Vec<&'a RunItem> {
+    items
+        .iter()
+        .filter(|i| i.status == "completed" || i.status == "rollback_skipped")
+        .filter(|i| scope.includes(i))
+        .collect()
+}
+
+/// Whether something occupies `path`, counting dangling symlinks.
+fn path_taken(path: &Path) -> bool {
+    path.symlink_metadata().is_ok()
+}
+
+/// Rolls back the items of a run picked by `scope`, moving files back to
+/// their original locations. Items rolled back before are left alone; items a
+/// previous rollback skipped are tried again.
+///
+/// A file a later run moved again is handled with `chain`: the later moves
+/// are rolled back first, latest run first, or the file is moved straight
+/// back from where it is now and the later moves are marked as undone too.
+///
+/// The run becomes `rolled_back` once all of it has been undone, or
+/// `rollback_partial` while some items remain (see
+/// [`runs::mark_run_rolled_back`]).
+///
+/// Nothing at an original path is ever replaced: a taken path is handled
+/// with `strategy` (see [`restore_target`]). Files edited at their
+/// destination after the run are flagged. What was done is recorded on each
+/// run item and the unusual cases are returned in the result.
+///
+/// Progress is reported through `control` after each item. With `verify`,
+/// files copied back across devices are checked like during execution.
+pub fn rollback_run(
+    conn: &Connection,
+    run_id: &str,
+    scope: RollbackScope,
+    strategy: &str,
+    chain: ChainMode,
+    verify: bool,
+    control: &RunControl,
+) -> Result<RollbackResult> {
+    if runs::get_run(conn, run_id)?.is_some_and(|run| run.pruned_at.is_some()) {
+        bail!("Run {} was pruned from history and can no longer be rolled back", run_id);
+    }
+
+    let items = runs::get_run_items(conn, run_id)
+        .context("Failed to get run items for rollback")?;
+
+    let mut result = RollbackResult {
+        run_id: run_id.to_string(),
+        rolled_back: 0,
+        skipped: 0,
+        errors: 0,
+        items: Vec::new(),
+        dependent_runs: Vec::new(),
+    };
 
-        let dest = Path::new(&item.destination_path);
+    let completed = pending(&items, scope);
+
+    // Files later runs moved on: either restored straight from where they are
+    // now, or brought back here by rolling back those runs first
+    let mut chains: HashMap<&str, Vec<RunItem>> = HashMap::new();
+    let mut direct: HashMap<&str, &RunItem> = HashMap::new();
+    let mut next_moves: Vec<&RunItem> = Vec::new();
+    for item in &completed {
+        let steps = trace_chain(conn, item)?;
+        if !steps.is_empty() {
+            chains.insert(&item.id, steps);
+        }
+    }
+    for (id, steps) in &chains {
+        let item = completed.iter().find(|i| i.id == *id).expect("traced item");
+        if chain == ChainMode::Latest && restorable_directly(item, steps) {
+            direct.insert(id, &steps[steps.len() - 1]);
+        } else {
+            next_moves.push(&steps[0]);
+        }
+    }
+    roll_back_later_moves(conn, &next_moves, strategy, chain, verify, control, &mut result)?;
+    let mut progress = OrganizerProgress {
+        run_id: run_id.to_string(),
+        phase: "rollback".to_string(),
+        done: 0,
+        total: completed.len() as u32,
+        bytes_done: 0,
+        bytes_total: completed.iter().map(|i| i.file_size.max(0) as u64).sum(),
+        current_file: String::new(),
+    };
+
+    // Only rollback items that were successfully completed
+    for item in completed {
+        progress.current_file = item.destination_path.clone();
+        control.report(&progress);
+        progress.done += 1;
+        progress.bytes_done += item.file_size.max(0) as u64;
+
+        // Where the file is now, and the item that last put it there
+        let (source, reference) = match direct.get(item.id.as_str()) {
+            Some(last) => (PathBuf::from(&last.destination_path), *last),
+            None => match chains.get(item.id.as_str()) {
+                // Rolled back above, possibly to beside its original path
+                Some(steps) => {
+                    let restored_path = runs::get_run_item(conn, &steps[0].id)?
+                        .and_then(|step| step.restored_path);
+                    let path = restored_path.unwrap_or_else(|| item.destination_path.clone());
+                    (PathBuf::from(path), item)
+                }
+                None => (PathBuf::from(&item.destination_path), item),
+            },
+        };
+        let dest = source.as_path();
         let original = Path::new(&item.original_path);
 
+        match item.action_type.as_str() {
+            "extract" => {
+                // Extracted files did not exist before the run: remove them,
+                // unless they were edited since, which would lose the changes
+                let modified = dest.exists()
+                    && integrity::changed_since(
+                        dest,
+                        item.file_size.max(0) as u64,
+                        item.dest_mtime.as_deref(),
+                        item.checksum.as_deref(),
+                    )
+                    .unwrap_or(false);
+                if modified {
+                    log::warn!(
+                        "Rollback: {} was modified after extraction, keeping it",
+                        dest.display()
+                    );
+                    kept(conn, item, true, &mut result)?;
+                    continue;
+                }
+                match remove_extracted(dest, original) {
+                    Ok(()) => {
+                        runs::update_run_item_status(conn, &item.id, "rolled_back")?;
+                        result.rolled_back += 1;
+                    }
+                    Err(e) => {
+                        let msg = format!("Rollback failed to remove {}: {}", dest.display(), e);
+                        failed(conn, item, errors::classify(&e), &msg, &mut result)?;
+                    }
+                }
+                continue;
+            }
+            "archive" => {
+                // Re-extract the stored entry to its original location
+                let entry = item.archive_entry.as_deref().unwrap_or_default();
+                if entry.is_empty() || !dest.exists() {
+                    log::warn!(
+                        "Rollback: archive entry for {} is no longer available",
+                        original.display()
+                    );
+                    runs::update_run_item_status(conn, &item.id, "rollback_skipped")?;
+                    result.skipped += 1;
+                    continue;
+                }
+                // A file that appears at the target meanwhile is never
+                // replaced: the strategy picks again
+                while let Some((target, resolution)) =
+                    place(conn, item, original, strategy, false, &mut result)?
+                {
+                    match archive::restore_from_archive(dest, entry, &target) {
+                        Ok(()) => {
+                            restored(conn, item, &target, resolution, false, &mut result)?;
+                            log::info!("Restored {} from {}", target.display(), dest.display());
+                        }
+                        Err(e) if taken_meanwhile(&e) => {
+                            log::warn!("Rollback: {} was taken meanwhile", target.display());
+                            continue;
+                        }
+                        Err(e) => {
+                            let msg = format!(
+                                "Rollback failed to restore {} from {}: {}",
+                                target.display(),
+                                dest.display(),
+                                e
+                            );
+                            failed(conn, item, errors::classify(&e), &msg, &mut result)?;
+                        }
+                    }
+                    break;
+                }
+                continue;
+            }
+            "link" => {
+                // Nothing was moved: only the link has to go
+                match remove_link_for_item(conn, &item.id, dest, original) {
+                    Ok(()) => {
+                        restore_replaced(item.replaced_path.as_deref(), dest);
+                        runs::update_run_item_status(conn, &item.id, "rolled_back")?;
+                        result.rolled_back += 1;
+                    }
+                    Err(e) => {
+                        let msg =
+                            format!("Rollback failed to remove link {}: {:#}", dest.display(), e);
+                        failed(conn, item, errors::classify(&e), &msg, &mut result)?;
+                    }
+                }
+                continue;
+            }
+            "delete" => {
+                log::warn!(
+                    "Rollback: {} was deleted and cannot be restored",
+                    original.display()
+                );
+                runs::update_run_item_status(conn, &item.id, "rollback_skipped")?;
+                result.skipped += 1;
+                continue;
+            }
+            _ => {}
+        }
+
         // Check that the destination file still exists
         if !dest.exists() {
             log::warn!(
                 "Rollback: destination file no longer exists: {}",
                 dest.display()
             );
-            runs::update_run_item_status(conn, &item.id, "rollback_skipped").ok();
+            runs::update_run_item_status(conn, &item.id, "rollback_skipped")?;
+            result.skipped += 1;
             continue;
         }
 
-        // Ensure original directory exists
-        if let Some(parent) = original.parent() {
-            if let Err(e) = fs::create_dir_all(parent) {
-                log::error!(
-                    "Rollback: failed to create directory {}: {}",
-                    parent.display(),
-                    e
-                );
-                errors += 1;
+        let modified = integrity::changed_since(
+            dest,
+            reference.file_size.max(0) as u64,
+            reference.dest_mtime.as_deref(),
+            reference.checksum.as_deref(),
+        )
+        .unwrap_or(false);
+        if modified {
+            log::warn!("Rollback: {} was modified after the run", dest.display());
+        }
+
+        // The link left at the original path must be removed before the file
+        // can be moved back there
+        if item.action_type == "move_and_link" {
+            if let Err(e) = remove_link_for_item(conn, &item.id, original, dest) {
+                let msg =
+                    format!("Rollback failed to remove link {}: {:#}", original.display(), e);
+                failed(conn, item, errors::classify(&e), &msg, &mut result)?;
                 continue;
             }
         }
 
-        // Move file back
-        match move_file_back(dest, original) {
-            Ok(()) => {
-                runs::update_run_item_status(conn, &item.id, "rolled_back").ok();
-                rolled_back += 1;
-                log::info!(
-                    "Rolled back: {} -> {}",
-                    dest.display(),
-                    original.display()
-                );
-            }
-            Err(e) => {
-                log::error!(
-                    "Rollback failed for {} -> {}: {}",
-                    dest.display(),
-                    original.display(),
-                    e
-                );
-                errors += 1;
+        // Move file back. A file that appears at the target meanwhile is
+        // never replaced: the strategy picks again
+        while let Some((target, resolution)) =
+            place(conn, item, original, strategy, modified, &mut result)?
+        {
+            match move_file_back(dest, &target, verify) {
+                Ok(()) => {
+                    if direct.contains_key(item.id.as_str()) {
+                        supersede(conn, &chains[item.id.as_str()], &target, &mut result)?;
+                    } else {
+                        restore_replaced(item.replaced_path.as_deref(), dest);
+                    }
+                    restored(conn, item, &target, resolution, modified, &mut result)?;
+                    log::info!(
+                        "Rolled back: {} -> {}",
+                        dest.display(),
+                        target.display()
+                    );
+                }
+                Err(e) if taken_meanwhile(&e) => {
+                    log::warn!("Rollback: {} was taken meanwhile", target.display());
+                    continue;
+                }
+                Err(e) => {
+                    let msg = format!(
+                        "Rollback failed for {} -> {}: {}",
+                        dest.display(),
+                        target.display(),
+                        e
+                    );
+                    failed(conn, item, errors::classify(&e), &msg, &mut result)?;
+                }
             }
+            break;
         }
     }
 
-    // Update the run status
-    let total_moved = items.iter().filter(|i| i.status == "completed").count() as i32;
-    let status = if errors > 0 { "rollback_partial" } else { "rolled_back" };
-    runs::update_run_status(conn, run_id, status, total_moved - rolled_back as i32, 0, errors as i32).ok();
+    progress.current_file.clear();
+    control.report(&progress);
+
+    runs::mark_run_rolled_back(conn, run_id).context("Failed to update run status")?;
 
     log::info!(
-        "Rollback complete for run {}: {} rolled back, {} errors",
+        "Rollback complete for run {}: {} rolled back, {} skipped, {} errors",
         run_id,
-        rolled_back,
-        errors
+        result.rolled_back,
+        result.skipped,
+        result.errors
     );
 
-    Ok(rolled_back)
+    Ok(result)
+}
+
+/// Rolls back the given items of later runs, latest run first. Each of those
+/// rollbacks does the same for runs after it.
+fn roll_back_later_moves(
+    conn: &Connection,
+    steps: &[&RunItem],
+    strategy: &str,
+    chain: ChainMode,
+    verify: bool,
+    control: &RunControl,
+    result: &mut RollbackResult,
+) -> Result<()> {
+    let mut by_run: HashMap<&str, Vec<String>> = HashMap::new();
+    for step in steps {
+        let ids = by_run.entry(&step.run_id).or_default();
+        if !ids.contains(&step.id) {
+            ids.push(step.id.clone());
+        }
+    }
+
+    let mut later: Vec<(String, &str)> = Vec::new();
+    for run_id in by_run.keys() {
+        let started_at = runs::get_run(conn, run_id)?.map(|r| r.started_at).unwrap_or_default();
+        later.push((started_at, run_id));
+    }
+    later.sort_by(|a, b| b.cmp(a));
+
+    for (_, run_id) in later {
+        log::info!("Rolling back moves of later run {} first", run_id);
+        let scope = RollbackScope::Items(&by_run[run_id]);
+        let nested = rollback_run(conn, run_id, scope, strategy, chain, verify, control)?;
+        result.dependent_runs.push(run_id.to_string());
+        result.dependent_runs.extend(nested.dependent_runs);
+    }
+    Ok(())
+}
+
+/// Marks the later moves of a file restored straight from its latest location
+/// as rolled back, since the file is no longer where they left it.
+fn supersede(
+    conn: &Connection,
+    steps: &[RunItem],
+    target: &Path,
+    result: &mut RollbackResult,
+) -> Result<()> {
+    let target = target.to_string_lossy();
+    for step in steps {
+        runs::set_run_item_rollback(conn, &step.id, "chained", Some(&target), false)?;
+        runs::update_run_item_status(conn, &step.id, "rolled_back")?;
+        runs::mark_run_rolled_back(conn, &step.run_id)?;
+        if !result.dependent_runs.contains(&step.run_id) {
+            result.dependent_runs.push(step.run_id.clone());
+        }
+    }
+    Ok(())
+}
+
+/// Picks where an item goes back to, and the resolution that chose it, and
+/// prepares its folder. Returns `None` when the item is skipped or the folder
+/// can't be created; either way the item has been accounted for in `result`.
+fn place(
+    conn: &Connection,
+    item: &RunItem,
+    original: &Path,
+    strategy: &str,
+    modified: bool,
+    result: &mut RollbackResult,
+) -> Result<Option<(PathBuf, &'static str)>> {
+    let (target, resolution) = match restore_target(original, strategy, &path_taken) {
+        RestoreTarget::Original => (original.to_path_buf(), "restored"),
+        RestoreTarget::Elsewhere { resolution, path } => (path, resolution),
+        RestoreTarget::Skip => {
+            log::warn!("Rollback: {} is taken, leaving the file in place", original.display());
+            kept(conn, item, modified, result)?;
+            return Ok(None);
+        }
+    };
+
+    if let Some(parent) = target.parent() {
+        if let Err(e) = fs::create_dir_all(parent) {
+            let msg = format!("Rollback: failed to create directory {}: {}", parent.display(), e);
+            failed(conn, item, errors::classify_io(&e), &msg, result)?;
+            return Ok(None);
+        }
+    }
+    Ok(Some((target, resolution)))
+}
+
+/// Leaves an item's file where the run put it, recording the choice on the
+/// item, which a later rollback tries again.
+fn kept(
+    conn: &Connection,
+    item: &RunItem,
+    modified: bool,
+    result: &mut RollbackResult,
+) -> Result<()> {
+    runs::set_run_item_rollback(conn, &item.id, "skipped", None, modified)?;
+    runs::update_run_item_status(conn, &item.id, "rollback_skipped")?;
+    result.skipped += 1;
+    result.items.push(RollbackItemReport {
+        run_item_id: item.id.clone(),
+        original_path: item.original_path.clone(),
+        resolution: "skipped".to_string(),
+        restored_path: None,
+        destination_modified: modified,
+    });
+    Ok(())
+}
+
+/// Counts a failed rollback and records why on the item, which stays as it was
+/// so the rollback can be retried.
+fn failed(
+    conn: &Connection,
+    item: &RunItem,
+    error_code: &str,
+    error_message: &str,
+    result: &mut RollbackResult,
+) -> Result<()> {
+    log::error!("{}", error_message);
+    runs::set_run_item_error(conn, &item.id, error_code, error_message)?;
+    result.errors += 1;
+    Ok(())
+}
+
+/// Marks an item as rolled back to `target` and records how it got there.
+fn restored(
+    conn: &Connection,
+    item: &RunItem,
+    target: &Path,
+    resolution: &str,
+    modified: bool,
+    result: &mut RollbackResult,
+) -> Result<()> {
+    let elsewhere = target != Path::new(&item.original_path);
+    let restored_path = elsewhere.then(|| target.to_string_lossy().to_string());
+
+    runs::set_run_item_rollback(conn, &item.id, resolution, restored_path.as_deref(), modified)?;
+    runs::update_run_item_status(conn, &item.id, "rolled_back")?;
+    result.rolled_back += 1;
+
+    if elsewhere || modified {
+        result.items.push(RollbackItemReport {
+            run_item_id: item.id.clone(),
+            original_path: item.original_path.clone(),
+            resolution: resolution.to_string(),
+            restored_path,
+            destination_modified: modified,
+        });
+    }
+    Ok(())
+}
+
+/// Removes a file created by an `extract` action, then removes any folders
+/// left empty by it, up to (not including) the archive's own folder.
+fn remove_extracted(extracted: &Path, archive: &Path) -> Result<()> {
+    if extracted.exists() {
+        fs::remove_file(extracted)
+            .with_context(|| format!("Failed to remove {}", extracted.display()))?;
+    }
+
+    if let Some(archive_dir) = archive.parent() {
+        let mut dir = extracted.parent();
+        while let Some(d) = dir {
+            if d == archive_dir || !d.starts_with(archive_dir) || fs::remove_dir(d).is_err() {
+                break;
+            }
+            dir = d.parent();
+        }
+    }
+
+    Ok(())
+}
+
+/// Removes the link created by a run item and its `shortcuts` record.
+/// `default_link` is used when no record exists; the on-disk link may differ
+/// from it when a `.url`/`.desktop` file was written instead of a symlink.
+fn remove_link_for_item(
+    conn: &Connection,
+    run_item_id: &str,
+    default_link: &Path,
+    target: &Path,
+) -> Result<()> {
+    let shortcut = shortcuts::get_link_for_run_item(conn, run_item_id)?;
+    let link_path = shortcut
+        .as_ref()
+        .map(|s| PathBuf::from(&s.link_path))
+        .unwrap_or_else(|| default_link.to_path_buf());
+
+    link::remove_link(&link_path, target)?;
+
+    if let Some(shortcut) = shortcut {
+        shortcuts::delete_shortcut(conn, &shortcut.id)?;
+    }
+    Ok(())
 }
 
-/// Moves a file back to its original location.
-fn move_file_back(source: &Path, dest: &Path) -> Result<()> {
-    match fs::rename(source, dest) {
-        Ok(()) => Ok(()),
+/// Puts back the file an `overwrite_*` strategy moved aside, once the path it
+/// came from is free again.
+fn restore_replaced(replaced_path: Option<&str>, dest: &Path) {
+    let backup = match replaced_path {
+        Some(p) => Path::new(p),
+        None => return,
+    };
+    if !backup.exists() || dest.exists() {
+        log::warn!(
+            "Rollback: cannot restore replaced file {} to {}",
+            backup.display(),
+            dest.display()
+        );
+        return;
+    }
+    match move_file_back(backup, dest, false) {
+        Ok(()) => log::info!("Restored replaced file {}", dest.display()),
+        Err(e) => log::error!(
+            "Rollback failed to restore replaced file {} -> {}: {}",
+            backup.display(),
+            dest.display(),
+            e
+        ),
+    }
+}
+
+/// Moves a file back to its original location, never replacing a file that
+/// is already there: that fails with `AlreadyExists` (see [`taken_meanwhile`])
+/// and both files are left as they were.
+///
+/// The file is hard-linked at `dest` and then unlinked from `source`. Only
+/// when that can't be done, across devices or on filesystems without hard
+/// links, is it copied into a newly created `dest` instead; with `verify`,
+/// that copy is checked before the moved file is deleted.
+fn move_file_back(source: &Path, dest: &Path, verify: bool) -> Result<()> {
+    match fs::hard_link(source, dest) {
+        Ok(()) => {
+            if let Err(e) = fs::remove_file(source) {
+                fs::remove_file(dest).ok();
+                return Err(anyhow::anyhow!(e)
+                    .context(format!("Rollback delete failed: {}", source.display())));
+            }
+            Ok(())
+        }
+        Err(e) if !links_unavailable(&e) => Err(anyhow::anyhow!(e).context(format!(
+            "Rollback move failed: {} -> {}",
+            source.display(),
+            dest.display()
+        ))),
         Err(_) => {
-            // Cross-device: copy then delete
-            fs::copy(source, dest)
-                .with_context(|| {
-                    format!(
-                        "Rollback copy failed: {} -> {}",
-                        source.display(),
-                        dest.display()
-                    )
-                })?;
-            fs::remove_file(source)
-                .with_context(|| {
-                    format!("Rollback delete failed: {}", source.display())
-                })?;
+            // Cross-device or no hard links: copy then delete
+            copy_new(source, dest).with_context(|| {
+                format!(
+                    "Rollback copy failed: {} -> {}",
+                    source.display(),
+                    dest.display()
+                )
+            })?;
+            if verify {
+                if let Err(e) = integrity::verify_copy(source, dest) {
+                    fs::remove_file(dest).ok();
+                    return Err(e.context("Rollback copy verification failed, file kept in place"));
+                }
+            }
+            if let Err(e) = fs::remove_file(source) {
+                fs::remove_file(dest).ok();
+                return Err(anyhow::anyhow!(e)
+                    .context(format!("Rollback delete failed: {}", source.display())));
+            }
             Ok(())
         }
     }
 }
+
+/// Whether a hard link failed because the files are on different devices or
+/// the filesystem has no hard links (`EPERM` on Linux, e.g. FAT drives).
+fn links_unavailable(e: &std::io::Error) -> bool {
+    errors::classify_io(e) == errors::CROSS_DEVICE
+        || matches!(
+            e.kind(),
+            std::io::ErrorKind::Unsupported | std::io::ErrorKind::PermissionDenied
+        )
+}
+
+/// Copies `source` into a file created at `dest`, failing if one exists.
+/// A partial copy is removed.
+fn copy_new(source: &Path, dest: &Path) -> std::io::Result<()> {
+    let mut input = fs::File::open(source)?;
+    let mut output = fs::OpenOptions::new().write(true).create_new(true).open(dest)?;
+    let copied = std::io::copy(&mut input, &mut output)
+        .and_then(|_| output.set_permissions(input.metadata()?.permissions()))
+        .and_then(|()| output.sync_all());
+    if copied.is_err() {
+        drop(output);
+        fs::remove_file(dest).ok();
+    }
+    copied
+}
+
+/// Whether restoring failed because a file appeared at the target after it
+/// was picked.
+fn taken_meanwhile(e: &anyhow::Error) -> bool {
+    e.chain()
+        .filter_map(|cause| cause.downcast_ref::<std::io::Error>())
+        .any(|e| e.kind() == std::io::ErrorKind::AlreadyExists)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support;
+
+    #[test]
+    fn test_restore_target_never_replaces() {
+        let taken =
+            |p: &Path| p == Path::new("/home/a.txt") || p == Path::new("/home/Restored/a.txt");
+        assert_eq!(
+            restore_target(Path::new("/home/b.txt"), "skip", &taken),
+            RestoreTarget::Original
+        );
+        assert_eq!(
+            restore_target(Path::new("/home/a.txt"), "skip", &taken),
+            RestoreTarget::Skip
+        );
+        assert_eq!(
+            restore_target(Path::new("/home/a.txt"), "suffix", &taken),
+            RestoreTarget::Elsewhere {
+                resolution: "suffix",
+                path: PathBuf::from("/home/a_1.txt"),
+            }
+        );
+        assert_eq!(
+            restore_target(Path::new("/home/a.txt"), "restored_folder", &taken),
+            RestoreTarget::Elsewhere {
+                resolution: "restored_folder",
+                path: PathBuf::from("/home/Restored/a_1.txt"),
+            }
+        );
+    }
+
+    #[test]
+    fn test_move_file_back_never_replaces() {
+        let dir = test_support::temp_dir("move-back");
+        let (source, dest) = (dir.join("moved.txt"), dir.join("original.txt"));
+        fs::write(&source, b"moved").unwrap();
+        fs::write(&dest, b"created meanwhile").unwrap();
+
+        let err = move_file_back(&source, &dest, false).unwrap_err();
+        assert!(taken_meanwhile(&err));
+        assert_eq!(fs::read(&source).unwrap(), b"moved");
+        assert_eq!(fs::read(&dest).unwrap(), b"created meanwhile");
+
+        // The copy used across devices refuses to replace too
+        let err = copy_new(&source, &dest).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read(&dest).unwrap(), b"created meanwhile");
+
+        fs::remove_file(&dest).unwrap();
+        move_file_back(&source, &dest, true).unwrap();
+        assert!(!source.exists());
+        assert_eq!(fs::read(&dest).unwrap(), b"moved");
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_rollback_keeps_new_file_at_original_path() {
+        let dir = test_support::temp_dir("rollback");
+        fs::create_dir_all(dir.join("out")).unwrap();
+        let original = dir.join("notes.txt");
+        let dest = dir.join("out").join("notes.txt");
+
+        let conn = test_support::conn();
+        let run = test_support::run(&conn, &dir.to_string_lossy());
+
+        // The run moved notes.txt away...
+        fs::write(&dest, b"moved").unwrap();
+        let item = runs::create_run_item(
+            &conn,
+            &run.id,
+            None,
+            &original.to_string_lossy(),
+            &dest.to_string_lossy(),
+            5,
+            "move_to_folder",
+            "completed",
+            "suffix",
+        )
+        .unwrap();
+        let stamp = integrity::mtime_stamp(&dest).unwrap();
+        runs::set_run_item_dest_mtime(&conn, &item.id, &stamp).unwrap();
+
+        // ...then the user created a new notes.txt and edited the moved one
+        fs::write(&original, b"new file").unwrap();
+        fs::write(&dest, b"moved, edited").unwrap();
+
+        let (all, cascade) = (RollbackScope::All, ChainMode::Cascade);
+        let result =
+            rollback_run(&conn, &run.id, all, "suffix", cascade, false, &RunControl::none())
+                .unwrap();
+        assert_eq!(result.rolled_back, 1);
+        assert_eq!(fs::read(&original).unwrap(), b"new file");
+        assert_eq!(fs::read(dir.join("notes_1.txt")).unwrap(), b"moved, edited");
+
+        let report = &result.items[0];
+        assert_eq!(report.resolution, "suffix");
+        assert!(report.destination_modified);
+
+        let stored = &runs::get_run_items(&conn, &run.id).unwrap()[0];
+        assert_eq!(stored.rollback_resolution.as_deref(), Some("suffix"));
+        assert!(stored.dest_modified);
+        assert_eq!(
+            stored.restored_path.as_deref(),
+            Some(dir.join("notes_1.txt").to_string_lossy().as_ref())
+        );
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_rollback_keeps_extracted_files_edited_since() {
+        let dir = test_support::temp_dir("rollback-extract");
+        fs::create_dir_all(dir.join("out")).unwrap();
+        let archive = dir.join("photos.zip");
+
+        let conn = test_support::conn();
+        let run = test_support::run(&conn, &dir.to_string_lossy());
+
+        let mut items = Vec::new();
+        for name in ["a.txt", "b.txt"] {
+            let extracted = dir.join("out").join(name);
+            fs::write(&extracted, b"12345").unwrap();
+            let item = runs::create_run_item(
+                &conn,
+                &run.id,
+                None,
+                &archive.to_string_lossy(),
+                &extracted.to_string_lossy(),
+                5,
+                "extract",
+                "completed",
+                "suffix",
+            )
+            .unwrap();
+            let stamp = integrity::mtime_stamp(&extracted).unwrap();
+            runs::set_run_item_dest_mtime(&conn, &item.id, &stamp).unwrap();
+            items.push(item);
+        }
+
+        // The user edited one of the extracted files
+        fs::write(dir.join("out").join("b.txt"), b"edited").unwrap();
+
+        let preview = simulate_rollback(&conn, &run.id, RollbackScope::All, "suffix").unwrap();
+        let outcome = |id: &str| {
+            preview.items.iter().find(|i| i.run_item_id == id).unwrap().outcome.clone()
+        };
+        assert_eq!(outcome(&items[0].id), "remove");
+        assert_eq!(outcome(&items[1].id), "keep");
+
+        let (all, cascade) = (RollbackScope::All, ChainMode::Cascade);
+        let result =
+            rollback_run(&conn, &run.id, all, "suffix", cascade, false, &RunControl::none())
+                .unwrap();
+        assert_eq!(result.rolled_back, 1);
+        assert_eq!(result.skipped, 1);
+        assert!(!dir.join("out").join("a.txt").exists());
+        assert_eq!(fs::read(dir.join("out").join("b.txt")).unwrap(), b"edited");
+
+        let stored = runs::get_run_items(&conn, &run.id).unwrap();
+        let kept = stored.iter().find(|i| i.id == items[1].id).unwrap();
+        assert_eq!(kept.status, "rollback_skipped");
+        assert_eq!(kept.rollback_resolution.as_deref(), Some("skipped"));
+        assert!(kept.dest_modified);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_partial_rollback_by_item_then_rule() {
+        let dir = test_support::temp_dir("partial");
+        fs::create_dir_all(dir.join("out")).unwrap();
+
+        let conn = test_support::conn();
+        test_support::rule(&conn, "r1", "Docs");
+        let run = test_support::run(&conn, &dir.to_string_lossy());
+
+        let mut items = Vec::new();
+        for name in ["a.txt", "b.txt"] {
+            let dest = dir.join("out").join(name);
+            fs::write(&dest, name).unwrap();
+            items.push(
+                runs::create_run_item(
+                    &conn,
+                    &run.id,
+                    Some("r1"),
+                    &dir.join(name).to_string_lossy(),
+                    &dest.to_string_lossy(),
+                    5,
+                    "move_to_folder",
+                    "completed",
+                    "suffix",
+                )
+                .unwrap(),
+            );
+        }
+        runs::update_run_status(&conn, &run.id, "completed", 2, 0, 0).unwrap();
+
+        let ids = [items[0].id.clone()];
+        let (none, cascade) = (RunControl::none(), ChainMode::Cascade);
+        let scope = RollbackScope::Items(&ids);
+        let result =
+            rollback_run(&conn, &run.id, scope, "suffix", cascade, false, &none).unwrap();
+        assert_eq!(result.rolled_back, 1);
+        assert!(dir.join("a.txt").exists());
+        assert!(dir.join("out").join("b.txt").exists());
+
+        let partial = runs::get_run(&conn, &run.id).unwrap().unwrap();
+        assert_eq!(partial.status, "rollback_partial");
+        assert!(partial.rolled_back_at.is_some());
+        assert_eq!(partial.moved_files, 2);
+
+        let scope = RollbackScope::Rule("r1");
+        let result =
+            rollback_run(&conn, &run.id, scope, "suffix", cascade, false, &none).unwrap();
+        assert_eq!(result.rolled_back, 1);
+        assert!(dir.join("b.txt").exists());
+        assert_eq!(runs::get_run(&conn, &run.id).unwrap().unwrap().status, "rolled_back");
+        assert!(runs::get_run_items(&conn, &run.id)
+            .unwrap()
+            .iter()
+            .all(|i| i.rolled_back_at.is_some()));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_rollback_follows_files_moved_by_later_runs() {
+        let dir = test_support::temp_dir("chain");
+        fs::create_dir_all(dir.join("out")).unwrap();
+        let at =
+            |folder: &str, name: &str| dir.join(folder).join(name).to_string_lossy().to_string();
+
+        let conn = test_support::conn();
+
+        // Run 1 moved in/ -> mid/, then run 2 moved mid/ -> out/
+        let first = test_support::run(&conn, &at("in", ""));
+        let second = test_support::run(&conn, &at("mid", ""));
+        let mut items = Vec::new();
+        for name in ["a.txt", "b.txt"] {
+            fs::write(at("out", name), name).unwrap();
+            for (run, from, to) in [(&first, "in", "mid"), (&second, "mid", "out")] {
+                let item = runs::create_run_item(
+                    &conn,
+                    &run.id,
+                    None,
+                    &at(from, name),
+                    &at(to, name),
+                    5,
+                    "move_to_folder",
+                    "completed",
+                    "suffix",
+                )
+                .unwrap();
+                items.push(item);
+            }
+        }
+
+        let deps = rollback_dependencies(&conn, &first.id, RollbackScope::All).unwrap();
+        assert_eq!(deps.chains.len(), 2);
+        assert!(deps.chains.iter().all(|c| c.direct && c.steps.len() == 1));
+        assert_eq!(deps.chains[0].latest_path.as_deref(), Some(at("out", "a.txt").as_str()));
+        assert_eq!(deps.dependent_runs.len(), 1);
+        assert_eq!(deps.dependent_runs[0].run_id, second.id);
+        assert_eq!(deps.dependent_runs[0].items, 2);
+
+        // Cascade: run 2's move is rolled back first
+        let none = RunControl::none();
+        let ids = [items[0].id.clone()];
+        let scope = RollbackScope::Items(&ids);
+        let result =
+            rollback_run(&conn, &first.id, scope, "suffix", ChainMode::Cascade, false, &none)
+                .unwrap();
+        assert_eq!(result.rolled_back, 1);
+        assert_eq!(result.dependent_runs, vec![second.id.clone()]);
+        assert!(Path::new(&at("in", "a.txt")).exists());
+        assert!(!Path::new(&at("mid", "a.txt")).exists());
+
+        // Latest: straight back from out/, run 2's move marked as undone
+        let ids = [items[2].id.clone()];
+        let scope = RollbackScope::Items(&ids);
+        let result =
+            rollback_run(&conn, &first.id, scope, "suffix", ChainMode::Latest, false, &none)
+                .unwrap();
+        assert_eq!(result.rolled_back, 1);
+        assert!(Path::new(&at("in", "b.txt")).exists());
+        assert!(!Path::new(&at("mid", "b.txt")).exists());
+        let later = runs::get_run_item(&conn, &items[3].id).unwrap().unwrap();
+        assert_eq!(
+            (later.status.as_str(), later.rollback_resolution.as_deref()),
+            ("rolled_back", Some("chained"))
+        );
+
+        for run in [&first, &second] {
+            assert_eq!(runs::get_run(&conn, &run.id).unwrap().unwrap().status, "rolled_back");
+        }
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_simulate_rollback_moves_nothing() {
+        let dir = test_support::temp_dir("undo-sim");
+        fs::create_dir_all(dir.join("out")).unwrap();
+
+        let conn = test_support::conn();
+        let run = test_support::run(&conn, &dir.to_string_lossy());
+
+        // a.txt can go back, b.txt's slot is taken again, c.txt is gone
+        fs::write(dir.join("out").join("a.txt"), b"a").unwrap();
+        fs::write(dir.join("out").join("b.txt"), b"b").unwrap();
+        fs::write(dir.join("b.txt"), b"new").unwrap();
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            runs::create_run_item(
+                &conn,
+                &run.id,
+                None,
+                &dir.join(name).to_string_lossy(),
+                &dir.join("out").join(name).to_string_lossy(),
+                1,
+                "move_to_folder",
+                "completed",
+                "suffix",
+            )
+            .unwrap();
+        }
+
+        let preview = simulate_rollback(&conn, &run.id, RollbackScope::All, "suffix").unwrap();
+        let outcomes: Vec<&str> = preview.items.iter().map(|i| i.outcome.as_str()).collect();
+        assert_eq!(outcomes, ["restore", "relocate", "missing"]);
+        assert_eq!((preview.restored, preview.relocated, preview.missing), (1, 1, 1));
+        assert!(!preview.items[1].original_free);
+        assert_eq!(
+            preview.items[1].target_path.as_deref(),
+            Some(dir.join("b_1.txt").to_string_lossy().as_ref())
+        );
+
+        assert!(!dir.join("a.txt").exists());
+        assert!(dir.join("out").join("a.txt").exists());
+        let status = runs::get_run(&conn, &run.id).unwrap().unwrap().status;
+        assert_eq!(status, "running");
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -68,9 +68,22 @@ fn collect_files(dir: &Path, recursive: bool, entries: &mut Vec<FileEntry>) -> R
 
 /// Builds a `FileEntry` from a directory entry.
 fn build_file_entry(entry: &fs::DirEntry) -> Result<FileEntry> {
-    let path = entry.path();
     let metadata = entry.metadata().context("Failed to read file metadata")?;
+    Ok(file_entry_from_metadata(&entry.path(), &metadata))
+}
 
+/// Builds a `FileEntry` for a single file path, reading its metadata from disk.
+pub fn file_entry_for_path(path: &Path) -> Result<FileEntry> {
+    let metadata = fs::metadata(path)
+        .with_context(|| format!("Failed to read file metadata: {}", path.display()))?;
+    if !metadata.is_file() {
+        return Err(anyhow::anyhow!("Path is not a file: {}", path.display()));
+    }
+    Ok(file_entry_from_metadata(path, &metadata))
+}
+
+/// Converts a path and its metadata into a `FileEntry`.
+fn file_entry_from_metadata(path: &Path, metadata: &fs::Metadata) -> FileEntry {
     let name = path
         .file_name()
         .unwrap_or_default()
@@ -103,12 +116,12 @@ fn build_file_entry(entry: &fs::DirEntry) -> Result<FileEntry> {
         })
         .unwrap_or_default();
 
-    Ok(FileEntry {
+    FileEntry {
         path: path.to_string_lossy().to_string(),
         name,
         extension,
         size,
         created_at,
         modified_at,
-    })
+    }
 }
@@ -1,17 +1,29 @@
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
-use crate::db::models::{FileEntry, RuleAction, RuleCondition, SimulationItem, SimulationResult};
+use crate::db::models::{
+    FileEntry, ItemSelection, RuleAction, RuleCondition, SimulationItem, SimulationResult,
+};
+use crate::organizer::conflict::{self, Resolution};
+use crate::organizer::parallel;
+use crate::rules::actions::{resolve_action_with_captures, resolve_folder};
+use crate::rules::conditions::collect_captures;
 use crate::rules::engine::evaluate_rules;
 
 /// Simulates file organization without moving anything.
 /// Takes a list of files and rules with their conditions/actions,
 /// evaluates which rules match each file, and builds a simulation result.
+///
+/// Conflicts are resolved virtually, in execution order, with each action's
+/// strategy (or `conflict_strategy` when it has none): files earlier in the
+/// batch count as present at their destination, so two files heading to the
+/// same name are flagged and each item reports the path it will really get.
 pub fn simulate(
     files: &[FileEntry],
     rules: &[crate::db::models::Rule],
     conditions: &HashMap<String, Vec<RuleCondition>>,
     actions: &HashMap<String, Vec<RuleAction>>,
+    conflict_strategy: &str,
 ) -> SimulationResult {
     let mut items = Vec::new();
     let mut matched: u32 = 0;
@@ -26,15 +38,33 @@ pub fn simulate(
                     .map(|r| r.name.clone())
                     .unwrap_or_default();
 
+                let captures = conditions
+                    .get(&rule_id)
+                    .map(|conds| collect_captures(file, conds))
+                    .unwrap_or_default();
+
                 for action in &matched_actions {
-                    let destination = crate::rules::actions::resolve_action(file, action)
-                        .map(|p| p.to_string_lossy().to_string())
-                        .unwrap_or_default();
+                    let (destination, mut error) = match resolve_action_with_captures(file, action, &captures) {
+                        Ok(path) => (
+                            path.map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
+                            None,
+                        ),
+                        Err(e) => {
+                            log::warn!("Could not resolve action for {}: {}", file.path, e);
+                            (String::new(), Some(e.to_string()))
+                        }
+                    };
 
-                    let conflict = if !destination.is_empty() {
-                        Path::new(&destination).exists()
+                    let post_destination = if action.post_action == "move" && error.is_none() {
+                        match resolve_folder(file, &action.post_destination, &captures) {
+                            Ok(p) => p.to_string_lossy().to_string(),
+                            Err(e) => {
+                                error = Some(e.to_string());
+                                String::new()
+                            }
+                        }
                     } else {
-                        false
+                        String::new()
                     };
 
                     items.push(SimulationItem {
@@ -43,7 +73,15 @@ pub fn simulate(
                         rule_name: rule_name.clone(),
                         action_type: action.action_type.clone(),
                         destination,
-                        conflict,
+                        // Set by `plan_item` below
+                        conflict: false,
+                        error,
+                        post_action: action.post_action.clone(),
+                        post_destination,
+                        conflict_strategy: action.conflict_strategy.clone(),
+                        final_path: String::new(),
+                        skip_reason: None,
+                        journal_id: None,
                     });
                 }
                 matched += 1;
@@ -54,10 +92,248 @@ pub fn simulate(
         }
     }
 
+    let mut planned = PlannedFs::default();
+    for item in &mut items {
+        plan_item(item, conflict_strategy, &mut planned);
+    }
+
     SimulationResult {
         total_files: files.len() as u32,
         matched_files: matched,
         unmatched_files: unmatched,
         items,
+        simulation_id: None,
+    }
+}
+
+/// Keeps the items of a simulation picked by `selection`: those included by
+/// index or path (all of them when nothing is included explicitly), minus the
+/// excluded ones. Excluding a path drops every action planned for that file.
+pub fn select_items(result: &SimulationResult, selection: &ItemSelection) -> SimulationResult {
+    let include_all =
+        selection.include_indices.is_empty() && selection.include_paths.is_empty();
+    let include_indices: HashSet<usize> = selection.include_indices.iter().copied().collect();
+    let include_paths: HashSet<&str> = selection.include_paths.iter().map(String::as_str).collect();
+    let exclude_indices: HashSet<usize> = selection.exclude_indices.iter().copied().collect();
+    let exclude_paths: HashSet<&str> = selection.exclude_paths.iter().map(String::as_str).collect();
+
+    let items: Vec<SimulationItem> = result
+        .items
+        .iter()
+        .enumerate()
+        .filter(|(index, item)| {
+            let path = item.file.path.as_str();
+            let included = include_all
+                || include_indices.contains(index)
+                || include_paths.contains(path);
+            included && !exclude_indices.contains(index) && !exclude_paths.contains(path)
+        })
+        .map(|(_, item)| item.clone())
+        .collect();
+
+    let matched: HashSet<&str> = items.iter().map(|i| i.file.path.as_str()).collect();
+    SimulationResult {
+        total_files: result.total_files,
+        matched_files: matched.len() as u32,
+        unmatched_files: result.unmatched_files,
+        simulation_id: result.simulation_id.clone(),
+        items,
+    }
+}
+
+/// The filesystem as it will look while the plan runs, as far as the items
+/// planned so far change it.
+#[derive(Default)]
+struct PlannedFs {
+    /// Path -> the real file whose content will be there, or `None` once an
+    /// earlier item moved it away.
+    changes: HashMap<PathBuf, Option<PathBuf>>,
+}
+
+impl PlannedFs {
+    fn occupant(&self, path: &Path) -> Option<PathBuf> {
+        match self.changes.get(path) {
+            Some(change) => change.clone(),
+            None => path.exists().then(|| path.to_path_buf()),
+        }
+    }
+
+    fn place(&mut self, path: PathBuf, content: PathBuf) {
+        self.changes.insert(path, Some(content));
+    }
+
+    fn free(&mut self, path: PathBuf) {
+        self.changes.insert(path, None);
+    }
+}
+
+/// Resolves an item the way the executor will and applies it to the planned
+/// filesystem, filling in `conflict`, `final_path` and `skip_reason`.
+fn plan_item(item: &mut SimulationItem, default_strategy: &str, planned: &mut PlannedFs) {
+    if item.error.is_some() || item.destination.is_empty() {
+        return;
+    }
+
+    let source = PathBuf::from(&item.file.path);
+    if planned.changes.get(&source) == Some(&None) {
+        item.skip_reason = Some("File is moved by an earlier action".to_string());
+        return;
+    }
+
+    // Extracting into an existing folder or appending to an existing archive
+    // is expected, not a conflict
+    if item.action_type == "extract" || item.action_type == "archive" {
+        item.final_path = item.destination.clone();
+        return;
+    }
+
+    let strategy = if item.conflict_strategy.is_empty() {
+        default_strategy
+    } else {
+        item.conflict_strategy.as_str()
+    };
+    let target = match parallel::planned_target(item) {
+        Some(target) => target,
+        None => return,
+    };
+
+    let mut final_path = target.clone();
+    if let Some(existing) = planned.occupant(&target) {
+        item.conflict = true;
+        match conflict::resolve_with(&source, &target, strategy, &|p: &Path| planned.occupant(p)) {
+            Ok(Resolution::Use(path)) => final_path = path,
+            Ok(Resolution::Replace { backup }) => planned.place(backup, existing),
+            Ok(Resolution::Skip(reason)) => {
+                item.skip_reason = Some(
+                    reason.unwrap_or_else(|| "A file already exists at the destination".to_string()),
+                );
+                return;
+            }
+            Err(e) => {
+                item.skip_reason =
+                    Some(format!("Could not compare with the existing file: {:#}", e));
+                return;
+            }
+        }
+    }
+
+    // A link leaves the original in place; a move_and_link leaves a link there
+    if item.action_type != "link" && item.action_type != "move_and_link" {
+        planned.free(source.clone());
+    }
+    item.final_path = final_path.to_string_lossy().to_string();
+    planned.place(final_path, source);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::Rule;
+    use crate::db::test_support;
+    use std::fs;
+
+    #[test]
+    fn test_simulation_predicts_batch_collisions() {
+        let dir = test_support::temp_dir("simulate");
+        fs::create_dir_all(dir.join("a")).unwrap();
+        fs::create_dir_all(dir.join("b")).unwrap();
+        fs::create_dir_all(dir.join("Docs")).unwrap();
+        fs::write(dir.join("a").join("report.pdf"), b"first").unwrap();
+        fs::write(dir.join("b").join("report.pdf"), b"second").unwrap();
+        fs::write(dir.join("Docs").join("report.pdf"), b"already there").unwrap();
+
+        let scanned = |folder: &str| {
+            let path = dir.join(folder).join("report.pdf");
+            crate::organizer::scanner::file_entry_for_path(&path).unwrap()
+        };
+        let files = vec![scanned("a"), scanned("b")];
+
+        let rule = Rule {
+            id: "r1".to_string(),
+            name: "Docs".to_string(),
+            description: String::new(),
+            is_enabled: true,
+            priority: 0,
+            sort_order: 0,
+            created_at: String::new(),
+            updated_at: String::new(),
+        };
+        let action = RuleAction {
+            id: "a1".to_string(),
+            rule_id: "r1".to_string(),
+            action_type: "move_to_folder".to_string(),
+            destination: dir.join("Docs").to_string_lossy().to_string(),
+            rename_pattern: String::new(),
+            tag_name: String::new(),
+            sort_order: 0,
+            created_at: String::new(),
+            post_action: "keep".to_string(),
+            post_destination: String::new(),
+            conflict_strategy: String::new(),
+        };
+        let condition = RuleCondition {
+            id: "c1".to_string(),
+            rule_id: "r1".to_string(),
+            field: "extension".to_string(),
+            operator: "equals".to_string(),
+            value: "pdf".to_string(),
+            logic_gate: "AND".to_string(),
+            sort_order: 0,
+            created_at: String::new(),
+        };
+        let conditions = HashMap::from([("r1".to_string(), vec![condition])]);
+        let actions = HashMap::from([("r1".to_string(), vec![action])]);
+
+        let result = simulate(&files, &[rule], &conditions, &actions, "suffix");
+        let final_paths: Vec<_> = result.items.iter().map(|i| i.final_path.clone()).collect();
+        assert_eq!(
+            final_paths,
+            vec![
+                dir.join("Docs").join("report_1.pdf").to_string_lossy().to_string(),
+                dir.join("Docs").join("report_2.pdf").to_string_lossy().to_string(),
+            ]
+        );
+        assert!(result.items.iter().all(|i| i.conflict));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_select_items() {
+        let item = |path: &str, action_type: &str| SimulationItem {
+            action_type: action_type.to_string(),
+            ..test_support::item(test_support::file_entry(path, 1), "/out/")
+        };
+        let result = SimulationResult {
+            total_files: 3,
+            matched_files: 3,
+            ..test_support::simulation(vec![
+                item("/a", "move_to_folder"),
+                item("/b", "copy"),
+                item("/b", "move_to_folder"),
+                item("/c", "move_to_folder"),
+            ])
+        };
+        let paths = |r: &SimulationResult| {
+            r.items.iter().map(|i| i.file.path.clone()).collect::<Vec<_>>()
+        };
+
+        let everything_but_b = ItemSelection {
+            exclude_paths: vec!["/b".to_string()],
+            ..Default::default()
+        };
+        let selected = select_items(&result, &everything_but_b);
+        assert_eq!(paths(&selected), vec!["/a", "/c"]);
+        assert_eq!(selected.matched_files, 2);
+
+        let picked = ItemSelection {
+            include_indices: vec![0, 1],
+            include_paths: vec!["/c".to_string()],
+            exclude_indices: vec![0],
+            ..Default::default()
+        };
+        let selected = select_items(&result, &picked);
+        assert_eq!(paths(&selected), vec!["/b", "/c"]);
+        assert_eq!(selected.items[0].action_type, "copy");
     }
 }
@@ -1,6 +1,10 @@
-use std::path::PathBuf;
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 use crate::db::models::{FileEntry, RuleAction};
+use crate::rules::formats::archive_kind;
+use crate::rules::sanitize;
 
 /// Resolves an action to compute the final destination path for a file.
 ///
@@ -12,72 +16,162 @@ use crate::db::models::{FileEntry, RuleAction};
 /// - `{original}` — the original filename without extension
 /// - `{counter}` — a simple counter placeholder (resolved as 1 by default)
 ///
-/// Returns `None` if the action type does not produce a path (e.g., tag-only actions).
-pub fn resolve_action(file: &FileEntry, action: &RuleAction) -> Option<PathBuf> {
+/// Every expanded segment is sanitized (see [`crate::rules::sanitize`]).
+/// Relative actions (`move_to_subfolder`, `rename`) must stay inside the
+/// file's current folder; anything that would escape it is an error.
+///
+/// Returns `Ok(None)` if the action type does not produce a path (e.g., tag-only actions).
+pub fn resolve_action(file: &FileEntry, action: &RuleAction) -> Result<Option<PathBuf>> {
+    resolve_action_with_captures(file, action, &HashMap::new())
+}
+
+/// Same as [`resolve_action`], but also expands regex capture groups collected
+/// from the rule's `matches` conditions: `{1}`, `{2}`, ... for numbered groups
+/// and `{name}` for named groups. Built-in placeholders take precedence over a
+/// capture group with the same name.
+pub fn resolve_action_with_captures(
+    file: &FileEntry,
+    action: &RuleAction,
+    captures: &HashMap<String, String>,
+) -> Result<Option<PathBuf>> {
     match action.action_type.as_str() {
-        "move" | "copy" | "move_to_folder" => {
+        "move" | "copy" | "move_to_folder" | "move_and_link" | "link" => {
+            // For `link` the destination is where the link is created
             if action.destination.is_empty() {
-                return None;
+                return Ok(None);
             }
 
-            let dest_dir = expand_template(&action.destination, file);
+            let dest_dir = sanitize::sanitize_path(&expand_template(
+                &action.destination,
+                file,
+                captures,
+            ))?;
 
-            if action.rename_pattern.is_empty() {
-                Some(PathBuf::from(&dest_dir).join(&file.name))
-            } else {
-                let new_name = expand_template(&action.rename_pattern, file);
-                Some(PathBuf::from(&dest_dir).join(&new_name))
-            }
+            let name = resolve_file_name(file, action, captures)?;
+            Ok(Some(dest_dir.join(name)))
         }
         "move_to_subfolder" => {
             if action.destination.is_empty() {
-                return None;
+                return Ok(None);
             }
 
             // Create a subfolder relative to the file's current parent directory
-            let parent = std::path::Path::new(&file.path)
+            let parent = Path::new(&file.path)
                 .parent()
-                .unwrap_or_else(|| std::path::Path::new("."));
+                .unwrap_or_else(|| Path::new("."));
 
-            let subfolder_name = expand_template(&action.destination, file);
-            let dest_dir = parent.join(&subfolder_name);
+            let subfolder = sanitize::sanitize_relative(&expand_template(
+                &action.destination,
+                file,
+                captures,
+            ))?;
 
-            if action.rename_pattern.is_empty() {
-                Some(dest_dir.join(&file.name))
-            } else {
-                let new_name = expand_template(&action.rename_pattern, file);
-                Some(dest_dir.join(&new_name))
-            }
+            let name = resolve_file_name(file, action, captures)?;
+            let dest = parent.join(subfolder).join(name);
+            sanitize::ensure_within(parent, &dest)?;
+            Ok(Some(dest))
         }
         "rename" => {
             if action.rename_pattern.is_empty() {
-                return None;
+                return Ok(None);
             }
 
-            let parent = std::path::Path::new(&file.path)
+            let parent = Path::new(&file.path)
                 .parent()
-                .unwrap_or_else(|| std::path::Path::new("."));
+                .unwrap_or_else(|| Path::new("."));
 
-            let new_name = expand_template(&action.rename_pattern, file);
-            Some(parent.join(&new_name))
+            let name = resolve_file_name(file, action, captures)?;
+            let dest = parent.join(name);
+            sanitize::ensure_within(parent, &dest)?;
+            Ok(Some(dest))
+        }
+        "extract" => {
+            // The destination is the folder the archive is unpacked into,
+            // defaulting to a folder named after the archive
+            if archive_kind(Path::new(&file.path)).is_none() {
+                bail!("'{}' is not a supported archive (zip, tar, tar.gz)", file.name);
+            }
+
+            let template = if action.destination.is_empty() {
+                "{original}"
+            } else {
+                action.destination.as_str()
+            };
+            resolve_folder(file, template, captures).map(Some)
+        }
+        "archive" => {
+            // The destination is the .zip file the file is added to
+            if action.destination.is_empty() {
+                return Ok(None);
+            }
+
+            let archive = resolve_folder(file, &action.destination, captures)?;
+            let is_zip = archive
+                .extension()
+                .map(|e| e.eq_ignore_ascii_case("zip"))
+                .unwrap_or(false);
+            if !is_zip {
+                bail!("Archive destination must be a .zip file: '{}'", archive.display());
+            }
+            Ok(Some(archive))
         }
         "tag" | "add_tag" => {
             // Tag actions don't produce a destination path
-            None
+            Ok(None)
         }
         "delete" => {
             // Delete actions don't move the file
-            None
+            Ok(None)
         }
         other => {
             log::warn!("Unknown action type: '{}', treating as no-op", other);
-            None
+            Ok(None)
         }
     }
 }
 
-/// Expands template placeholders in a string using file metadata.
-fn expand_template(template: &str, file: &FileEntry) -> String {
+/// Resolves a folder template for a file. Absolute templates are sanitized as
+/// they are; relative templates are placed under the file's current folder
+/// and must stay inside it.
+pub fn resolve_folder(
+    file: &FileEntry,
+    template: &str,
+    captures: &HashMap<String, String>,
+) -> Result<PathBuf> {
+    let expanded = expand_template(template, file, captures);
+
+    if Path::new(&expanded).is_absolute() {
+        return sanitize::sanitize_path(&expanded);
+    }
+
+    let parent = Path::new(&file.path)
+        .parent()
+        .unwrap_or_else(|| Path::new("."));
+    let dest = parent.join(sanitize::sanitize_relative(&expanded)?);
+    sanitize::ensure_within(parent, &dest)?;
+    Ok(dest)
+}
+
+/// Returns the target file name: the expanded rename pattern when set,
+/// otherwise the file's current name.
+fn resolve_file_name(
+    file: &FileEntry,
+    action: &RuleAction,
+    captures: &HashMap<String, String>,
+) -> Result<String> {
+    if action.rename_pattern.is_empty() {
+        Ok(file.name.clone())
+    } else {
+        sanitize::sanitize_file_name(&expand_template(&action.rename_pattern, file, captures))
+    }
+}
+
+/// Expands template placeholders in a string using file metadata and regex
+/// captures. Placeholders are resolved in a single pass, so values inserted
+/// from the filename are never expanded again, and each inserted value has
+/// separators and illegal characters replaced. Unknown placeholders are kept
+/// as-is.
+fn expand_template(template: &str, file: &FileEntry, captures: &HashMap<String, String>) -> String {
     let stem = std::path::Path::new(&file.name)
         .file_stem()
         .unwrap_or_default()
@@ -86,13 +180,46 @@ fn expand_template(template: &str, file: &FileEntry) -> String {
 
     let (year, month, day) = extract_date_parts(&file.modified_at);
 
-    template
-        .replace("{extension}", &file.extension)
-        .replace("{year}", &year)
-        .replace("{month}", &month)
-        .replace("{day}", &day)
-        .replace("{original}", &stem)
-        .replace("{counter}", "1")
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+
+        let end = match after.find('}') {
+            Some(end) => end,
+            None => {
+                output.push_str(&rest[start..]);
+                return output;
+            }
+        };
+
+        let key = &after[..end];
+        let value = match key {
+            "extension" => Some(file.extension.as_str()),
+            "year" => Some(year.as_str()),
+            "month" => Some(month.as_str()),
+            "day" => Some(day.as_str()),
+            "original" => Some(stem.as_str()),
+            "counter" => Some("1"),
+            other => captures.get(other).map(String::as_str),
+        };
+
+        match value {
+            Some(v) => output.push_str(&sanitize::sanitize_value(v)),
+            None => {
+                output.push('{');
+                output.push_str(key);
+                output.push('}');
+            }
+        }
+
+        rest = &after[end + 1..];
+    }
+
+    output.push_str(rest);
+    output
 }
 
 /// Extracts year, month, day strings from a datetime string formatted as
@@ -148,9 +275,12 @@ mod tests {
             tag_name: "".to_string(),
             sort_order: 0,
             created_at: "".to_string(),
+            post_action: "keep".to_string(),
+            post_destination: "".to_string(),
+            conflict_strategy: "".to_string(),
         };
 
-        let result = resolve_action(&file, &action).unwrap();
+        let result = resolve_action(&file, &action).unwrap().unwrap();
         assert_eq!(result, PathBuf::from("/sorted/pdf/report.pdf"));
     }
 
@@ -166,15 +296,155 @@ mod tests {
             tag_name: "".to_string(),
             sort_order: 0,
             created_at: "".to_string(),
+            post_action: "keep".to_string(),
+            post_destination: "".to_string(),
+            conflict_strategy: "".to_string(),
         };
 
-        let result = resolve_action(&file, &action).unwrap();
+        let result = resolve_action(&file, &action).unwrap().unwrap();
         assert_eq!(
             result,
             PathBuf::from("/archive/2025/06/report_20250615.pdf")
         );
     }
 
+    #[test]
+    fn test_capture_groups_in_templates() {
+        let mut file = make_file();
+        file.name = "2024-11_fatura.pdf".to_string();
+        file.path = "/downloads/2024-11_fatura.pdf".to_string();
+
+        let action = RuleAction {
+            id: "a1".to_string(),
+            rule_id: "r1".to_string(),
+            action_type: "move".to_string(),
+            destination: "/faturas/{1}/{client}".to_string(),
+            rename_pattern: "{client}_{1}-{2}.{extension}".to_string(),
+            tag_name: "".to_string(),
+            sort_order: 0,
+            created_at: "".to_string(),
+            post_action: "keep".to_string(),
+            post_destination: "".to_string(),
+            conflict_strategy: "".to_string(),
+        };
+
+        let mut captures = HashMap::new();
+        captures.insert("1".to_string(), "2024".to_string());
+        captures.insert("2".to_string(), "11".to_string());
+        captures.insert("client".to_string(), "acme".to_string());
+
+        let result = resolve_action_with_captures(&file, &action, &captures).unwrap().unwrap();
+        assert_eq!(
+            result,
+            PathBuf::from("/faturas/2024/acme/acme_2024-11.pdf")
+        );
+    }
+
+    #[test]
+    fn test_subfolder_traversal_is_rejected() {
+        let file = make_file();
+        let action = RuleAction {
+            id: "a1".to_string(),
+            rule_id: "r1".to_string(),
+            action_type: "move_to_subfolder".to_string(),
+            destination: "../../etc".to_string(),
+            rename_pattern: "".to_string(),
+            tag_name: "".to_string(),
+            sort_order: 0,
+            created_at: "".to_string(),
+            post_action: "keep".to_string(),
+            post_destination: "".to_string(),
+            conflict_strategy: "".to_string(),
+        };
+
+        assert!(resolve_action(&file, &action).is_err());
+    }
+
+    #[test]
+    fn test_capture_values_cannot_add_separators() {
+        let file = make_file();
+        let action = RuleAction {
+            id: "a1".to_string(),
+            rule_id: "r1".to_string(),
+            action_type: "rename".to_string(),
+            destination: "".to_string(),
+            rename_pattern: "{client}.{extension}".to_string(),
+            tag_name: "".to_string(),
+            sort_order: 0,
+            created_at: "".to_string(),
+            post_action: "keep".to_string(),
+            post_destination: "".to_string(),
+            conflict_strategy: "".to_string(),
+        };
+
+        let mut captures = HashMap::new();
+        captures.insert("client".to_string(), "../evil:name".to_string());
+
+        let result = resolve_action_with_captures(&file, &action, &captures)
+            .unwrap()
+            .unwrap();
+        assert_eq!(result, PathBuf::from("/downloads/.._evil_name.pdf"));
+    }
+
+    #[test]
+    fn test_extract_defaults_to_folder_named_after_archive() {
+        let mut file = make_file();
+        file.path = "/downloads/photos.zip".to_string();
+        file.name = "photos.zip".to_string();
+        file.extension = "zip".to_string();
+
+        let action = RuleAction {
+            id: "a1".to_string(),
+            rule_id: "r1".to_string(),
+            action_type: "extract".to_string(),
+            destination: "".to_string(),
+            rename_pattern: "".to_string(),
+            tag_name: "".to_string(),
+            sort_order: 0,
+            created_at: "".to_string(),
+            post_action: "delete".to_string(),
+            post_destination: "".to_string(),
+            conflict_strategy: "".to_string(),
+        };
+
+        let result = resolve_action(&file, &action).unwrap().unwrap();
+        assert_eq!(result, PathBuf::from("/downloads/photos"));
+
+        // Non-archives cannot be extracted
+        assert!(resolve_action(&make_file(), &action).is_err());
+    }
+
+    #[test]
+    fn test_archive_resolves_dated_zip() {
+        let file = make_file();
+        let mut action = RuleAction {
+            id: "a1".to_string(),
+            rule_id: "r1".to_string(),
+            action_type: "archive".to_string(),
+            destination: "Arquivo/{year}-{month}.zip".to_string(),
+            rename_pattern: "".to_string(),
+            tag_name: "".to_string(),
+            sort_order: 0,
+            created_at: "".to_string(),
+            post_action: "keep".to_string(),
+            post_destination: "".to_string(),
+            conflict_strategy: "".to_string(),
+        };
+
+        let result = resolve_action(&file, &action).unwrap().unwrap();
+        assert_eq!(result, PathBuf::from("/downloads/Arquivo/2025-06.zip"));
+
+        action.destination = "Arquivo/{year}".to_string();
+        assert!(resolve_action(&file, &action).is_err());
+    }
+
+    #[test]
+    fn test_unknown_placeholder_is_kept() {
+        let file = make_file();
+        let expanded = expand_template("{original}_{missing}", &file, &HashMap::new());
+        assert_eq!(expanded, "report_{missing}");
+    }
+
     #[test]
     fn test_tag_action_returns_none() {
         let file = make_file();
@@ -187,8 +457,11 @@ mod tests {
             tag_name: "important".to_string(),
             sort_order: 0,
             created_at: "".to_string(),
+            post_action: "keep".to_string(),
+            post_destination: "".to_string(),
+            conflict_strategy: "".to_string(),
         };
 
-        assert!(resolve_action(&file, &action).is_none());
+        assert!(resolve_action(&file, &action).unwrap().is_none());
     }
 }
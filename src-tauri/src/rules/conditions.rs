@@ -1,4 +1,5 @@
 use regex::Regex;
+use std::collections::HashMap;
 
 use crate::db::models::{FileEntry, RuleCondition};
 
@@ -77,6 +78,50 @@ pub fn evaluate_conditions(file: &FileEntry, conditions: &[RuleCondition]) -> bo
     result
 }
 
+/// Collects regex capture groups from every `matches` condition that matches
+/// the file, so they can be reused as template variables in actions.
+///
+/// Numbered groups are exposed by index (`"1"`, `"2"`, ...) and named groups by
+/// name (`"client"`). When several conditions capture the same key, the first
+/// condition (in sort order) wins. Groups that did not participate in the
+/// match are left out.
+pub fn collect_captures(file: &FileEntry, conditions: &[RuleCondition]) -> HashMap<String, String> {
+    let mut captures = HashMap::new();
+
+    for condition in conditions {
+        if condition.operator != "matches" {
+            continue;
+        }
+
+        let re = match Regex::new(&condition.value) {
+            Ok(re) => re,
+            Err(_) => continue,
+        };
+
+        let file_value = get_field_value(file, &condition.field);
+        let caps = match re.captures(&file_value) {
+            Some(c) => c,
+            None => continue,
+        };
+
+        for (index, name) in re.capture_names().enumerate().skip(1) {
+            let group = match caps.get(index) {
+                Some(m) => m.as_str().to_string(),
+                None => continue,
+            };
+
+            captures
+                .entry(index.to_string())
+                .or_insert_with(|| group.clone());
+            if let Some(name) = name {
+                captures.entry(name.to_string()).or_insert(group);
+            }
+        }
+    }
+
+    captures
+}
+
 /// Extracts the value of a field from a file entry for comparison.
 fn get_field_value(file: &FileEntry, field: &str) -> String {
     match field {
@@ -160,6 +205,30 @@ mod tests {
         assert!(!evaluate_condition(&file, &make_condition("regex", "matches", r"^image\.\w+")));
     }
 
+    #[test]
+    fn test_collect_captures_numbered_and_named() {
+        let mut file = make_file();
+        file.name = "2025-03_fatura_acme.pdf".to_string();
+        let cond = make_condition(
+            "filename",
+            "matches",
+            r"^(\d{4})-(\d{2})_fatura_(?P<client>\w+)",
+        );
+
+        let caps = collect_captures(&file, &[cond]);
+        assert_eq!(caps.get("1").map(String::as_str), Some("2025"));
+        assert_eq!(caps.get("2").map(String::as_str), Some("03"));
+        assert_eq!(caps.get("3").map(String::as_str), Some("acme"));
+        assert_eq!(caps.get("client").map(String::as_str), Some("acme"));
+    }
+
+    #[test]
+    fn test_collect_captures_ignores_non_matching() {
+        let file = make_file();
+        let cond = make_condition("filename", "matches", r"^invoice_(\d+)");
+        assert!(collect_captures(&file, &[cond]).is_empty());
+    }
+
     #[test]
     fn test_and_logic() {
         let file = make_file();
@@ -112,6 +112,9 @@ mod tests {
             tag_name: "".to_string(),
             sort_order: 0,
             created_at: "".to_string(),
+            post_action: "keep".to_string(),
+            post_destination: "".to_string(),
+            conflict_strategy: "".to_string(),
         };
 
         let mut conditions = HashMap::new();
@@ -0,0 +1,37 @@
+use std::path::Path;
+
+/// Archive formats supported by the `extract` action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+/// Detects the archive format from the file name. Returns `None` for
+/// unsupported formats.
+pub fn archive_kind(path: &Path) -> Option<ArchiveKind> {
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    if name.ends_with(".zip") {
+        Some(ArchiveKind::Zip)
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Some(ArchiveKind::TarGz)
+    } else if name.ends_with(".tar") {
+        Some(ArchiveKind::Tar)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_kind() {
+        assert_eq!(archive_kind(Path::new("a.zip")), Some(ArchiveKind::Zip));
+        assert_eq!(archive_kind(Path::new("a.TAR.GZ")), Some(ArchiveKind::TarGz));
+        assert_eq!(archive_kind(Path::new("a.tar")), Some(ArchiveKind::Tar));
+        assert_eq!(archive_kind(Path::new("a.rar")), None);
+    }
+}
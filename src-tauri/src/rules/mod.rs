@@ -1,3 +1,5 @@
 pub mod actions;
 pub mod conditions;
 pub mod engine;
+pub mod formats;
+pub mod sanitize;
@@ -0,0 +1,176 @@
+use anyhow::{bail, Result};
+use std::path::{Component, Path, PathBuf};
+
+/// Characters that are not allowed in a single path segment. Includes the
+/// characters Windows rejects plus both path separators.
+const ILLEGAL_CHARS: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// Device names reserved by Windows, with or without an extension.
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Replaces illegal and control characters in a template value with `_`.
+///
+/// Applied to every value inserted into a template (filename parts, dates,
+/// regex captures), so a value can never introduce a path separator.
+pub fn sanitize_value(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| {
+            if ILLEGAL_CHARS.contains(&c) || c.is_control() {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Makes a single path segment safe on every platform:
+/// - illegal and control characters become `_`
+/// - trailing dots and spaces are removed (Windows drops them silently)
+/// - reserved device names (`CON`, `NUL.txt`, ...) are prefixed with `_`
+/// - empty segments, `.` and `..` become `_`
+pub fn sanitize_segment(segment: &str) -> String {
+    let mut cleaned = sanitize_value(segment);
+
+    let trimmed_len = cleaned.trim_end_matches(['.', ' ']).len();
+    cleaned.truncate(trimmed_len);
+
+    if cleaned.is_empty() {
+        return "_".to_string();
+    }
+
+    let base = cleaned.split('.').next().unwrap_or("").trim_end();
+    if RESERVED_NAMES.iter().any(|r| r.eq_ignore_ascii_case(base)) {
+        cleaned.insert(0, '_');
+    }
+
+    cleaned
+}
+
+/// Validates an expanded file name (e.g. a rename pattern result). The name
+/// must be a single segment: a separator coming from the template itself is
+/// rejected instead of silently creating folders.
+pub fn sanitize_file_name(name: &str) -> Result<String> {
+    if name.contains('/') || name.contains('\\') {
+        bail!(
+            "Rename pattern must produce a file name, not a path: '{}'",
+            name
+        );
+    }
+    if name.trim().is_empty() {
+        bail!("Rename pattern produced an empty file name");
+    }
+    Ok(sanitize_segment(name))
+}
+
+/// Sanitizes an expanded relative folder (e.g. a `move_to_subfolder`
+/// destination). Absolute paths and `..` segments are rejected because they
+/// would escape the folder the file is being organized in.
+pub fn sanitize_relative(path: &str) -> Result<PathBuf> {
+    if path.starts_with('/') || path.starts_with('\\') || has_drive_prefix(path) {
+        bail!("Destination must be a relative folder name: '{}'", path);
+    }
+
+    let mut result = PathBuf::new();
+    for segment in path.split(['/', '\\']) {
+        match segment {
+            "" | "." => continue,
+            ".." => bail!("Destination cannot contain '..': '{}'", path),
+            s => result.push(sanitize_segment(s)),
+        }
+    }
+
+    if result.as_os_str().is_empty() {
+        bail!("Destination resolved to an empty folder name: '{}'", path);
+    }
+
+    Ok(result)
+}
+
+/// Sanitizes an expanded destination folder that may be absolute. The root
+/// and drive prefix are kept as-is; every other segment is sanitized and
+/// `..` segments are rejected.
+pub fn sanitize_path(path: &str) -> Result<PathBuf> {
+    let mut result = PathBuf::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::Prefix(p) => result.push(p.as_os_str()),
+            Component::RootDir => result.push(Component::RootDir.as_os_str()),
+            Component::CurDir => continue,
+            Component::ParentDir => bail!("Destination cannot contain '..': '{}'", path),
+            Component::Normal(s) => result.push(sanitize_segment(&s.to_string_lossy())),
+        }
+    }
+
+    if result.as_os_str().is_empty() {
+        bail!("Destination resolved to an empty path: '{}'", path);
+    }
+
+    Ok(result)
+}
+
+/// Ensures `path` stays inside `root`. Both paths are compared lexically, so
+/// this is meant for paths that were already sanitized.
+pub fn ensure_within(root: &Path, path: &Path) -> Result<()> {
+    if !path.starts_with(root) || path == root {
+        bail!(
+            "Destination '{}' is outside the allowed folder '{}'",
+            path.display(),
+            root.display()
+        );
+    }
+    Ok(())
+}
+
+/// Returns true for Windows drive-qualified paths such as `C:` or `C:\`.
+fn has_drive_prefix(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_segment_illegal_chars() {
+        assert_eq!(sanitize_segment("a:b*c?d\"e<f>g|h"), "a_b_c_d_e_f_g_h");
+    }
+
+    #[test]
+    fn test_sanitize_segment_reserved_and_trailing() {
+        assert_eq!(sanitize_segment("CON"), "_CON");
+        assert_eq!(sanitize_segment("nul.txt"), "_nul.txt");
+        assert_eq!(sanitize_segment("report. "), "report");
+        assert_eq!(sanitize_segment(".."), "_");
+        assert_eq!(sanitize_segment("console"), "console");
+    }
+
+    #[test]
+    fn test_sanitize_relative_rejects_escape() {
+        assert!(sanitize_relative("../outside").is_err());
+        assert!(sanitize_relative("/etc").is_err());
+        assert!(sanitize_relative("C:\\Windows").is_err());
+        assert_eq!(
+            sanitize_relative("Docs/2025").unwrap(),
+            PathBuf::from("Docs").join("2025")
+        );
+    }
+
+    #[test]
+    fn test_sanitize_file_name_rejects_path() {
+        assert!(sanitize_file_name("sub/name.txt").is_err());
+        assert_eq!(sanitize_file_name("ok.txt").unwrap(), "ok.txt");
+    }
+
+    #[test]
+    fn test_ensure_within() {
+        let root = Path::new("/downloads");
+        assert!(ensure_within(root, Path::new("/downloads/Docs/a.pdf")).is_ok());
+        assert!(ensure_within(root, Path::new("/etc/passwd")).is_err());
+    }
+}
@@ -8,6 +8,7 @@ import {
   FlaskConical,
   Loader2,
   Inbox,
+  MinusCircle,
 } from 'lucide-react';
 import { Card } from '@/components/ui/Card';
 import { Badge } from '@/components/ui/Badge';
@@ -28,8 +29,10 @@ const STATUS_MAP: Record<string, StatusConfig> = {
   completed: { label: 'Concluído', variant: 'success', icon: CheckCircle2 },
   failed: { label: 'Falhou', variant: 'danger', icon: XCircle },
   rolled_back: { label: 'Revertido', variant: 'warning', icon: RotateCcw },
+  rollback_partial: { label: 'Parcialmente revertido', variant: 'warning', icon: RotateCcw },
   running: { label: 'Executando', variant: 'info', icon: Loader2 },
   pending: { label: 'Pendente', variant: 'default', icon: Loader2 },
+  idle: { label: 'Nada a fazer', variant: 'default', icon: MinusCircle },
 };
 
 const TYPE_MAP: Record<string, StatusConfig> = {
@@ -0,0 +1,56 @@
+import { useState, useCallback } from 'react';
+import { Download } from 'lucide-react';
+import { Button } from '@/components/ui/Button';
+import { Select } from '@/components/ui/Select';
+import { useToast } from '@/components/ui/Toast';
+import { tauriInvoke } from '@/lib/tauri';
+import { REPORT_FORMATS } from '@/lib/constants';
+import type { ReportFormat } from '@/types/runs';
+
+interface ExportReportButtonProps {
+  /** `export_simulation` or `export_run`. */
+  command: 'export_simulation' | 'export_run';
+  /** Arguments identifying the plan or run, e.g. `{ runId }`. */
+  args: Record<string, unknown>;
+}
+
+export function ExportReportButton({ command, args }: ExportReportButtonProps) {
+  const [format, setFormat] = useState<ReportFormat>('csv');
+  const [exporting, setExporting] = useState(false);
+  const toast = useToast();
+
+  const handleExport = useCallback(async () => {
+    setExporting(true);
+    try {
+      const path = await tauriInvoke<string | null>(command, { ...args, format });
+      if (path) {
+        toast.success(`Relatório salvo em ${path}`);
+      }
+    } catch (err) {
+      const message = err instanceof Error ? err.message : String(err);
+      toast.error(`Erro ao exportar: ${message}`);
+    } finally {
+      setExporting(false);
+    }
+  }, [command, args, format, toast]);
+
+  return (
+    <div className="flex items-center gap-2">
+      <Select
+        aria-label="Formato do relatório"
+        options={REPORT_FORMATS}
+        value={format}
+        onChange={(e) => setFormat(e.target.value as ReportFormat)}
+      />
+      <Button
+        variant="secondary"
+        size="sm"
+        icon={Download}
+        loading={exporting}
+        onClick={handleExport}
+      >
+        Exportar
+      </Button>
+    </div>
+  );
+}
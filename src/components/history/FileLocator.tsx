@@ -0,0 +1,137 @@
+import { useState, useCallback } from 'react';
+import { ArrowRight, FolderOpen, Search } from 'lucide-react';
+import { Card } from '@/components/ui/Card';
+import { Badge } from '@/components/ui/Badge';
+import { Button } from '@/components/ui/Button';
+import { Input } from '@/components/ui/Input';
+import { useToast } from '@/components/ui/Toast';
+import { tauriInvoke } from '@/lib/tauri';
+import { formatDate, truncatePath } from '@/lib/formatters';
+import type { FileLocation } from '@/types/runs';
+
+const statusConfig: Record<
+  FileLocation['status'],
+  { label: string; variant: 'success' | 'danger' | 'warning' | 'default' | 'info' }
+> = {
+  found: { label: 'Encontrado', variant: 'success' },
+  renamed: { label: 'Renomeado depois', variant: 'info' },
+  restored: { label: 'Revertido', variant: 'warning' },
+  deleted: { label: 'Excluído', variant: 'danger' },
+  missing: { label: 'Não encontrado', variant: 'danger' },
+};
+
+/** Answers "where did my file go?" from the run history and the disk. */
+export function FileLocator() {
+  const toast = useToast();
+  const [query, setQuery] = useState('');
+  const [results, setResults] = useState<FileLocation[] | null>(null);
+  const [searching, setSearching] = useState(false);
+
+  const handleSearch = useCallback(async () => {
+    if (!query.trim()) return;
+    setSearching(true);
+    try {
+      setResults(await tauriInvoke<FileLocation[]>('locate_file', { query }));
+    } catch (err) {
+      const message = err instanceof Error ? err.message : String(err);
+      toast.error(`Erro ao localizar arquivo: ${message}`);
+    } finally {
+      setSearching(false);
+    }
+  }, [query, toast]);
+
+  const handleOpenFolder = useCallback(
+    async (path: string) => {
+      try {
+        await tauriInvoke('open_containing_folder', { path });
+      } catch (err) {
+        const message = err instanceof Error ? err.message : String(err);
+        toast.error(message);
+      }
+    },
+    [toast],
+  );
+
+  return (
+    <Card padding="sm">
+      <form
+        className="flex items-center gap-2"
+        onSubmit={(e) => {
+          e.preventDefault();
+          handleSearch();
+        }}
+      >
+        <div className="flex-1">
+          <Input
+            icon={Search}
+            aria-label="Onde está meu arquivo?"
+            placeholder="Onde está meu arquivo? Digite o nome ou parte do caminho"
+            value={query}
+            onChange={(e) => setQuery(e.target.value)}
+          />
+        </div>
+        <Button type="submit" variant="secondary" size="sm" loading={searching}>
+          Localizar
+        </Button>
+      </form>
+
+      {results && (
+        <div className="mt-3 divide-y divide-gray-100 dark:divide-gray-800">
+          {results.length === 0 ? (
+            <p className="py-3 text-sm text-gray-500 dark:text-gray-400">
+              Nenhum arquivo com esse nome foi organizado pelo DeskCraft.
+            </p>
+          ) : (
+            results.map((location) => {
+              const config = statusConfig[location.status];
+              return (
+                <div key={location.run_item_id} className="py-3 flex items-start gap-3">
+                  <div className="flex-1 min-w-0 space-y-1">
+                    <div className="flex items-center gap-2 flex-wrap">
+                      <Badge variant={config.variant} size="sm">
+                        {config.label}
+                      </Badge>
+                      {location.rule_name && (
+                        <span className="text-xs text-gray-500 dark:text-gray-400">
+                          Regra: {location.rule_name}
+                        </span>
+                      )}
+                      {location.moved_at && (
+                        <span className="text-xs text-gray-400 dark:text-gray-500">
+                          {formatDate(location.moved_at)}
+                        </span>
+                      )}
+                    </div>
+                    <p className="text-sm text-gray-900 dark:text-gray-100 break-all">
+                      {location.current_path ?? truncatePath(location.original_path, 60)}
+                    </p>
+                    {location.path_history.length > 2 && (
+                      <div className="flex items-center gap-1 flex-wrap text-xs text-gray-400 dark:text-gray-500">
+                        {location.path_history.map((path, idx) => (
+                          <span key={idx} className="inline-flex items-center gap-1">
+                            {idx > 0 && <ArrowRight size={10} className="shrink-0" />}
+                            {truncatePath(path, 30)}
+                          </span>
+                        ))}
+                      </div>
+                    )}
+                  </div>
+                  {location.current_path && (
+                    <Button
+                      variant="ghost"
+                      size="sm"
+                      icon={FolderOpen}
+                      onClick={() => handleOpenFolder(location.current_path!)}
+                    >
+                      Abrir pasta
+                    </Button>
+                  )}
+                </div>
+              );
+            })
+          )}
+        </div>
+      )}
+    </Card>
+  );
+}
@@ -12,17 +12,22 @@ import {
   FolderOpen,
   Timer,
   Filter,
+  MinusCircle,
+  PlayCircle,
 } from 'lucide-react';
 import { Card } from '@/components/ui/Card';
 import { Badge } from '@/components/ui/Badge';
 import { Button } from '@/components/ui/Button';
 import { Select } from '@/components/ui/Select';
+import { Input } from '@/components/ui/Input';
 import { EmptyState } from '@/components/ui/EmptyState';
 import { useHistoryStore, useProfileStore } from '@/stores';
+import { useToast } from '@/components/ui/Toast';
 import { formatRelativeTime, formatDate } from '@/lib/formatters';
 import { RunDetail } from './RunDetail';
 import { RollbackDialog } from './RollbackDialog';
-import type { Run } from '@/types/runs';
+import { FileLocator } from './FileLocator';
+import type { Run, RunFilter } from '@/types/runs';
 
 /* ---------- Constants ---------- */
 
@@ -69,6 +74,30 @@ const statusConfig: Record<
     icon: RotateCcw,
     color: 'text-amber-500 dark:text-amber-400',
   },
+  rollback_partial: {
+    label: 'Parcialmente revertido',
+    variant: 'warning',
+    icon: RotateCcw,
+    color: 'text-amber-500 dark:text-amber-400',
+  },
+  interrupted: {
+    label: 'Interrompido',
+    variant: 'warning',
+    icon: AlertTriangle,
+    color: 'text-amber-500 dark:text-amber-400',
+  },
+  cancelled: {
+    label: 'Cancelado',
+    variant: 'default',
+    icon: XCircle,
+    color: 'text-gray-500 dark:text-gray-400',
+  },
+  idle: {
+    label: 'Nada a fazer',
+    variant: 'default',
+    icon: MinusCircle,
+    color: 'text-gray-400 dark:text-gray-500',
+  },
 };
 
 const runTypeConfig: Record<
@@ -79,6 +108,7 @@ const runTypeConfig: Record<
   simulation: { label: 'Simulação', variant: 'info' },
   watcher: { label: 'Monitoramento', variant: 'success' },
   scheduled: { label: 'Agendado', variant: 'warning' },
+  retry: { label: 'Repetição', variant: 'info' },
 };
 
 /* ---------- Filter Options ---------- */
@@ -88,17 +118,28 @@ const statusFilterOptions = [
   { value: 'completed', label: 'Concluídos' },
   { value: 'failed', label: 'Falhou' },
   { value: 'rolled_back', label: 'Revertido' },
-  { value: 'simulation', label: 'Simulação' },
+  { value: 'rollback_partial', label: 'Parcialmente revertido' },
+  { value: 'cancelled', label: 'Cancelado' },
+  { value: 'idle', label: 'Nada a fazer' },
   { value: 'pending', label: 'Pendente' },
 ];
 
+const typeFilterOptions = [
+  { value: 'all', label: 'Todos os tipos' },
+  ...Object.entries(runTypeConfig).map(([value, { label }]) => ({ value, label })),
+];
+
+/** How many runs a search brings back. */
+const SEARCH_LIMIT = 200;
+
 /* ---------- Duration Helper ---------- */
 
-function formatDuration(startedAt: string, completedAt: string | null): string {
-  if (!completedAt) return 'em andamento';
-  const start = new Date(startedAt).getTime();
-  const end = new Date(completedAt).getTime();
-  const diffMs = end - start;
+function formatDuration(run: Run): string {
+  if (!run.completed_at) return 'em andamento';
+  // Older runs have no recorded duration; fall back to the wall-clock time
+  const diffMs =
+    run.duration_ms ??
+    new Date(run.completed_at).getTime() - new Date(run.started_at).getTime();
 
   if (diffMs < 1000) return `${diffMs}ms`;
   if (diffMs < 60000) return `${(diffMs / 1000).toFixed(1)}s`;
@@ -114,9 +155,19 @@ interface RunListItemProps {
   index: number;
   onViewDetail: (id: string) => void;
   onRollback: (run: Run) => void;
+  onResume: (run: Run) => void;
+  /** Whether this run is being resumed. */
+  resuming: boolean;
 }
 
-function RunListItem({ run, index, onViewDetail, onRollback }: RunListItemProps) {
+function RunListItem({
+  run,
+  index,
+  onViewDetail,
+  onRollback,
+  onResume,
+  resuming,
+}: RunListItemProps) {
   const [isExpanded, setIsExpanded] = useState(false);
   const config = statusConfig[run.status];
   const typeConfig = runTypeConfig[run.run_type];
@@ -199,7 +250,7 @@ function RunListItem({ run, index, onViewDetail, onRollback }: RunListItemProps)
                 <span className="text-gray-300 dark:text-gray-600">|</span>
                 <span className="flex items-center gap-1">
                   <Timer size={10} />
-                  {formatDuration(run.started_at, run.completed_at)}
+                  {formatDuration(run)}
                 </span>
               </div>
             </div>
@@ -266,7 +317,7 @@ function RunListItem({ run, index, onViewDetail, onRollback }: RunListItemProps)
                     Arquivos:
                   </span>
                   <p className="text-gray-700 dark:text-gray-300 mt-0.5">
-                    {run.total_files} total, {run.moved_files} movido{run.moved_files !== 1 ? 's' : ''}, {run.skipped_files} ignorado{run.skipped_files !== 1 ? 's' : ''}, {run.error_files} erro{run.error_files !== 1 ? 's' : ''}
+                    {run.scanned_files} analisado{run.scanned_files !== 1 ? 's' : ''}, {run.total_files} total, {run.moved_files} movido{run.moved_files !== 1 ? 's' : ''}, {run.skipped_files} ignorado{run.skipped_files !== 1 ? 's' : ''}, {run.error_files} erro{run.error_files !== 1 ? 's' : ''}
                   </p>
                 </div>
               </div>
@@ -292,7 +343,9 @@ function RunListItem({ run, index, onViewDetail, onRollback }: RunListItemProps)
                 >
                   Ver detalhes
                 </Button>
-                {run.status === 'completed' && run.moved_files > 0 && (
+                {(run.status === 'completed' || run.status === 'rollback_partial') &&
+                  run.moved_files > 0 &&
+                  !run.pruned_at && (
                   <Button
                     variant="ghost"
                     size="sm"
@@ -306,6 +359,20 @@ function RunListItem({ run, index, onViewDetail, onRollback }: RunListItemProps)
                     Desfazer
                   </Button>
                 )}
+                {run.status === 'interrupted' && !run.pruned_at && (
+                  <Button
+                    variant="ghost"
+                    size="sm"
+                    icon={PlayCircle}
+                    loading={resuming}
+                    onClick={(e) => {
+                      e.stopPropagation();
+                      onResume(run);
+                    }}
+                  >
+                    Retomar
+                  </Button>
+                )}
               </div>
             </div>
           </motion.div>
@@ -320,36 +387,69 @@ function RunListItem({ run, index, onViewDetail, onRollback }: RunListItemProps)
 export function HistoryView() {
   const runs = useHistoryStore((s) => s.runs);
   const isLoading = useHistoryStore((s) => s.isLoading);
-  const fetchRuns = useHistoryStore((s) => s.fetchRuns);
+  const searchRuns = useHistoryStore((s) => s.searchRuns);
   const clearSelected = useHistoryStore((s) => s.clearSelected);
+  const resumeRun = useHistoryStore((s) => s.resumeRun);
+  const profiles = useProfileStore((s) => s.profiles);
   const fetchProfiles = useProfileStore((s) => s.fetchProfiles);
+  const toast = useToast();
 
   const [statusFilter, setStatusFilter] = useState('all');
+  const [typeFilter, setTypeFilter] = useState('all');
+  const [profileFilter, setProfileFilter] = useState('all');
+  const [fromDate, setFromDate] = useState('');
+  const [toDate, setToDate] = useState('');
   const [visibleCount, setVisibleCount] = useState(PAGE_SIZE);
   const [selectedRunId, setSelectedRunId] = useState<string | null>(null);
   const [rollbackRun, setRollbackRun] = useState<Run | null>(null);
+  const [resumingId, setResumingId] = useState<string | null>(null);
 
   useEffect(() => {
-    fetchRuns(200, 0);
     fetchProfiles();
   }, []); // eslint-disable-line react-hooks/exhaustive-deps
 
   /* --- Filter runs --- */
-  const filteredRuns = useMemo(() => {
-    let filtered = [...runs].sort(
-      (a, b) => new Date(b.started_at).getTime() - new Date(a.started_at).getTime(),
-    );
+  const filter = useMemo<RunFilter>(
+    () => ({
+      status: statusFilter !== 'all' ? (statusFilter as Run['status']) : undefined,
+      run_type: typeFilter !== 'all' ? (typeFilter as Run['run_type']) : undefined,
+      profile_id: profileFilter !== 'all' ? profileFilter : undefined,
+      from: fromDate || undefined,
+      to: toDate || undefined,
+      limit: SEARCH_LIMIT,
+    }),
+    [statusFilter, typeFilter, profileFilter, fromDate, toDate],
+  );
 
-    if (statusFilter !== 'all') {
-      if (statusFilter === 'simulation') {
-        filtered = filtered.filter((r) => r.run_type === 'simulation');
-      } else {
-        filtered = filtered.filter((r) => r.status === statusFilter);
-      }
-    }
+  const reload = useCallback(() => searchRuns(filter), [searchRuns, filter]);
 
-    return filtered;
-  }, [runs, statusFilter]);
+  useEffect(() => {
+    reload();
+    setVisibleCount(PAGE_SIZE);
+  }, [reload]);
+
+  const filteredRuns = useMemo(
+    () =>
+      [...runs].sort(
+        (a, b) => new Date(b.started_at).getTime() - new Date(a.started_at).getTime(),
+      ),
+    [runs],
+  );
+
+  const profileFilterOptions = useMemo(
+    () => [
+      { value: 'all', label: 'Todos os perfis' },
+      ...profiles.map((p) => ({ value: p.id, label: p.name })),
+    ],
+    [profiles],
+  );
+
+  const hasFilters =
+    statusFilter !== 'all' ||
+    typeFilter !== 'all' ||
+    profileFilter !== 'all' ||
+    !!fromDate ||
+    !!toDate;
 
   const visibleRuns = filteredRuns.slice(0, visibleCount);
   const hasMore = visibleCount < filteredRuns.length;
@@ -363,8 +463,8 @@ export function HistoryView() {
     setSelectedRunId(null);
     clearSelected();
     // Re-fetch to pick up any rollback changes
-    fetchRuns(200, 0);
-  }, [clearSelected, fetchRuns]);
+    reload();
+  }, [clearSelected, reload]);
 
   const handleRollback = useCallback((run: Run) => {
     setRollbackRun(run);
@@ -372,8 +472,27 @@ export function HistoryView() {
 
   const handleCloseRollback = useCallback(() => {
     setRollbackRun(null);
-    fetchRuns(200, 0);
-  }, [fetchRuns]);
+    reload();
+  }, [reload]);
+
+  const handleResume = useCallback(
+    async (run: Run) => {
+      setResumingId(run.id);
+      const result = await resumeRun(run.id);
+      setResumingId(null);
+
+      if (!result) {
+        toast.error(`Erro ao retomar: ${useHistoryStore.getState().error ?? ''}`);
+        return;
+      }
+      if (result.errors > 0) {
+        toast.info(`Execução retomada: ${result.moved} organizado(s), ${result.errors} com erro.`);
+      } else {
+        toast.success(`Execução retomada: ${result.moved} arquivo(s) organizado(s).`);
+      }
+    },
+    [resumeRun, toast],
+  );
 
   const handleLoadMore = useCallback(() => {
     setVisibleCount((c) => c + PAGE_SIZE);
@@ -381,7 +500,13 @@ export function HistoryView() {
 
   /* --- If viewing a run detail, show RunDetail --- */
   if (selectedRunId) {
-    return <RunDetail runId={selectedRunId} onBack={handleBackFromDetail} />;
+    return (
+      <RunDetail
+        runId={selectedRunId}
+        onBack={handleBackFromDetail}
+        onViewRun={handleViewDetail}
+      />
+    );
   }
 
   /* ========== RENDER ========== */
@@ -416,6 +541,15 @@ export function HistoryView() {
         </div>
       </motion.div>
 
+      {/* File Locator */}
+      <motion.div
+        initial={{ opacity: 0, y: 8 }}
+        animate={{ opacity: 1, y: 0 }}
+        transition={{ duration: 0.3, delay: 0.03 }}
+      >
+        <FileLocator />
+      </motion.div>
+
       {/* Filter Bar */}
       <motion.div
         initial={{ opacity: 0, y: 8 }}
@@ -427,12 +561,46 @@ export function HistoryView() {
             <Filter size={14} className="text-gray-400 dark:text-gray-500 shrink-0" />
             <div className="w-48">
               <Select
+                aria-label="Status"
                 options={statusFilterOptions}
                 value={statusFilter}
-                onChange={(e) => {
-                  setStatusFilter(e.target.value);
-                  setVisibleCount(PAGE_SIZE);
-                }}
+                onChange={(e) => setStatusFilter(e.target.value)}
+              />
+            </div>
+            <div className="w-44">
+              <Select
+                aria-label="Tipo de execução"
+                options={typeFilterOptions}
+                value={typeFilter}
+                onChange={(e) => setTypeFilter(e.target.value)}
+              />
+            </div>
+            <div className="w-44">
+              <Select
+                aria-label="Perfil"
+                options={profileFilterOptions}
+                value={profileFilter}
+                onChange={(e) => setProfileFilter(e.target.value)}
+              />
+            </div>
+            <div className="w-40">
+              <Input
+                type="date"
+                aria-label="De"
+                title="De"
+                value={fromDate}
+                max={toDate || undefined}
+                onChange={(e) => setFromDate(e.target.value)}
+              />
+            </div>
+            <div className="w-40">
+              <Input
+                type="date"
+                aria-label="Até"
+                title="Até"
+                value={toDate}
+                min={fromDate || undefined}
+                onChange={(e) => setToDate(e.target.value)}
               />
             </div>
             <span className="text-xs text-gray-400 dark:text-gray-500">
@@ -453,7 +621,7 @@ export function HistoryView() {
           animate={{ opacity: 1, y: 0 }}
           transition={{ duration: 0.3 }}
         >
-          {runs.length === 0 ? (
+          {!hasFilters ? (
             <EmptyState
               icon={Clock}
               title="Nenhuma execução registrada ainda"
@@ -482,6 +650,8 @@ export function HistoryView() {
                   index={idx}
                   onViewDetail={handleViewDetail}
                   onRollback={handleRollback}
+                  onResume={handleResume}
+                  resuming={resumingId === run.id}
                 />
               ))}
             </div>
@@ -1,12 +1,38 @@
-import { useState, useCallback } from 'react';
-import { AlertTriangle, RotateCcw } from 'lucide-react';
+import { useState, useCallback, useEffect } from 'react';
+import { AlertTriangle, ArrowRight, GitBranch, RotateCcw } from 'lucide-react';
 import { motion } from 'framer-motion';
 import { Dialog } from '@/components/ui/Dialog';
 import { Button } from '@/components/ui/Button';
 import { Badge } from '@/components/ui/Badge';
+import { Select } from '@/components/ui/Select';
 import { useHistoryStore } from '@/stores';
 import { useToast } from '@/components/ui/Toast';
-import type { Run } from '@/types/runs';
+import { tauriInvoke } from '@/lib/tauri';
+import { formatDate, truncatePath } from '@/lib/formatters';
+import type {
+  ChainMode,
+  RollbackDependencies,
+  RollbackPreview,
+  RollbackPreviewItem,
+  Run,
+} from '@/types/runs';
+
+const CHAIN_MODES: { value: ChainMode; label: string }[] = [
+  { value: 'cascade', label: 'Desfazer as execuções posteriores primeiro' },
+  { value: 'latest', label: 'Restaurar direto do local atual' },
+];
+
+/** How many file chains or items the preview lists before summarizing the rest. */
+const MAX_CHAINS_SHOWN = 5;
+
+const outcomeLabels: Record<RollbackPreviewItem['outcome'], string> = {
+  restore: 'volta ao lugar',
+  relocate: 'caminho original ocupado, restaurado em outro caminho',
+  keep: 'caminho original ocupado, mantido no destino',
+  remove: 'removido',
+  missing: 'não está mais no destino',
+  irreversible: 'excluído, não pode ser revertido',
+};
 
 /* ---------- Types ---------- */
 
@@ -24,6 +50,32 @@ export function RollbackDialog({ isOpen, onClose, run }: RollbackDialogProps) {
   const [isRollingBack, setIsRollingBack] = useState(false);
   const [result, setResult] = useState<'success' | 'error' | null>(null);
   const [errorMessage, setErrorMessage] = useState('');
+  const [dependencies, setDependencies] = useState<RollbackDependencies | null>(null);
+  const [preview, setPreview] = useState<RollbackPreview | null>(null);
+  const [chainMode, setChainMode] = useState<ChainMode>('cascade');
+
+  // Files moved again by later runs are shown before anything moves
+  useEffect(() => {
+    if (!isOpen) return;
+    let cancelled = false;
+    tauriInvoke<RollbackDependencies>('get_rollback_dependencies', { runId: run.id })
+      .then((deps) => {
+        if (!cancelled) setDependencies(deps);
+      })
+      .catch(() => {
+        if (!cancelled) setDependencies(null);
+      });
+    tauriInvoke<RollbackPreview>('simulate_rollback', { runId: run.id })
+      .then((simulated) => {
+        if (!cancelled) setPreview(simulated);
+      })
+      .catch(() => {
+        if (!cancelled) setPreview(null);
+      });
+    return () => {
+      cancelled = true;
+    };
+  }, [isOpen, run.id]);
 
   const handleRollback = useCallback(async () => {
     setIsRollingBack(true);
@@ -31,9 +83,28 @@ export function RollbackDialog({ isOpen, onClose, run }: RollbackDialogProps) {
     setErrorMessage('');
 
     try {
-      await rollbackRun(run.id);
+      const summary = await rollbackRun(run.id, chainMode);
       setResult('success');
       toast.success('Rollback realizado com sucesso!');
+      if (summary && summary.dependent_runs.length > 0) {
+        const count = summary.dependent_runs.length;
+        toast.info(
+          `${count} execuç${count !== 1 ? 'ões posteriores também foram revertidas' : 'ão posterior também foi revertida'} em parte.`,
+        );
+      }
+      if (summary && summary.items.length > 0) {
+        const relocated = summary.items.filter(
+          (i) => i.resolution === 'suffix' || i.resolution === 'restored_folder',
+        ).length;
+        const kept = summary.items.filter((i) => i.resolution === 'skipped').length;
+        const modified = summary.items.filter((i) => i.destination_modified).length;
+        const notes = [
+          relocated > 0 && `${relocated} restaurado${relocated !== 1 ? 's' : ''} em outro caminho`,
+          kept > 0 && `${kept} mantido${kept !== 1 ? 's' : ''} no destino`,
+          modified > 0 && `${modified} modificado${modified !== 1 ? 's' : ''} após a execução`,
+        ].filter(Boolean);
+        toast.info(`O caminho original de alguns arquivos estava ocupado ou eles mudaram: ${notes.join(', ')}.`);
+      }
       // Auto-close after success
       setTimeout(() => {
         onClose();
@@ -47,7 +118,7 @@ export function RollbackDialog({ isOpen, onClose, run }: RollbackDialogProps) {
     } finally {
       setIsRollingBack(false);
     }
-  }, [run.id, rollbackRun, toast, onClose]);
+  }, [run.id, chainMode, rollbackRun, toast, onClose]);
 
   const handleClose = useCallback(() => {
     if (!isRollingBack) {
@@ -129,10 +200,126 @@ export function RollbackDialog({ isOpen, onClose, run }: RollbackDialogProps) {
               )}
             </div>
 
+            {/* Consequences */}
+            {preview && (
+              <div className="space-y-2">
+                <div className="flex items-center gap-2 flex-wrap">
+                  {preview.restored > 0 && (
+                    <Badge variant="success">{preview.restored} voltam ao lugar</Badge>
+                  )}
+                  {preview.relocated > 0 && (
+                    <Badge variant="warning">{preview.relocated} em outro caminho</Badge>
+                  )}
+                  {preview.kept > 0 && (
+                    <Badge variant="default">{preview.kept} mantidos no destino</Badge>
+                  )}
+                  {preview.missing > 0 && (
+                    <Badge variant="danger">{preview.missing} ausentes</Badge>
+                  )}
+                  {preview.irreversible > 0 && (
+                    <Badge variant="danger">{preview.irreversible} não reversíveis</Badge>
+                  )}
+                  {preview.modified > 0 && (
+                    <Badge variant="warning">{preview.modified} modificados</Badge>
+                  )}
+                </div>
+                {(() => {
+                  const notable = preview.items.filter(
+                    (i) => (i.outcome !== 'restore' && i.outcome !== 'remove') || i.content_changed,
+                  );
+                  if (notable.length === 0) return null;
+                  return (
+                    <ul className="space-y-1">
+                      {notable.slice(0, MAX_CHAINS_SHOWN).map((item) => (
+                        <li key={item.run_item_id} className="text-xs text-gray-500 dark:text-gray-400">
+                          <span className="text-gray-700 dark:text-gray-300">
+                            {truncatePath(
+                              item.action_type === 'extract' ? item.current_path : item.original_path,
+                              40,
+                            )}
+                          </span>
+                          :{' '}
+                          {item.action_type === 'extract' && item.outcome === 'keep'
+                            ? 'arquivo extraído mantido no destino'
+                            : outcomeLabels[item.outcome]}
+                          {item.content_changed && ' (modificado após a execução)'}
+                        </li>
+                      ))}
+                      {notable.length > MAX_CHAINS_SHOWN && (
+                        <li className="text-xs text-gray-400 dark:text-gray-500">
+                          e mais {notable.length - MAX_CHAINS_SHOWN}
+                        </li>
+                      )}
+                    </ul>
+                  );
+                })()}
+              </div>
+            )}
+
+            {/* Later Runs */}
+            {dependencies && dependencies.chains.length > 0 && (
+              <div className="space-y-3 p-4 bg-gray-50 dark:bg-gray-800/50 rounded-lg">
+                <div className="flex items-center gap-2">
+                  <GitBranch size={14} className="text-gray-500 dark:text-gray-400" />
+                  <p className="text-sm font-medium text-gray-900 dark:text-gray-100">
+                    {dependencies.chains.length} arquivo{dependencies.chains.length !== 1 ? 's foram movidos' : ' foi movido'}{' '}
+                    de novo por execuções posteriores
+                  </p>
+                </div>
+
+                <ul className="space-y-1">
+                  {dependencies.dependent_runs.map((dep) => (
+                    <li key={dep.run_id} className="text-xs text-gray-600 dark:text-gray-400">
+                      {formatDate(dep.started_at)} · {truncatePath(dep.source_folder, 40)} ·{' '}
+                      {dep.items} ite{dep.items !== 1 ? 'ns' : 'm'}
+                    </li>
+                  ))}
+                </ul>
+
+                <ul className="space-y-1.5">
+                  {dependencies.chains.slice(0, MAX_CHAINS_SHOWN).map((chain) => (
+                    <li
+                      key={chain.run_item_id}
+                      className="flex items-center gap-1 flex-wrap text-xs text-gray-500 dark:text-gray-400"
+                    >
+                      <span>{truncatePath(chain.original_path, 30)}</span>
+                      {[chain.destination_path, ...chain.steps.map((step) => step.to)].map(
+                        (path, idx) => (
+                          <span key={idx} className="inline-flex items-center gap-1">
+                            <ArrowRight size={10} className="shrink-0" />
+                            {path ? truncatePath(path, 30) : 'excluído'}
+                          </span>
+                        ),
+                      )}
+                    </li>
+                  ))}
+                  {dependencies.chains.length > MAX_CHAINS_SHOWN && (
+                    <li className="text-xs text-gray-400 dark:text-gray-500">
+                      e mais {dependencies.chains.length - MAX_CHAINS_SHOWN}
+                    </li>
+                  )}
+                </ul>
+
+                <Select
+                  label="Como reverter esses arquivos"
+                  options={CHAIN_MODES}
+                  value={chainMode}
+                  onChange={(e) => setChainMode(e.target.value as ChainMode)}
+                />
+                {chainMode === 'latest' && dependencies.chains.some((c) => !c.direct) && (
+                  <p className="text-xs text-amber-600 dark:text-amber-400">
+                    Arquivos compactados, excluídos ou vinculados depois não podem ser restaurados
+                    direto; as execuções posteriores desses serão desfeitas primeiro.
+                  </p>
+                )}
+              </div>
+            )}
+
             {/* Note */}
             <p className="text-xs text-gray-500 dark:text-gray-400 leading-relaxed">
-              <strong>Nota:</strong> Arquivos modificados ou deletados externamente
-              podem não ser revertidos corretamente.
+              <strong>Nota:</strong> Arquivos existentes nos caminhos originais nunca são
+              substituídos; o que fazer nesse caso é definido em Configurações &gt; Conflitos.
+              Arquivos deletados externamente não podem ser revertidos.
             </p>
           </>
         )}
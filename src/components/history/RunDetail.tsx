@@ -14,18 +14,24 @@ import {
   Timer,
   FileText,
   Loader2,
+  RefreshCw,
+  MinusCircle,
+  PlayCircle,
 } from 'lucide-react';
 import { Card } from '@/components/ui/Card';
 import { Badge } from '@/components/ui/Badge';
 import { Button } from '@/components/ui/Button';
 import { useHistoryStore, useProfileStore } from '@/stores';
+import { useToast } from '@/components/ui/Toast';
 import {
   formatDate,
   formatFileSize,
   truncatePath,
 } from '@/lib/formatters';
+import { ERROR_CODE_LABELS } from '@/lib/constants';
 import { RollbackDialog } from './RollbackDialog';
-import type { Run, RunItem } from '@/types/runs';
+import { ExportReportButton } from './ExportReportButton';
+import type { RollbackResult, Run, RunItem } from '@/types/runs';
 
 /* ---------- Status Helpers ---------- */
 
@@ -37,6 +43,7 @@ const statusConfig: Record<
   completed: { label: 'Movido', variant: 'success', icon: CheckCircle2 },
   failed: { label: 'Falhou', variant: 'danger', icon: XCircle },
   rolled_back: { label: 'Revertido', variant: 'warning', icon: RotateCcw },
+  rollback_skipped: { label: 'Não revertido', variant: 'default', icon: SkipForward },
   skipped: { label: 'Ignorado', variant: 'default', icon: SkipForward },
 };
 
@@ -49,6 +56,10 @@ const runStatusConfig: Record<
   completed: { label: 'Concluído', variant: 'success', icon: CheckCircle2 },
   failed: { label: 'Falhou', variant: 'danger', icon: XCircle },
   rolled_back: { label: 'Revertido', variant: 'warning', icon: RotateCcw },
+  rollback_partial: { label: 'Parcialmente revertido', variant: 'warning', icon: RotateCcw },
+  interrupted: { label: 'Interrompido', variant: 'warning', icon: AlertTriangle },
+  cancelled: { label: 'Cancelado', variant: 'default', icon: XCircle },
+  idle: { label: 'Nada a fazer', variant: 'default', icon: MinusCircle },
 };
 
 const runTypeLabels: Record<Run['run_type'], string> = {
@@ -56,15 +67,17 @@ const runTypeLabels: Record<Run['run_type'], string> = {
   simulation: 'Simulação',
   watcher: 'Monitoramento',
   scheduled: 'Agendado',
+  retry: 'Repetição',
 };
 
 /* ---------- Duration Helper ---------- */
 
-function formatDuration(startedAt: string, completedAt: string | null): string {
-  if (!completedAt) return 'Em andamento';
-  const start = new Date(startedAt).getTime();
-  const end = new Date(completedAt).getTime();
-  const diffMs = end - start;
+function formatDuration(run: Run): string {
+  if (!run.completed_at) return 'Em andamento';
+  // Older runs have no recorded duration; fall back to the wall-clock time
+  const diffMs =
+    run.duration_ms ??
+    new Date(run.completed_at).getTime() - new Date(run.started_at).getTime();
 
   if (diffMs < 1000) return `${diffMs}ms`;
   if (diffMs < 60000) return `${(diffMs / 1000).toFixed(1)}s`;
@@ -78,19 +91,30 @@ function formatDuration(startedAt: string, completedAt: string | null): string {
 interface RunDetailProps {
   runId: string;
   onBack: () => void;
+  /** Opens another run, e.g. the retry of this one. */
+  onViewRun?: (id: string) => void;
 }
 
 /* ---------- Component ---------- */
 
-export function RunDetail({ runId, onBack }: RunDetailProps) {
+export function RunDetail({ runId, onBack, onViewRun }: RunDetailProps) {
   const fetchRunDetails = useHistoryStore((s) => s.fetchRunDetails);
   const selectedRun = useHistoryStore((s) => s.selectedRun);
   const runItems = useHistoryStore((s) => s.runItems);
   const isLoading = useHistoryStore((s) => s.isLoading);
   const error = useHistoryStore((s) => s.error);
   const profiles = useProfileStore((s) => s.profiles);
+  const rollbackItems = useHistoryStore((s) => s.rollbackItems);
+  const rollbackRule = useHistoryStore((s) => s.rollbackRule);
+  const retries = useHistoryStore((s) => s.retries);
+  const retryErrors = useHistoryStore((s) => s.retryErrors);
+  const resumeRun = useHistoryStore((s) => s.resumeRun);
+  const toast = useToast();
 
   const [showRollback, setShowRollback] = useState(false);
+  const [undoingId, setUndoingId] = useState<string | null>(null);
+  const [retrying, setRetrying] = useState(false);
+  const [resuming, setResuming] = useState(false);
 
   useEffect(() => {
     fetchRunDetails(runId);
@@ -102,6 +126,63 @@ export function RunDetail({ runId, onBack }: RunDetailProps) {
     fetchRunDetails(runId);
   }, [runId, fetchRunDetails]);
 
+  const handleUndo = useCallback(
+    async (item: RunItem, wholeRule: boolean) => {
+      setUndoingId(item.id);
+      let summary: RollbackResult | null;
+      if (wholeRule && item.rule_id) {
+        summary = await rollbackRule(runId, item.rule_id);
+      } else {
+        summary = await rollbackItems(runId, [item.id]);
+      }
+      setUndoingId(null);
+
+      if (!summary) {
+        toast.error(`Erro no rollback: ${useHistoryStore.getState().error ?? ''}`);
+        return;
+      }
+      const s = summary.rolled_back !== 1 ? 's' : '';
+      if (summary.errors > 0) {
+        toast.info(`${summary.rolled_back} revertido${s}, ${summary.errors} com erro.`);
+      } else {
+        toast.success(`${summary.rolled_back} arquivo${s} revertido${s}.`);
+      }
+    },
+    [runId, rollbackItems, rollbackRule, toast],
+  );
+
+  const handleRetry = useCallback(async () => {
+    setRetrying(true);
+    const result = await retryErrors(runId);
+    setRetrying(false);
+
+    if (!result) {
+      toast.error(`Erro ao repetir: ${useHistoryStore.getState().error ?? ''}`);
+      return;
+    }
+    if (result.errors > 0) {
+      toast.info(`${result.moved} organizado(s), ${result.errors} ainda com erro.`);
+    } else {
+      toast.success(`${result.moved} arquivo(s) organizado(s) na nova tentativa.`);
+    }
+  }, [runId, retryErrors, toast]);
+
+  const handleResume = useCallback(async () => {
+    setResuming(true);
+    const result = await resumeRun(runId);
+    setResuming(false);
+
+    if (!result) {
+      toast.error(`Erro ao retomar: ${useHistoryStore.getState().error ?? ''}`);
+      return;
+    }
+    if (result.errors > 0) {
+      toast.info(`Execução retomada: ${result.moved} organizado(s), ${result.errors} com erro.`);
+    } else {
+      toast.success(`Execução retomada: ${result.moved} arquivo(s) organizado(s).`);
+    }
+  }, [runId, resumeRun, toast]);
+
   /* --- Loading --- */
   if (isLoading && !selectedRun) {
     return (
@@ -133,7 +214,11 @@ export function RunDetail({ runId, onBack }: RunDetailProps) {
   const RunStatusIcon = runStatus.icon;
   const profileName =
     profiles.find((p) => p.id === run.profile_id)?.name || 'Desconhecido';
-  const canRollback = run.status === 'completed' && run.moved_files > 0;
+  const isUndoable =
+    (run.status === 'completed' || run.status === 'rollback_partial') && !run.pruned_at;
+  const canRollback = isUndoable && run.moved_files > 0;
+  const canRetry = run.error_files > 0 && run.status !== 'running' && !run.pruned_at;
+  const canResume = run.status === 'interrupted' && !run.pruned_at;
 
   return (
     <div className="space-y-6">
@@ -172,7 +257,7 @@ export function RunDetail({ runId, onBack }: RunDetailProps) {
                     flex items-center justify-center w-10 h-10 rounded-lg
                     ${run.status === 'completed' ? 'bg-emerald-50 dark:bg-emerald-900/30' : ''}
                     ${run.status === 'failed' ? 'bg-red-50 dark:bg-red-900/30' : ''}
-                    ${run.status === 'rolled_back' ? 'bg-amber-50 dark:bg-amber-900/30' : ''}
+                    ${run.status === 'rolled_back' || run.status === 'rollback_partial' ? 'bg-amber-50 dark:bg-amber-900/30' : ''}
                     ${run.status === 'pending' || run.status === 'running' ? 'bg-gray-100 dark:bg-gray-800' : ''}
                   `}
                 >
@@ -181,7 +266,7 @@ export function RunDetail({ runId, onBack }: RunDetailProps) {
                     className={`
                       ${run.status === 'completed' ? 'text-emerald-600 dark:text-emerald-400' : ''}
                       ${run.status === 'failed' ? 'text-red-600 dark:text-red-400' : ''}
-                      ${run.status === 'rolled_back' ? 'text-amber-600 dark:text-amber-400' : ''}
+                      ${run.status === 'rolled_back' || run.status === 'rollback_partial' ? 'text-amber-600 dark:text-amber-400' : ''}
                       ${run.status === 'pending' || run.status === 'running' ? 'text-gray-500 dark:text-gray-400' : ''}
                       ${run.status === 'running' ? 'animate-spin' : ''}
                     `}
@@ -198,17 +283,45 @@ export function RunDetail({ runId, onBack }: RunDetailProps) {
                 </div>
               </div>
 
-              {/* Rollback Button */}
-              {canRollback && (
-                <Button
-                  variant="danger"
-                  size="sm"
-                  icon={RotateCcw}
-                  onClick={() => setShowRollback(true)}
-                >
-                  Desfazer esta execução
-                </Button>
-              )}
+              <div className="flex items-center gap-2 flex-wrap">
+                <ExportReportButton command="export_run" args={{ runId: run.id }} />
+
+                {/* Rollback Button */}
+                {canRollback && (
+                  <Button
+                    variant="danger"
+                    size="sm"
+                    icon={RotateCcw}
+                    onClick={() => setShowRollback(true)}
+                  >
+                    Desfazer esta execução
+                  </Button>
+                )}
+
+                {canResume && (
+                  <Button
+                    variant="primary"
+                    size="sm"
+                    icon={PlayCircle}
+                    loading={resuming}
+                    onClick={handleResume}
+                  >
+                    Retomar execução
+                  </Button>
+                )}
+
+                {canRetry && (
+                  <Button
+                    variant="secondary"
+                    size="sm"
+                    icon={RefreshCw}
+                    loading={retrying}
+                    onClick={handleRetry}
+                  >
+                    Repetir itens com erro
+                  </Button>
+                )}
+              </div>
             </div>
 
             {/* Details Grid */}
@@ -284,7 +397,7 @@ export function RunDetail({ runId, onBack }: RunDetailProps) {
                     Duração
                   </p>
                   <p className="text-sm text-gray-900 dark:text-gray-100">
-                    {formatDuration(run.started_at, run.completed_at)}
+                    {formatDuration(run)}
                   </p>
                 </div>
               </div>
@@ -295,6 +408,16 @@ export function RunDetail({ runId, onBack }: RunDetailProps) {
               <span className="text-xs font-medium text-gray-500 dark:text-gray-400">
                 Arquivos:
               </span>
+              {run.scanned_files > 0 && (
+                <Badge variant="info">
+                  {run.scanned_files} analisado{run.scanned_files !== 1 ? 's' : ''}
+                </Badge>
+              )}
+              {run.unmatched_files > 0 && (
+                <Badge variant="default">
+                  {run.unmatched_files} sem regra
+                </Badge>
+              )}
               <Badge variant="default">{run.total_files} total</Badge>
               <Badge variant="success">
                 {run.moved_files} movido{run.moved_files !== 1 ? 's' : ''}
@@ -309,8 +432,43 @@ export function RunDetail({ runId, onBack }: RunDetailProps) {
                   {run.error_files} erro{run.error_files !== 1 ? 's' : ''}
                 </Badge>
               )}
+              {run.total_bytes > 0 && (
+                <span className="text-xs text-gray-500 dark:text-gray-400">
+                  {formatFileSize(run.total_bytes)}
+                </span>
+              )}
             </div>
 
+            {/* Retry links */}
+            {(run.parent_run_id || retries.length > 0) && (
+              <div className="flex flex-col gap-1 text-xs text-gray-600 dark:text-gray-400">
+                {run.parent_run_id && (
+                  <button
+                    type="button"
+                    onClick={() => onViewRun?.(run.parent_run_id!)}
+                    className="flex items-center gap-1.5 text-left hover:text-brand-600 dark:hover:text-brand-400"
+                  >
+                    <RefreshCw size={12} className="shrink-0" />
+                    Repete os itens com erro de outra execução
+                    <ArrowRight size={12} className="shrink-0" />
+                  </button>
+                )}
+                {retries.map((retry) => (
+                  <button
+                    key={retry.id}
+                    type="button"
+                    onClick={() => onViewRun?.(retry.id)}
+                    className="flex items-center gap-1.5 text-left hover:text-brand-600 dark:hover:text-brand-400"
+                  >
+                    <RefreshCw size={12} className="shrink-0" />
+                    Repetida em {formatDate(retry.started_at)}: {retry.moved_files} organizado
+                    {retry.moved_files !== 1 ? 's' : ''}, {retry.error_files} com erro
+                    <ArrowRight size={12} className="shrink-0" />
+                  </button>
+                ))}
+              </div>
+            )}
+
             {/* Error Message */}
             {run.error_message && (
               <div className="p-3 bg-red-50 dark:bg-red-900/20 rounded-lg">
@@ -346,6 +504,9 @@ export function RunDetail({ runId, onBack }: RunDetailProps) {
                 {runItems.map((item, idx) => {
                   const config = statusConfig[item.status];
                   const StatusIcon = config.icon;
+                  const canUndo =
+                    isUndoable &&
+                    (item.status === 'completed' || item.status === 'rollback_skipped');
 
                   return (
                     <motion.div
@@ -394,13 +555,57 @@ export function RunDetail({ runId, onBack }: RunDetailProps) {
                             <span className="text-xs text-gray-400 dark:text-gray-500">
                               {formatFileSize(item.file_size)}
                             </span>
+                            {item.error_code && (
+                              <Badge variant="danger" size="sm">
+                                {ERROR_CODE_LABELS[item.error_code]}
+                              </Badge>
+                            )}
                             {item.error_message && (
-                              <span className="text-xs text-red-500 dark:text-red-400">
+                              <span
+                                className="text-xs text-red-500 dark:text-red-400"
+                                title={item.failed_at ? formatDate(item.failed_at) : undefined}
+                              >
                                 {item.error_message}
                               </span>
                             )}
+                            {item.restored_path && (
+                              <span className="text-xs text-amber-600 dark:text-amber-400 truncate">
+                                Restaurado em {truncatePath(item.restored_path, 45)}
+                              </span>
+                            )}
+                            {item.dest_modified && (
+                              <span className="text-xs text-amber-600 dark:text-amber-400">
+                                Modificado após a execução
+                              </span>
+                            )}
                           </div>
                         </div>
+
+                        {/* Partial Rollback */}
+                        {canUndo && (
+                          <div className="flex items-center gap-1 shrink-0">
+                            <Button
+                              variant="ghost"
+                              size="sm"
+                              icon={RotateCcw}
+                              loading={undoingId === item.id}
+                              disabled={undoingId !== null}
+                              onClick={() => handleUndo(item, false)}
+                            >
+                              Desfazer
+                            </Button>
+                            {item.rule_id && (
+                              <Button
+                                variant="ghost"
+                                size="sm"
+                                disabled={undoingId !== null}
+                                onClick={() => handleUndo(item, true)}
+                              >
+                                Desfazer regra
+                              </Button>
+                            )}
+                          </div>
+                        )}
                       </div>
                     </motion.div>
                   );
@@ -410,7 +615,9 @@ export function RunDetail({ runId, onBack }: RunDetailProps) {
           ) : (
             <div className="py-8 text-center">
               <p className="text-sm text-gray-500 dark:text-gray-400">
-                Nenhum item encontrado para esta execução.
+                {run.pruned_at
+                  ? `Os itens desta execução foram removidos pela retenção do histórico em ${formatDate(run.pruned_at)} e ela não pode mais ser desfeita.`
+                  : 'Nenhum item encontrado para esta execução.'}
               </p>
             </div>
           )}
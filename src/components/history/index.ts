@@ -1,3 +1,5 @@
 export { HistoryView } from './HistoryView';
 export { RunDetail } from './RunDetail';
 export { RollbackDialog } from './RollbackDialog';
+export { ExportReportButton } from './ExportReportButton';
+export { FileLocator } from './FileLocator';
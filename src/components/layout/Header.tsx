@@ -73,6 +73,10 @@ function getRunStatusLabel(run: Run): string {
       return 'Em andamento...';
     case 'rolled_back':
       return 'Revertido';
+    case 'rollback_partial':
+      return 'Parcialmente revertido';
+    case 'idle':
+      return 'Nada a fazer';
     default:
       return run.status;
   }
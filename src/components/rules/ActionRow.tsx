@@ -3,8 +3,8 @@ import { X, FolderOpen } from 'lucide-react';
 import { Select } from '@/components/ui/Select';
 import { Input } from '@/components/ui/Input';
 import { Badge } from '@/components/ui/Badge';
-import { ACTION_TYPES } from '@/lib/constants';
-import type { ActionType } from '@/types/rules';
+import { ACTION_TYPES, ACTION_CONFLICT_STRATEGIES } from '@/lib/constants';
+import type { ActionType, ActionConflictStrategy } from '@/types/rules';
 
 /* ---------- Portuguese Labels ---------- */
 
@@ -12,6 +12,10 @@ const ACTION_LABELS: Record<string, string> = {
   move_to_folder: 'Mover para pasta',
   move_to_subfolder: 'Mover para subpasta',
   rename: 'Renomear',
+  extract: 'Extrair compactado',
+  archive: 'Compactar em arquivo',
+  move_and_link: 'Mover e deixar atalho',
+  link: 'Criar atalho',
   add_tag: 'Adicionar tag',
 };
 
@@ -42,8 +46,18 @@ export interface ActionRowData {
   destination: string;
   rename_pattern: string;
   tag_name: string;
+  conflict_strategy: ActionConflictStrategy;
 }
 
+/** Actions that place a file at a path and can therefore hit a conflict. */
+const CONFLICT_ACTIONS: ActionType[] = [
+  'move_to_folder',
+  'move_to_subfolder',
+  'rename',
+  'move_and_link',
+  'link',
+];
+
 interface ActionRowProps {
   action: ActionRowData;
   index: number;
@@ -123,7 +137,9 @@ export function ActionRow({ action, index, onChange, onDelete }: ActionRowProps)
         </div>
 
         {/* Conditional fields based on action type */}
-        {action.action_type === 'move_to_folder' && (
+        {(action.action_type === 'move_to_folder' ||
+          action.action_type === 'move_and_link' ||
+          action.action_type === 'link') && (
           <div className="flex items-end gap-2">
             <div className="flex-1 min-w-0">
               <Input
@@ -223,6 +239,22 @@ export function ActionRow({ action, index, onChange, onDelete }: ActionRowProps)
           </div>
         )}
 
+        {CONFLICT_ACTIONS.includes(action.action_type) && (
+          <div className="flex-1 min-w-0 max-w-xs">
+            <Select
+              label="Se o arquivo já existir"
+              options={ACTION_CONFLICT_STRATEGIES}
+              value={action.conflict_strategy}
+              onChange={(e) =>
+                onChange(action.id, {
+                  conflict_strategy: e.target.value as ActionConflictStrategy,
+                })
+              }
+              className="text-sm"
+            />
+          </div>
+        )}
+
         {action.action_type === 'add_tag' && (
           <div className="flex-1 min-w-0 max-w-xs">
             <Input
@@ -16,6 +16,7 @@ import { VIEWS } from '@/lib/constants';
 import { ConditionRow, type ConditionRowData } from './ConditionRow';
 import { ActionRow, type ActionRowData } from './ActionRow';
 import { RulePreview } from './RulePreview';
+import { RuleTester } from './RuleTester';
 import type { ConditionField, ConditionOperator, ActionType } from '@/types/rules';
 
 /* ---------- Helpers ---------- */
@@ -42,6 +43,7 @@ function createEmptyAction(): ActionRowData {
     destination: '',
     rename_pattern: '',
     tag_name: '',
+    conflict_strategy: '',
   };
 }
 
@@ -117,6 +119,7 @@ export function RuleBuilder({ ruleId }: RuleBuilderProps) {
             destination: a.destination || '',
             rename_pattern: a.rename_pattern || '',
             tag_name: a.tag_name || '',
+            conflict_strategy: a.conflict_strategy || '',
           })),
         );
       }
@@ -268,6 +271,7 @@ export function RuleBuilder({ ruleId }: RuleBuilderProps) {
           destination,
           renamePattern,
           tagName,
+          a.conflict_strategy,
         );
       }
 
@@ -488,6 +492,9 @@ export function RuleBuilder({ ruleId }: RuleBuilderProps) {
       >
         <RulePreview conditions={conditions} actions={actions} />
       </motion.div>
+
+      {/* Tester */}
+      {ruleId && <RuleTester ruleId={ruleId} />}
     </div>
   );
 }
@@ -0,0 +1,144 @@
+import { useState, useCallback } from 'react';
+import { ArrowRight, FlaskConical } from 'lucide-react';
+import { Card } from '@/components/ui/Card';
+import { Badge } from '@/components/ui/Badge';
+import { Button } from '@/components/ui/Button';
+import { Input } from '@/components/ui/Input';
+import { useToast } from '@/components/ui/Toast';
+import { tauriInvoke } from '@/lib/tauri';
+import { ACTION_TYPES } from '@/lib/constants';
+import { truncatePath } from '@/lib/formatters';
+import type { RuleTestResult } from '@/types/rules';
+
+function actionLabel(actionType: string): string {
+  return ACTION_TYPES.find((a) => a.value === actionType)?.label || actionType;
+}
+
+interface RuleTesterProps {
+  ruleId: string;
+}
+
+/**
+ * Runs the saved version of a rule against one file path and shows the regex
+ * captures and the destinations its templates expand to.
+ */
+export function RuleTester({ ruleId }: RuleTesterProps) {
+  const toast = useToast();
+  const [filePath, setFilePath] = useState('');
+  const [result, setResult] = useState<RuleTestResult | null>(null);
+  const [testing, setTesting] = useState(false);
+
+  const handleTest = useCallback(async () => {
+    if (!filePath.trim()) return;
+    setTesting(true);
+    try {
+      setResult(
+        await tauriInvoke<RuleTestResult>('test_rule', { ruleId, filePath: filePath.trim() }),
+      );
+    } catch (err) {
+      const message = err instanceof Error ? err.message : String(err);
+      toast.error(`Erro ao testar a regra: ${message}`);
+    } finally {
+      setTesting(false);
+    }
+  }, [ruleId, filePath, toast]);
+
+  const captures = result ? Object.entries(result.captures) : [];
+
+  return (
+    <Card padding="md">
+      <div className="flex items-center gap-2.5 mb-3">
+        <FlaskConical size={16} className="text-gray-500 dark:text-gray-400" />
+        <div>
+          <h2 className="text-sm font-semibold text-gray-900 dark:text-gray-100">
+            Testar regra
+          </h2>
+          <p className="text-xs text-gray-500 dark:text-gray-400">
+            Usa a versão salva da regra. Caminhos inexistentes são simulados pelo nome.
+          </p>
+        </div>
+      </div>
+
+      <form
+        className="flex items-center gap-2"
+        onSubmit={(e) => {
+          e.preventDefault();
+          handleTest();
+        }}
+      >
+        <div className="flex-1">
+          <Input
+            aria-label="Caminho do arquivo"
+            placeholder="Ex: C:\Users\voce\Downloads\fatura_2024-05.pdf"
+            value={filePath}
+            onChange={(e) => setFilePath(e.target.value)}
+          />
+        </div>
+        <Button type="submit" variant="secondary" size="sm" loading={testing}>
+          Testar
+        </Button>
+      </form>
+
+      {result && (
+        <div className="mt-4 space-y-3">
+          <div className="flex items-center gap-2">
+            <Badge variant={result.matched ? 'success' : 'default'} size="sm">
+              {result.matched ? 'Corresponde' : 'Não corresponde'}
+            </Badge>
+            <span className="text-sm text-gray-600 dark:text-gray-400 truncate">
+              {result.file.name}
+            </span>
+          </div>
+
+          {captures.length > 0 && (
+            <div>
+              <p className="text-xs font-medium text-gray-500 dark:text-gray-400 mb-1">
+                Capturas
+              </p>
+              <div className="flex flex-wrap gap-1.5">
+                {captures.map(([name, value]) => (
+                  <code
+                    key={name}
+                    className="text-xs px-1.5 py-0.5 rounded bg-gray-100 dark:bg-gray-800 text-gray-700 dark:text-gray-300"
+                  >
+                    {`{${name}}`} = {value}
+                  </code>
+                ))}
+              </div>
+            </div>
+          )}
+
+          {result.items.length > 0 && (
+            <div className="divide-y divide-gray-100 dark:divide-gray-800">
+              {result.items.map((item, i) => (
+                <div key={`${item.action_type}-${i}`} className="py-2 text-sm">
+                  <div className="flex items-center gap-2">
+                    <Badge variant="info" size="sm">
+                      {actionLabel(item.action_type)}
+                    </Badge>
+                    {item.has_conflict && (
+                      <Badge variant="warning" size="sm">
+                        Conflito
+                      </Badge>
+                    )}
+                  </div>
+                  <div className="mt-1 flex items-center gap-1.5 text-gray-600 dark:text-gray-400">
+                    <ArrowRight size={14} className="shrink-0" />
+                    <span className="truncate" title={item.final_path || item.destination}>
+                      {truncatePath(item.final_path || item.destination, 80)}
+                    </span>
+                  </div>
+                  {(item.error || item.skip_reason) && (
+                    <p className="mt-1 text-xs text-red-600 dark:text-red-400">
+                      {item.error || item.skip_reason}
+                    </p>
+                  )}
+                </div>
+              ))}
+            </div>
+          )}
+        </div>
+      )}
+    </Card>
+  );
+}
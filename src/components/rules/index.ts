@@ -3,3 +3,4 @@ export { RuleBuilder } from './RuleBuilder';
 export { ConditionRow } from './ConditionRow';
 export { ActionRow } from './ActionRow';
 export { RulePreview } from './RulePreview';
+export { RuleTester } from './RuleTester';
@@ -8,6 +8,9 @@ import {
   Check,
 } from 'lucide-react';
 import { Card } from '@/components/ui/Card';
+import { Switch } from '@/components/ui/Switch';
+import { Select } from '@/components/ui/Select';
+import { ROLLBACK_CONFLICT_STRATEGIES } from '@/lib/constants';
 import { useSettingsStore } from '@/stores/settingsStore';
 import type { AppSettings } from '@/types/settings';
 
@@ -78,9 +81,13 @@ const STRATEGY_OPTIONS: StrategyOption[] = [
  * - Visual explanation for each strategy
  * - Example preview (before/after) for each option
  * - Recommended indicator on suffix strategy
+ * - Toggle to verify copies between drives before deleting the original
+ * - What rollback does when a file's original path is taken again
  */
 export function ConflictSettings() {
   const conflictStrategy = useSettingsStore((s) => s.settings.conflict_strategy);
+  const verifyMoves = useSettingsStore((s) => s.settings.verify_moves);
+  const rollbackStrategy = useSettingsStore((s) => s.settings.rollback_conflict_strategy);
   const updateSetting = useSettingsStore((s) => s.updateSetting);
 
   const handleStrategyChange = useCallback(
@@ -90,6 +97,23 @@ export function ConflictSettings() {
     [updateSetting],
   );
 
+  const handleVerifyMovesChange = useCallback(
+    (checked: boolean) => {
+      updateSetting('verify_moves', checked);
+    },
+    [updateSetting],
+  );
+
+  const handleRollbackStrategyChange = useCallback(
+    (e: React.ChangeEvent<HTMLSelectElement>) => {
+      updateSetting(
+        'rollback_conflict_strategy',
+        e.target.value as AppSettings['rollback_conflict_strategy'],
+      );
+    },
+    [updateSetting],
+  );
+
   return (
     <motion.div
       initial={{ opacity: 0, y: 8 }}
@@ -247,6 +271,30 @@ export function ConflictSettings() {
               );
             })}
           </div>
+
+          {/* Divider */}
+          <div className="border-t border-gray-100 dark:border-gray-800" />
+
+          {/* Copy verification */}
+          <Switch
+            checked={verifyMoves}
+            onChange={handleVerifyMovesChange}
+            label="Verificar cópias entre discos"
+            description="Confere tamanho e SHA-256 antes de apagar o original ao mover para outro disco. Mais seguro, porém mais lento"
+          />
+
+          {/* Rollback conflicts */}
+          <div>
+            <Select
+              label="Ao desfazer, se já existir um arquivo no caminho original"
+              options={ROLLBACK_CONFLICT_STRATEGIES}
+              value={rollbackStrategy}
+              onChange={handleRollbackStrategyChange}
+            />
+            <p className="text-xs text-gray-400 dark:text-gray-500 mt-1">
+              O arquivo existente nunca é substituído
+            </p>
+          </div>
         </div>
       </Card>
     </motion.div>
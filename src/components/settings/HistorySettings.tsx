@@ -0,0 +1,214 @@
+import { useCallback, useEffect, useState } from 'react';
+import { motion } from 'framer-motion';
+import { History, Eraser, Minimize2 } from 'lucide-react';
+import { Card } from '@/components/ui/Card';
+import { Input } from '@/components/ui/Input';
+import { Button } from '@/components/ui/Button';
+import { useToast } from '@/components/ui/Toast';
+import { tauriInvoke } from '@/lib/tauri';
+import { formatFileSize } from '@/lib/formatters';
+import { useSettingsStore } from '@/stores/settingsStore';
+import type { AppSettings } from '@/types/settings';
+import type { DatabaseSize, PruneResult } from '@/types/runs';
+
+type RetentionKey =
+  | 'history_retention_days'
+  | 'history_retention_runs'
+  | 'history_keep_undoable_days';
+
+/**
+ * HistorySettings — How much run history DeskCraft keeps.
+ *
+ * Provides:
+ * - Retention by age and by number of runs (0 keeps everything)
+ * - Minimum days a run that can still be undone is kept
+ * - Database size report, with buttons to prune now and to compact the file
+ *
+ * Pruning also runs once a day in the background.
+ */
+export function HistorySettings() {
+  const settings = useSettingsStore((s) => s.settings);
+  const updateSetting = useSettingsStore((s) => s.updateSetting);
+  const toast = useToast();
+  const [size, setSize] = useState<DatabaseSize | null>(null);
+  const [pruning, setPruning] = useState(false);
+  const [vacuuming, setVacuuming] = useState(false);
+
+  const fetchSize = useCallback(async () => {
+    try {
+      setSize(await tauriInvoke<DatabaseSize>('get_database_size'));
+    } catch {
+      setSize(null);
+    }
+  }, []);
+
+  useEffect(() => {
+    fetchSize();
+  }, [fetchSize]);
+
+  const handleNumberChange = useCallback(
+    (key: RetentionKey) => (e: React.ChangeEvent<HTMLInputElement>) => {
+      const value = Math.max(0, Math.floor(Number(e.target.value) || 0));
+      updateSetting(key, value as AppSettings[RetentionKey]);
+    },
+    [updateSetting],
+  );
+
+  const handlePrune = useCallback(async () => {
+    setPruning(true);
+    try {
+      const result = await tauriInvoke<PruneResult>('prune_history');
+      toast.info(
+        result.runs_pruned > 0
+          ? `${result.runs_pruned} execução(ões) limpa(s), ${result.items_removed} item(ns) removido(s)`
+          : 'Nada a limpar com as configurações atuais',
+      );
+      await fetchSize();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : String(err);
+      toast.error(`Erro ao limpar o histórico: ${message}`);
+    } finally {
+      setPruning(false);
+    }
+  }, [toast, fetchSize]);
+
+  const handleVacuum = useCallback(async () => {
+    setVacuuming(true);
+    try {
+      const before = size?.total_bytes ?? 0;
+      const after = await tauriInvoke<DatabaseSize>('vacuum_database');
+      setSize(after);
+      toast.success(
+        `Banco de dados compactado: ${formatFileSize(Math.max(0, before - after.total_bytes))} liberados`,
+      );
+    } catch (err) {
+      const message = err instanceof Error ? err.message : String(err);
+      toast.error(`Erro ao compactar o banco de dados: ${message}`);
+    } finally {
+      setVacuuming(false);
+    }
+  }, [size, toast]);
+
+  return (
+    <motion.div
+      initial={{ opacity: 0, y: 8 }}
+      animate={{ opacity: 1, y: 0 }}
+      transition={{ duration: 0.2 }}
+    >
+      <Card padding="lg">
+        {/* Section header */}
+        <div className="flex items-center gap-3 mb-6">
+          <div
+            className="
+              flex items-center justify-center
+              w-9 h-9 rounded-lg
+              bg-indigo-50 dark:bg-indigo-500/10
+              ring-1 ring-indigo-200/50 dark:ring-indigo-500/20
+            "
+          >
+            <History size={18} className="text-indigo-500 dark:text-indigo-400" />
+          </div>
+          <div>
+            <h3 className="text-sm font-semibold text-gray-900 dark:text-gray-100">
+              Histórico
+            </h3>
+            <p className="text-xs text-gray-500 dark:text-gray-400 mt-0.5">
+              Quanto do histórico de execuções o DeskCraft guarda
+            </p>
+          </div>
+        </div>
+
+        <div className="space-y-5">
+          <div className="grid grid-cols-2 gap-4">
+            <Input
+              label="Manter execuções por (dias)"
+              type="number"
+              min={0}
+              value={settings.history_retention_days}
+              onChange={handleNumberChange('history_retention_days')}
+            />
+            <Input
+              label="Manter as últimas execuções"
+              type="number"
+              min={0}
+              value={settings.history_retention_runs}
+              onChange={handleNumberChange('history_retention_runs')}
+            />
+          </div>
+          <p className="text-xs text-gray-400 dark:text-gray-500 -mt-3">
+            Use 0 para não limitar. Execuções limpas continuam no histórico, mas
+            sem a lista de arquivos, e não podem mais ser desfeitas
+          </p>
+
+          <div>
+            <Input
+              label="Sempre manter execuções que ainda podem ser desfeitas por (dias)"
+              type="number"
+              min={0}
+              value={settings.history_keep_undoable_days}
+              onChange={handleNumberChange('history_keep_undoable_days')}
+            />
+          </div>
+
+          {/* Divider */}
+          <div className="border-t border-gray-100 dark:border-gray-800" />
+
+          {/* Database size */}
+          <div
+            className="
+              px-4 py-3 rounded-lg
+              bg-gray-50 dark:bg-gray-800/50
+              border border-gray-100 dark:border-gray-800
+            "
+          >
+            {size ? (
+              <div className="grid grid-cols-2 gap-2 text-xs text-gray-600 dark:text-gray-400">
+                <span>Tamanho do banco de dados</span>
+                <span className="text-right font-medium text-gray-900 dark:text-gray-100">
+                  {formatFileSize(size.total_bytes)}
+                </span>
+                <span>Espaço recuperável ao compactar</span>
+                <span className="text-right font-medium text-gray-900 dark:text-gray-100">
+                  {formatFileSize(size.free_bytes)}
+                </span>
+                <span>Execuções (limpas)</span>
+                <span className="text-right font-medium text-gray-900 dark:text-gray-100">
+                  {size.runs} ({size.pruned_runs})
+                </span>
+                <span>Itens no histórico</span>
+                <span className="text-right font-medium text-gray-900 dark:text-gray-100">
+                  {size.run_items}
+                </span>
+              </div>
+            ) : (
+              <p className="text-xs text-gray-500 dark:text-gray-400">
+                Não foi possível obter o tamanho do banco de dados.
+              </p>
+            )}
+          </div>
+
+          <div className="flex items-center gap-2">
+            <Button
+              variant="secondary"
+              size="sm"
+              icon={Eraser}
+              loading={pruning}
+              onClick={handlePrune}
+            >
+              Limpar agora
+            </Button>
+            <Button
+              variant="secondary"
+              size="sm"
+              icon={Minimize2}
+              loading={vacuuming}
+              onClick={handleVacuum}
+            >
+              Compactar banco de dados
+            </Button>
+          </div>
+        </div>
+      </Card>
+    </motion.div>
+  );
+}
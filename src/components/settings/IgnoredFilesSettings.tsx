@@ -0,0 +1,160 @@
+import { useCallback, useEffect, useState } from 'react';
+import { motion, AnimatePresence } from 'framer-motion';
+import { EyeOff, FileX, Loader2, Undo2 } from 'lucide-react';
+import { Card } from '@/components/ui/Card';
+import { Button } from '@/components/ui/Button';
+import { useToast } from '@/components/ui/Toast';
+import { tauriInvoke } from '@/lib/tauri';
+import { formatDate } from '@/lib/formatters';
+import type { IgnoredFile } from '@/types/runs';
+
+/**
+ * IgnoredFilesSettings -- Files excluded for good from a simulation.
+ *
+ * Lists them grouped by the folder they were ignored in, with an action to
+ * let simulations pick each one up again.
+ */
+export function IgnoredFilesSettings() {
+  const toast = useToast();
+  const [files, setFiles] = useState<IgnoredFile[]>([]);
+  const [isLoading, setIsLoading] = useState(true);
+  const [removingId, setRemovingId] = useState<string | null>(null);
+
+  useEffect(() => {
+    async function load() {
+      setIsLoading(true);
+      try {
+        setFiles(await tauriInvoke<IgnoredFile[]>('list_ignored_files', { folder: null }));
+      } catch (err) {
+        const msg = err instanceof Error ? err.message : String(err);
+        console.warn('Erro ao carregar arquivos ignorados:', msg);
+      } finally {
+        setIsLoading(false);
+      }
+    }
+    load();
+  }, []);
+
+  const handleUnignore = useCallback(
+    async (id: string) => {
+      setRemovingId(id);
+      try {
+        await tauriInvoke('unignore_file', { id });
+        setFiles((prev) => prev.filter((f) => f.id !== id));
+        toast.success('O arquivo voltará a aparecer nas simulações.');
+      } catch (err) {
+        const msg = err instanceof Error ? err.message : String(err);
+        toast.error(`Erro ao deixar de ignorar o arquivo: ${msg}`);
+      } finally {
+        setRemovingId(null);
+      }
+    },
+    [toast],
+  );
+
+  const folders = Array.from(new Set(files.map((f) => f.folder)));
+
+  return (
+    <motion.div
+      initial={{ opacity: 0, y: 8 }}
+      animate={{ opacity: 1, y: 0 }}
+      transition={{ duration: 0.2 }}
+    >
+      <Card padding="lg">
+        {/* Section header */}
+        <div className="flex items-center gap-3 mb-6">
+          <div
+            className="
+              flex items-center justify-center
+              w-9 h-9 rounded-lg
+              bg-gray-100 dark:bg-gray-800
+              ring-1 ring-gray-200/50 dark:ring-gray-700
+            "
+          >
+            <EyeOff size={18} className="text-gray-500 dark:text-gray-400" />
+          </div>
+          <div>
+            <h3 className="text-sm font-semibold text-gray-900 dark:text-gray-100">
+              Arquivos ignorados
+            </h3>
+            <p className="text-xs text-gray-500 dark:text-gray-400 mt-0.5">
+              Arquivos que as simulações deixam de fora
+            </p>
+          </div>
+        </div>
+
+        {isLoading && (
+          <div className="flex items-center justify-center py-6">
+            <Loader2 size={20} className="animate-spin text-gray-400 dark:text-gray-500" />
+            <span className="ml-2 text-sm text-gray-500 dark:text-gray-400">
+              Carregando arquivos...
+            </span>
+          </div>
+        )}
+
+        {!isLoading && files.length === 0 && (
+          <p className="text-sm text-gray-500 dark:text-gray-400 text-center py-4">
+            Nenhum arquivo ignorado. Use "Excluir este arquivo para sempre" na
+            simulação para deixar um arquivo de fora.
+          </p>
+        )}
+
+        {!isLoading && files.length > 0 && (
+          <div className="space-y-4">
+            {folders.map((folder) => (
+              <div key={folder}>
+                <p className="text-xs font-medium text-gray-500 dark:text-gray-400 mb-2 truncate font-mono">
+                  {folder}
+                </p>
+                <div className="space-y-2">
+                  <AnimatePresence mode="popLayout">
+                    {files
+                      .filter((f) => f.folder === folder)
+                      .map((file) => (
+                        <motion.div
+                          key={file.id}
+                          layout
+                          initial={{ opacity: 0, height: 0 }}
+                          animate={{ opacity: 1, height: 'auto' }}
+                          exit={{ opacity: 0, height: 0 }}
+                          transition={{ duration: 0.2 }}
+                          className="flex items-center gap-3 px-4 py-3 rounded-lg
+                            bg-gray-50 dark:bg-gray-800/50
+                            border border-gray-100 dark:border-gray-800"
+                        >
+                          <FileX
+                            size={16}
+                            className="text-gray-400 dark:text-gray-500 shrink-0"
+                          />
+                          <div className="flex-1 min-w-0">
+                            <p
+                              className="text-sm text-gray-900 dark:text-gray-100 truncate font-mono"
+                              title={file.path}
+                            >
+                              {file.path}
+                            </p>
+                            <p className="text-xs text-gray-400 dark:text-gray-500">
+                              Ignorado em {formatDate(file.created_at)}
+                            </p>
+                          </div>
+                          <Button
+                            variant="ghost"
+                            size="sm"
+                            icon={Undo2}
+                            loading={removingId === file.id}
+                            onClick={() => handleUnignore(file.id)}
+                          >
+                            Deixar de ignorar
+                          </Button>
+                        </motion.div>
+                      ))}
+                  </AnimatePresence>
+                </div>
+              </div>
+            ))}
+          </div>
+        )}
+      </Card>
+    </motion.div>
+  );
+}
@@ -5,6 +5,7 @@ import {
   Settings2,
   FolderOpen,
   ShieldAlert,
+  History,
   Lightbulb,
   KeyRound,
   Info,
@@ -12,7 +13,9 @@ import {
 } from 'lucide-react';
 import { GeneralSettings } from './GeneralSettings';
 import { FolderSettings } from './FolderSettings';
+import { IgnoredFilesSettings } from './IgnoredFilesSettings';
 import { ConflictSettings } from './ConflictSettings';
+import { HistorySettings } from './HistorySettings';
 import { TipsSettings } from './TipsSettings';
 import { LicenseSettings } from './LicenseSettings';
 import { AboutSection } from './AboutSection';
@@ -41,7 +44,12 @@ const SETTINGS_TABS: SettingsTab[] = [
     icon: FolderOpen,
     iconColor: 'text-emerald-500 dark:text-emerald-400',
     iconBg: 'bg-emerald-50 dark:bg-emerald-500/10',
-    component: <FolderSettings />,
+    component: (
+      <div className="space-y-6">
+        <FolderSettings />
+        <IgnoredFilesSettings />
+      </div>
+    ),
   },
   {
     id: 'conflicts',
@@ -51,6 +59,14 @@ const SETTINGS_TABS: SettingsTab[] = [
     iconBg: 'bg-red-50 dark:bg-red-500/10',
     component: <ConflictSettings />,
   },
+  {
+    id: 'history',
+    label: 'Histórico',
+    icon: History,
+    iconColor: 'text-indigo-500 dark:text-indigo-400',
+    iconBg: 'bg-indigo-50 dark:bg-indigo-500/10',
+    component: <HistorySettings />,
+  },
   {
     id: 'tips',
     label: 'Dicas',
@@ -85,7 +101,7 @@ const SETTINGS_TABS: SettingsTab[] = [
  * - Left sidebar: vertical tab navigation
  * - Right content: active section with fade transition
  *
- * Sections: Geral, Pastas, Conflitos, Dicas, Licença, Sobre
+ * Sections: Geral, Pastas, Conflitos, Histórico, Dicas, Licença, Sobre
  */
 export function SettingsView() {
   const [activeTab, setActiveTab] = useState(SETTINGS_TABS[0].id);
@@ -1,7 +1,9 @@
 export { SettingsView } from './SettingsView';
 export { GeneralSettings } from './GeneralSettings';
 export { FolderSettings } from './FolderSettings';
+export { IgnoredFilesSettings } from './IgnoredFilesSettings';
 export { ConflictSettings } from './ConflictSettings';
+export { HistorySettings } from './HistorySettings';
 export { TipsSettings } from './TipsSettings';
 export { LicenseSettings } from './LicenseSettings';
 export { AboutSection } from './AboutSection';
@@ -14,11 +14,13 @@ import {
   ChevronUp,
   ArrowUpDown,
   Search,
+  EyeOff,
 } from 'lucide-react';
 import type { LucideIcon } from 'lucide-react';
 import { Badge } from '@/components/ui/Badge';
 import { Input } from '@/components/ui/Input';
 import { Card } from '@/components/ui/Card';
+import { Button } from '@/components/ui/Button';
 import type { SimulationItem } from '@/types/runs';
 import { truncatePath } from '@/lib/formatters';
 
@@ -123,9 +125,19 @@ type SortDirection = 'asc' | 'desc';
 
 interface SimulationPreviewProps {
   items: SimulationItem[];
+  /** Indices (in `items`) left out of the execution. */
+  excluded?: Set<number>;
+  onToggleItem?: (index: number) => void;
+  /** Excludes the file from every future simulation of the folder. */
+  onIgnoreForever?: (item: SimulationItem) => void;
 }
 
-export function SimulationPreview({ items }: SimulationPreviewProps) {
+export function SimulationPreview({
+  items,
+  excluded,
+  onToggleItem,
+  onIgnoreForever,
+}: SimulationPreviewProps) {
   const [searchQuery, setSearchQuery] = useState('');
   const [sortField, setSortField] = useState<SortField>('name');
   const [sortDirection, setSortDirection] = useState<SortDirection>('asc');
@@ -134,6 +146,9 @@ export function SimulationPreview({ items }: SimulationPreviewProps) {
 
   const PAGE_SIZE = 50;
 
+  // Sorting and filtering reorder the list; selection refers to plan order
+  const planIndex = useMemo(() => new Map(items.map((item, i) => [item, i])), [items]);
+
   /* ---------- Filter & Sort ---------- */
 
   const filteredAndSorted = useMemo(() => {
@@ -255,6 +270,7 @@ export function SimulationPreview({ items }: SimulationPreviewProps) {
         {filteredAndSorted.length === items.length
           ? `${items.length} arquivo${items.length !== 1 ? 's' : ''}`
           : `${filteredAndSorted.length} de ${items.length} arquivo${items.length !== 1 ? 's' : ''}`}
+        {excluded && excluded.size > 0 && ` · ${excluded.size} fora desta execução`}
       </p>
 
       {/* Items List */}
@@ -266,6 +282,8 @@ export function SimulationPreview({ items }: SimulationPreviewProps) {
               const isExpanded = expandedRows.has(globalIdx);
               const FileIcon = getFileIcon(item.file.extension);
               const iconColor = getFileIconColor(item.file.extension);
+              const itemIndex = planIndex.get(item) ?? globalIdx;
+              const isExcluded = excluded?.has(itemIndex) ?? false;
 
               return (
                 <motion.div
@@ -280,67 +298,85 @@ export function SimulationPreview({ items }: SimulationPreviewProps) {
                   }}
                 >
                   {/* Main Row */}
-                  <button
-                    type="button"
-                    onClick={() => toggleExpand(globalIdx)}
-                    className={`
-                      w-full text-left px-4 py-3 transition-colors
-                      hover:bg-gray-50 dark:hover:bg-gray-800/50
-                      ${isExpanded ? 'bg-gray-50 dark:bg-gray-800/50' : ''}
-                    `}
-                  >
-                    <div className="flex items-center gap-3 min-w-0">
-                      {/* File Icon */}
-                      <FileIcon size={18} className={`shrink-0 ${iconColor}`} />
-
-                      {/* Original Name + Path */}
-                      <div className="flex-1 min-w-0">
-                        <p className="text-sm font-medium text-gray-900 dark:text-gray-100 truncate">
-                          {item.file.name}
-                        </p>
-                        <p className="text-xs text-gray-400 dark:text-gray-500 truncate">
-                          {truncatePath(item.file.path, 50)}
-                        </p>
-                      </div>
+                  <div className="flex items-center">
+                    {onToggleItem && (
+                      <label className="pl-4 py-3 cursor-pointer" title="Incluir nesta execução">
+                        <input
+                          type="checkbox"
+                          checked={!isExcluded}
+                          onChange={() => onToggleItem(itemIndex)}
+                          className="rounded border-gray-300 dark:border-gray-600 text-brand-600 focus:ring-brand-500"
+                        />
+                      </label>
+                    )}
+                    <button
+                      type="button"
+                      onClick={() => toggleExpand(globalIdx)}
+                      className={`
+                        flex-1 min-w-0 text-left px-4 py-3 transition-colors
+                        hover:bg-gray-50 dark:hover:bg-gray-800/50
+                        ${isExpanded ? 'bg-gray-50 dark:bg-gray-800/50' : ''}
+                        ${isExcluded ? 'opacity-50' : ''}
+                      `}
+                    >
+                      <div className="flex items-center gap-3 min-w-0">
+                        {/* File Icon */}
+                        <FileIcon size={18} className={`shrink-0 ${iconColor}`} />
+
+                        {/* Original Name + Path */}
+                        <div className="flex-1 min-w-0">
+                          <p className="text-sm font-medium text-gray-900 dark:text-gray-100 truncate">
+                            {item.file.name}
+                          </p>
+                          <p className="text-xs text-gray-400 dark:text-gray-500 truncate">
+                            {truncatePath(item.file.path, 50)}
+                          </p>
+                        </div>
 
-                      {/* Arrow */}
-                      <ArrowRight
-                        size={14}
-                        className="shrink-0 text-gray-300 dark:text-gray-600"
-                      />
-
-                      {/* Destination */}
-                      <div className="flex-1 min-w-0 hidden sm:block">
-                        <p className="text-sm text-gray-700 dark:text-gray-300 truncate">
-                          {truncatePath(item.destination, 50)}
-                        </p>
-                      </div>
+                        {/* Arrow */}
+                        <ArrowRight
+                          size={14}
+                          className="shrink-0 text-gray-300 dark:text-gray-600"
+                        />
+
+                        {/* Destination */}
+                        <div className="flex-1 min-w-0 hidden sm:block">
+                          <p className="text-sm text-gray-700 dark:text-gray-300 truncate">
+                            {truncatePath(item.final_path || item.destination, 50)}
+                          </p>
+                          {item.skip_reason && (
+                            <p className="text-xs text-amber-600 dark:text-amber-400 truncate">
+                              Será ignorado
+                            </p>
+                          )}
+                        </div>
 
-                      {/* Rule Badge */}
-                      <Badge variant="info" size="sm" className="shrink-0">
-                        {item.rule_name}
-                      </Badge>
-
-                      {/* Conflict Indicator */}
-                      {item.has_conflict && (
-                        <span className="shrink-0" title="Conflito detectado">
-                          <AlertTriangle
-                            size={16}
-                            className="text-amber-500 dark:text-amber-400"
-                          />
-                        </span>
-                      )}
-
-                      {/* Expand Chevron */}
-                      <ChevronDown
-                        size={14}
-                        className={`
-                          shrink-0 text-gray-400 dark:text-gray-500 transition-transform duration-200
-                          ${isExpanded ? 'rotate-180' : ''}
-                        `}
-                      />
-                    </div>
-                  </button>
+                        {/* Rule Badge */}
+                        <Badge variant="info" size="sm" className="shrink-0">
+                          {item.rule_name}
+                        </Badge>
+
+                        {/* Conflict Indicator */}
+                        {item.has_conflict && (
+                          <span className="shrink-0" title="Conflito detectado">
+                            <AlertTriangle
+                              size={16}
+                              className="text-amber-500 dark:text-amber-400"
+                            />
+                          </span>
+                        )}
+
+                        {/* Expand Chevron */}
+                        <ChevronDown
+                          size={14}
+                          className={`
+                            shrink-0 text-gray-400 dark:text-gray-500 transition-transform duration-200
+                            ${isExpanded ? 'rotate-180' : ''}
+                          `}
+                        />
+                      </div>
+                    </button>
+                  </div>
 
                   {/* Expanded Detail */}
                   <AnimatePresence>
@@ -370,6 +406,16 @@ export function SimulationPreview({ items }: SimulationPreviewProps) {
                                 {item.destination}
                               </p>
                             </div>
+                            {item.final_path && item.final_path !== item.destination && (
+                              <div className="sm:col-span-2">
+                                <span className="font-medium text-gray-500 dark:text-gray-400">
+                                  Caminho final:
+                                </span>
+                                <p className="text-gray-700 dark:text-gray-300 break-all mt-0.5">
+                                  {item.final_path}
+                                </p>
+                              </div>
+                            )}
                             <div>
                               <span className="font-medium text-gray-500 dark:text-gray-400">
                                 Regra:
@@ -394,12 +440,29 @@ export function SimulationPreview({ items }: SimulationPreviewProps) {
                                 <div className="flex items-center gap-1.5 text-amber-600 dark:text-amber-400">
                                   <AlertTriangle size={12} />
                                   <span className="font-medium">
-                                    Conflito: já existe um arquivo no destino
+                                    Conflito: o destino já está ocupado
                                   </span>
                                 </div>
+                                {item.skip_reason && (
+                                  <p className="text-amber-600 dark:text-amber-400 mt-0.5">
+                                    Será ignorado: {item.skip_reason}
+                                  </p>
+                                )}
                               </div>
                             )}
                           </div>
+                          {onIgnoreForever && (
+                            <div className="mt-3 flex justify-end">
+                              <Button
+                                variant="ghost"
+                                size="sm"
+                                icon={EyeOff}
+                                onClick={() => onIgnoreForever(item)}
+                              >
+                                Excluir este arquivo para sempre
+                              </Button>
+                            </div>
+                          )}
                         </div>
                       </motion.div>
                     )}
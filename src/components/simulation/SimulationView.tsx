@@ -22,10 +22,19 @@ import { Badge } from '@/components/ui/Badge';
 import { Progress } from '@/components/ui/Progress';
 import { useToast } from '@/components/ui/Toast';
 import { SimulationPreview } from './SimulationPreview';
+import { ExportReportButton } from '@/components/history/ExportReportButton';
 import { useProfileStore, useAppStore } from '@/stores';
 import { tauriInvoke } from '@/lib/tauri';
 import { VIEWS } from '@/lib/constants';
-import type { SimulationResult, SimulationItem, ExecutionResult } from '@/types/runs';
+import { listen } from '@tauri-apps/api/event';
+import type {
+  SimulationResult,
+  SimulationItem,
+  ExecutionResult,
+  OrganizerProgress,
+  PreflightReport,
+  ItemSelection,
+} from '@/types/runs';
 
 /* ---------- Types ---------- */
 
@@ -117,6 +126,8 @@ export function SimulationView() {
   const [simulationResult, setSimulationResult] =
     useState<SimulationResult | null>(null);
   const [conflictCount, setConflictCount] = useState(0);
+  // Indices of plan items left out of this execution
+  const [excluded, setExcluded] = useState<Set<number>>(new Set());
 
   // Execution state
   const [executionProgress, setExecutionProgress] =
@@ -175,6 +186,7 @@ export function SimulationView() {
       });
 
       setSimulationResult(result);
+      setExcluded(new Set());
       const conflicts = result.items.filter(
         (item: SimulationItem) => item.has_conflict,
       ).length;
@@ -191,10 +203,69 @@ export function SimulationView() {
     }
   }, [folderPath, selectedProfileId, toast]);
 
+  /* --- Include / exclude items --- */
+  const handleToggleItem = useCallback((index: number) => {
+    setExcluded((prev) => {
+      const next = new Set(prev);
+      if (next.has(index)) {
+        next.delete(index);
+      } else {
+        next.add(index);
+      }
+      return next;
+    });
+  }, []);
+
+  const handleIgnoreForever = useCallback(
+    async (item: SimulationItem) => {
+      if (!simulationResult) return;
+      try {
+        await tauriInvoke('ignore_file', { path: item.file.path, folder: folderPath.trim() });
+        setExcluded((prev) => {
+          const next = new Set(prev);
+          simulationResult.items.forEach((other, index) => {
+            if (other.file.path === item.file.path) next.add(index);
+          });
+          return next;
+        });
+        toast.success(`${item.file.name} não aparecerá mais nas simulações desta pasta.`);
+      } catch (err) {
+        const message = err instanceof Error ? err.message : String(err);
+        toast.error(`Erro ao ignorar arquivo: ${message}`);
+      }
+    },
+    [simulationResult, folderPath, toast],
+  );
+
   /* --- Execute organization --- */
   const handleExecute = useCallback(async () => {
     if (!simulationResult || !selectedProfileId) return;
 
+    const selection: ItemSelection = { exclude_indices: [...excluded] };
+
+    try {
+      const report = await tauriInvoke<PreflightReport>('preflight_simulation', {
+        simulationId: simulationResult.simulation_id,
+        selection,
+      });
+      if (!report.go) {
+        const shown = report.blockers
+          .slice(0, 3)
+          .map((b) => `${b.path}: ${b.message}`)
+          .join('\n');
+        const more = report.blockers.length > 3 ? `\n+${report.blockers.length - 3} outros` : '';
+        toast.error(`A organização não pode ser executada:\n${shown}${more}`);
+        return;
+      }
+      if (report.warnings.length > 0) {
+        toast.info(report.warnings.map((w) => `${w.path}: ${w.message}`).join('\n'));
+      }
+    } catch (err) {
+      const message = err instanceof Error ? err.message : String(err);
+      toast.error(`Erro na verificação prévia: ${message}`);
+      return;
+    }
+
     setState('executing');
     setIsExecuting(true);
     setExecutionResult(null);
@@ -207,31 +278,57 @@ export function SimulationView() {
       percentage: 0,
     });
 
-    try {
-      const result = await tauriInvoke<ExecutionResult>('execute_simulation', {
-        simulationJson: JSON.stringify(simulationResult),
+    const unlisten = await listen<OrganizerProgress>('organizer-progress', (event) => {
+      const p = event.payload;
+      if (p.phase !== 'execute') return;
+      setExecutionProgress({
+        current: p.done,
+        total: p.total,
+        currentFile: p.current_file,
+        percentage: p.bytes_total > 0 ? Math.round((p.bytes_done / p.bytes_total) * 100) : 100,
       });
+    });
 
-      // Simulate incremental progress for UX
-      setExecutionProgress({
-        current: total,
-        total,
-        currentFile: '',
-        percentage: 100,
+    try {
+      const result = await tauriInvoke<ExecutionResult>('execute_simulation', {
+        simulationId: simulationResult.simulation_id,
+        selection,
       });
 
       setExecutionResult(result);
-      toast.success(
-        `Organização concluída! ${result.moved} arquivo${result.moved !== 1 ? 's' : ''} movido${result.moved !== 1 ? 's' : ''}.`,
-      );
+      if (result.drifted.length > 0) {
+        toast.info(
+          `${result.drifted.length} arquivo${result.drifted.length !== 1 ? 's' : ''} mudou desde a simulação e foi ignorado.`,
+        );
+      }
+      if (result.cancelled) {
+        toast.info(
+          `Organização cancelada após ${result.total} arquivo${result.total !== 1 ? 's' : ''}. Ela pode ser revertida no histórico.`,
+        );
+      } else {
+        toast.success(
+          `Organização concluída! ${result.moved} arquivo${result.moved !== 1 ? 's' : ''} movido${result.moved !== 1 ? 's' : ''}.`,
+        );
+      }
     } catch (err) {
       const message = err instanceof Error ? err.message : String(err);
       toast.error(`Erro na execução: ${message}`);
       setState('results');
     } finally {
+      unlisten();
       setIsExecuting(false);
     }
-  }, [simulationResult, selectedProfileId, folderPath, toast]);
+  }, [simulationResult, selectedProfileId, folderPath, excluded, toast]);
+
+  /* --- Stop a running execution --- */
+  const handleStopExecution = useCallback(async () => {
+    try {
+      await tauriInvoke('cancel_execution');
+    } catch (err) {
+      const message = err instanceof Error ? err.message : String(err);
+      toast.error(`Erro ao cancelar: ${message}`);
+    }
+  }, [toast]);
 
   /* --- Cancel / Reset --- */
   const handleCancel = useCallback(() => {
@@ -239,6 +336,7 @@ export function SimulationView() {
     setSimulationResult(null);
     setExecutionResult(null);
     setConflictCount(0);
+    setExcluded(new Set());
     setExecutionProgress({
       current: 0,
       total: 0,
@@ -423,7 +521,12 @@ export function SimulationView() {
 
             {/* Simulation Preview Table */}
             {simulationResult.items.length > 0 ? (
-              <SimulationPreview items={simulationResult.items} />
+              <SimulationPreview
+                items={simulationResult.items}
+                excluded={excluded}
+                onToggleItem={handleToggleItem}
+                onIgnoreForever={handleIgnoreForever}
+              />
             ) : (
               <Card padding="lg">
                 <div className="text-center py-8">
@@ -445,7 +548,10 @@ export function SimulationView() {
                 size="lg"
                 icon={Play}
                 onClick={handleExecute}
-                disabled={simulationResult.matched_files === 0}
+                disabled={
+                  simulationResult.matched_files === 0 ||
+                  excluded.size === simulationResult.items.length
+                }
                 className="sm:flex-none"
               >
                 Executar Organização
@@ -458,6 +564,14 @@ export function SimulationView() {
               >
                 Cancelar
               </Button>
+              {simulationResult.simulation_id && (
+                <div className="sm:ml-auto">
+                  <ExportReportButton
+                    command="export_simulation"
+                    args={{ simulationId: simulationResult.simulation_id }}
+                  />
+                </div>
+              )}
             </div>
           </motion.div>
         )}
@@ -528,8 +642,7 @@ export function SimulationView() {
                       variant="ghost"
                       size="sm"
                       icon={X}
-                      onClick={handleCancel}
-                      disabled={isExecuting}
+                      onClick={isExecuting ? handleStopExecution : handleCancel}
                     >
                       Cancelar
                     </Button>
@@ -1,5 +1,11 @@
 import type { AppSettings } from '@/types/settings';
-import type { ConditionField, ConditionOperator, ActionType } from '@/types/rules';
+import type {
+  ConditionField,
+  ConditionOperator,
+  ActionType,
+  ActionConflictStrategy,
+} from '@/types/rules';
+import type { ErrorCode, ReportFormat } from '@/types/runs';
 
 export const APP_NAME = 'DeskCraft';
 
@@ -48,13 +54,60 @@ export const ACTION_TYPES: { value: ActionType; label: string }[] = [
   { value: 'move_to_folder', label: 'Mover para pasta' },
   { value: 'move_to_subfolder', label: 'Mover para subpasta' },
   { value: 'rename', label: 'Renomear' },
+  { value: 'extract', label: 'Extrair compactado' },
+  { value: 'archive', label: 'Compactar em arquivo' },
+  { value: 'move_and_link', label: 'Mover e deixar atalho' },
+  { value: 'link', label: 'Criar atalho' },
   { value: 'add_tag', label: 'Adicionar tag' },
 ];
 
+export const ACTION_CONFLICT_STRATEGIES: { value: ActionConflictStrategy; label: string }[] = [
+  { value: '', label: 'Padrão das configurações' },
+  { value: 'suffix', label: 'Sufixo numérico' },
+  { value: 'conflict_folder', label: 'Pasta Conflitos' },
+  { value: 'skip', label: 'Ignorar' },
+  { value: 'overwrite_if_newer', label: 'Substituir se for mais recente' },
+  { value: 'overwrite_if_larger', label: 'Substituir se for maior' },
+  { value: 'keep_both_by_date', label: 'Manter ambos (data no nome)' },
+  { value: 'skip_if_identical', label: 'Ignorar se for idêntico' },
+];
+
+export const REPORT_FORMATS: { value: ReportFormat; label: string }[] = [
+  { value: 'csv', label: 'CSV' },
+  { value: 'json', label: 'JSON' },
+  { value: 'markdown', label: 'Markdown' },
+  { value: 'html', label: 'HTML' },
+];
+
+export const ERROR_CODE_LABELS: Record<ErrorCode, string> = {
+  permission_denied: 'Sem permissão',
+  not_found: 'Arquivo não encontrado',
+  disk_full: 'Disco cheio',
+  cross_device: 'Outro disco',
+  locked: 'Arquivo em uso',
+  path_too_long: 'Caminho longo demais',
+  unsafe_destination: 'Destino inseguro',
+  other: 'Outro erro',
+};
+
+export const ROLLBACK_CONFLICT_STRATEGIES: {
+  value: AppSettings['rollback_conflict_strategy'];
+  label: string;
+}[] = [
+  { value: 'suffix', label: 'Restaurar com sufixo numérico' },
+  { value: 'restored_folder', label: 'Restaurar na pasta Restored' },
+  { value: 'skip', label: 'Deixar o arquivo onde está' },
+];
+
 export const DEFAULT_SETTINGS: AppSettings = {
   theme: 'system',
   language: 'pt-BR',
   conflict_strategy: 'suffix',
+  verify_moves: false,
+  rollback_conflict_strategy: 'suffix',
+  history_retention_days: 0,
+  history_retention_runs: 0,
+  history_keep_undoable_days: 30,
   start_minimized: false,
   start_with_os: false,
   log_level: 'info',
@@ -1,68 +1,190 @@
 import { create } from 'zustand';
 import { tauriInvoke } from '@/lib/tauri';
-import type { Run, RunItem } from '@/types/runs';
+import type {
+  ChainMode,
+  ExecutionResult,
+  RollbackResult,
+  Run,
+  RunFilter,
+  RunItem,
+  RunStats,
+} from '@/types/runs';
 
 interface HistoryState {
   runs: Run[];
   selectedRun: Run | null;
   runItems: RunItem[];
+  /** Runs that retried the failed items of `selectedRun`. */
+  retries: Run[];
+  stats: RunStats | null;
   isLoading: boolean;
   error: string | null;
 
   fetchRuns: (limit?: number, offset?: number) => Promise<void>;
+  searchRuns: (filter: RunFilter) => Promise<void>;
+  searchRunItems: (query: string, limit?: number) => Promise<RunItem[]>;
+  fetchStats: (filter?: RunFilter) => Promise<void>;
   fetchRunDetails: (id: string) => Promise<void>;
-  rollbackRun: (id: string) => Promise<void>;
+  rollbackRun: (id: string, chainMode?: ChainMode) => Promise<RollbackResult | null>;
+  rollbackItems: (
+    runId: string,
+    itemIds: string[],
+    chainMode?: ChainMode
+  ) => Promise<RollbackResult | null>;
+  rollbackRule: (
+    runId: string,
+    ruleId: string,
+    chainMode?: ChainMode
+  ) => Promise<RollbackResult | null>;
+  retryErrors: (runId: string) => Promise<ExecutionResult | null>;
+  /** Executes the items an interrupted run had not started yet. */
+  resumeRun: (runId: string) => Promise<ExecutionResult | null>;
   clearSelected: () => void;
 }
 
-export const useHistoryStore = create<HistoryState>()((set) => ({
-  runs: [],
-  selectedRun: null,
-  runItems: [],
-  isLoading: false,
-  error: null,
-
-  fetchRuns: async (limit = 50, offset = 0) => {
-    set({ isLoading: true, error: null });
-    try {
-      const runs = await tauriInvoke<Run[]>('list_runs', { limit, offset });
-      set({ runs, isLoading: false });
-    } catch (err) {
-      const message = err instanceof Error ? err.message : String(err);
-      set({ error: message, isLoading: false });
-    }
-  },
-
-  fetchRunDetails: async (id) => {
-    set({ isLoading: true, error: null });
-    try {
-      const [run, runItems] = await Promise.all([
-        tauriInvoke<Run>('get_run', { id }),
-        tauriInvoke<RunItem[]>('list_run_items', { runId: id }),
-      ]);
-      set({ selectedRun: run, runItems, isLoading: false });
-    } catch (err) {
-      const message = err instanceof Error ? err.message : String(err);
-      set({ error: message, isLoading: false });
-    }
-  },
-
-  rollbackRun: async (id) => {
+export const useHistoryStore = create<HistoryState>()((set, get) => {
+  /** Runs a rollback command, then reloads the run and its items. */
+  const rollback = async (
+    command: string,
+    runId: string,
+    args: Record<string, unknown> = {}
+  ): Promise<RollbackResult | null> => {
     set({ isLoading: true, error: null });
     try {
-      await tauriInvoke('rollback_run', { runId: id });
-      // Re-fetch the run to get the updated status
-      const run = await tauriInvoke<Run>('get_run', { id });
+      const result = await tauriInvoke<RollbackResult>(command, { runId, ...args });
+      // Re-fetch the run, and any later run rolled back with it, to get the updated status
+      const changed = await Promise.all(
+        [runId, ...result.dependent_runs].map((id) => tauriInvoke<Run>('get_run', { id }))
+      );
+      const byId = new Map(changed.map((r) => [r.id, r]));
+      const run = changed[0];
+      const runItems =
+        get().selectedRun?.id === runId
+          ? await tauriInvoke<RunItem[]>('list_run_items', { runId })
+          : get().runItems;
       set((state) => ({
-        runs: state.runs.map((r) => (r.id === id ? run : r)),
-        selectedRun: state.selectedRun?.id === id ? run : state.selectedRun,
+        runs: state.runs.map((r) => byId.get(r.id) ?? r),
+        selectedRun: state.selectedRun?.id === runId ? run : state.selectedRun,
+        runItems,
         isLoading: false,
       }));
+      return result;
     } catch (err) {
       const message = err instanceof Error ? err.message : String(err);
       set({ error: message, isLoading: false });
+      return null;
     }
-  },
+  };
+
+  return {
+    runs: [],
+    selectedRun: null,
+    runItems: [],
+    retries: [],
+    stats: null,
+    isLoading: false,
+    error: null,
+
+    fetchRuns: async (limit = 50, offset = 0) => {
+      set({ isLoading: true, error: null });
+      try {
+        const runs = await tauriInvoke<Run[]>('list_runs', { limit, offset });
+        set({ runs, isLoading: false });
+      } catch (err) {
+        const message = err instanceof Error ? err.message : String(err);
+        set({ error: message, isLoading: false });
+      }
+    },
+
+    searchRuns: async (filter) => {
+      set({ isLoading: true, error: null });
+      try {
+        const runs = await tauriInvoke<Run[]>('search_runs', { filter });
+        set({ runs, isLoading: false });
+      } catch (err) {
+        const message = err instanceof Error ? err.message : String(err);
+        set({ error: message, isLoading: false });
+      }
+    },
+
+    searchRunItems: async (query, limit) => {
+      try {
+        return await tauriInvoke<RunItem[]>('search_run_items', { query, limit });
+      } catch (err) {
+        const message = err instanceof Error ? err.message : String(err);
+        set({ error: message });
+        return [];
+      }
+    },
+
+    fetchStats: async (filter) => {
+      try {
+        const stats = await tauriInvoke<RunStats>('get_run_stats', { filter });
+        set({ stats });
+      } catch (err) {
+        const message = err instanceof Error ? err.message : String(err);
+        set({ error: message });
+      }
+    },
+
+    fetchRunDetails: async (id) => {
+      set({ isLoading: true, error: null });
+      try {
+        const [run, runItems, retries] = await Promise.all([
+          tauriInvoke<Run>('get_run', { id }),
+          tauriInvoke<RunItem[]>('list_run_items', { runId: id }),
+          tauriInvoke<Run[]>('search_runs', { filter: { parent_run_id: id } }),
+        ]);
+        set({ selectedRun: run, runItems, retries, isLoading: false });
+      } catch (err) {
+        const message = err instanceof Error ? err.message : String(err);
+        set({ error: message, isLoading: false });
+      }
+    },
+
+    rollbackRun: (id, chainMode) => rollback('rollback_run', id, { chainMode }),
+
+    rollbackItems: (runId, itemIds, chainMode) =>
+      rollback('rollback_run_items', runId, { itemIds, chainMode }),
+
+    rollbackRule: (runId, ruleId, chainMode) =>
+      rollback('rollback_run_rule', runId, { ruleId, chainMode }),
+
+    retryErrors: async (runId) => {
+      set({ isLoading: true, error: null });
+      try {
+        const result = await tauriInvoke<ExecutionResult>('retry_run_errors', { runId });
+        // The original run now lists the new retry
+        await get().fetchRunDetails(runId);
+        return result;
+      } catch (err) {
+        const message = err instanceof Error ? err.message : String(err);
+        set({ error: message, isLoading: false });
+        return null;
+      }
+    },
+
+    resumeRun: async (runId) => {
+      set({ isLoading: true, error: null });
+      try {
+        const result = await tauriInvoke<ExecutionResult>('resume_run', { runId });
+        // The run goes on under the same ID, so refresh it wherever it is shown
+        const run = await tauriInvoke<Run>('get_run', { id: runId });
+        set((s) => ({
+          runs: s.runs.map((r) => (r.id === runId ? run : r)),
+          isLoading: false,
+        }));
+        if (get().selectedRun?.id === runId) {
+          await get().fetchRunDetails(runId);
+        }
+        return result;
+      } catch (err) {
+        const message = err instanceof Error ? err.message : String(err);
+        set({ error: message, isLoading: false });
+        return null;
+      }
+    },
 
-  clearSelected: () => set({ selectedRun: null, runItems: [] }),
-}));
+    clearSelected: () => set({ selectedRun: null, runItems: [], retries: [] }),
+  };
+});
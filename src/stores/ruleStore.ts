@@ -35,6 +35,7 @@ interface RuleState {
     destination: string | null,
     renamePattern: string | null,
     tagName: string | null,
+    conflictStrategy?: string,
   ) => Promise<void>;
   deleteAction: (id: string) => Promise<void>;
   clearSelected: () => void;
@@ -168,7 +169,14 @@ export const useRuleStore = create<RuleState>()((set, get) => ({
     }
   },
 
-  addAction: async (ruleId, actionType, destination, renamePattern, tagName) => {
+  addAction: async (
+    ruleId,
+    actionType,
+    destination,
+    renamePattern,
+    tagName,
+    conflictStrategy = '',
+  ) => {
     set({ error: null });
     try {
       const action = await tauriInvoke<RuleAction>('add_rule_action', {
@@ -177,6 +185,7 @@ export const useRuleStore = create<RuleState>()((set, get) => ({
         destination,
         renamePattern,
         tagName,
+        conflictStrategy,
       });
       const { selectedRule } = get();
       if (selectedRule && selectedRule.id === ruleId) {
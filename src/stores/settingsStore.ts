@@ -28,12 +28,36 @@ function parseSettings(raw: Setting[]): AppSettings {
     return val === 'true' || val === '1';
   };
 
+  const parseNumber = (val: string | undefined, fallback: number): number => {
+    const parsed = Number(val);
+    return val === undefined || Number.isNaN(parsed) ? fallback : parsed;
+  };
+
   return {
     theme: (map.get('theme') as AppSettings['theme']) ?? DEFAULT_SETTINGS.theme,
     language: map.get('language') ?? DEFAULT_SETTINGS.language,
     conflict_strategy:
       (map.get('conflict_strategy') as AppSettings['conflict_strategy']) ??
       DEFAULT_SETTINGS.conflict_strategy,
+    verify_moves: parseBool(
+      map.get('verify_moves'),
+      DEFAULT_SETTINGS.verify_moves,
+    ),
+    rollback_conflict_strategy:
+      (map.get('rollback_conflict_strategy') as AppSettings['rollback_conflict_strategy']) ??
+      DEFAULT_SETTINGS.rollback_conflict_strategy,
+    history_retention_days: parseNumber(
+      map.get('history_retention_days'),
+      DEFAULT_SETTINGS.history_retention_days,
+    ),
+    history_retention_runs: parseNumber(
+      map.get('history_retention_runs'),
+      DEFAULT_SETTINGS.history_retention_runs,
+    ),
+    history_keep_undoable_days: parseNumber(
+      map.get('history_keep_undoable_days'),
+      DEFAULT_SETTINGS.history_keep_undoable_days,
+    ),
     start_minimized: parseBool(
       map.get('start_minimized'),
       DEFAULT_SETTINGS.start_minimized,
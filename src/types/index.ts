@@ -6,6 +6,7 @@ export type {
   RuleAction,
   ActionType,
   RuleWithDetails,
+  RuleTestResult,
 } from './rules';
 
 export type { Profile } from './profiles';
@@ -17,6 +18,28 @@ export type {
   SimulationResult,
   SimulationItem,
   ExecutionResult,
+  DriftedItem,
+  OrganizerProgress,
+  PreflightReport,
+  PreflightIssue,
+  VolumeUsage,
+  ReportFormat,
+  ItemSelection,
+  IgnoredFile,
+  RollbackResolution,
+  RollbackResult,
+  RollbackItemReport,
+  RollbackDependencies,
+  RunFilter,
+  FileLocation,
+  RunStats,
+  StatBucket,
+  RollbackPreview,
+  RollbackPreviewItem,
+  FileChain,
+  ChainStep,
+  DependentRun,
+  ChainMode,
 } from './runs';
 
 export type { Setting, AppSettings } from './settings';
@@ -1,3 +1,5 @@
+import type { FileEntry, SimulationItem } from './runs';
+
 export interface Rule {
   id: string;
   name: string;
@@ -51,15 +53,40 @@ export interface RuleAction {
   tag_name: string | null;
   sort_order: number;
   created_at: string;
+  post_action: 'keep' | 'delete' | 'move';
+  post_destination: string;
+  /** Empty string means the global conflict strategy applies. */
+  conflict_strategy: ActionConflictStrategy;
 }
 
+export type ActionConflictStrategy =
+  | ''
+  | 'suffix'
+  | 'conflict_folder'
+  | 'skip'
+  | 'overwrite_if_newer'
+  | 'overwrite_if_larger'
+  | 'keep_both_by_date'
+  | 'skip_if_identical';
+
 export type ActionType =
   | 'move_to_folder'
   | 'move_to_subfolder'
   | 'rename'
+  | 'extract'
+  | 'archive'
+  | 'move_and_link'
+  | 'link'
   | 'add_tag';
 
 export interface RuleWithDetails extends Rule {
   conditions: RuleCondition[];
   actions: RuleAction[];
 }
+
+export interface RuleTestResult {
+  file: FileEntry;
+  matched: boolean;
+  captures: Record<string, string>;
+  items: SimulationItem[];
+}
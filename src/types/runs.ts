@@ -1,10 +1,28 @@
 export interface Run {
   id: string;
   profile_id: string | null;
-  run_type: 'manual' | 'simulation' | 'watcher' | 'scheduled';
-  status: 'pending' | 'running' | 'completed' | 'failed' | 'rolled_back';
+  run_type: 'manual' | 'simulation' | 'watcher' | 'scheduled' | 'retry';
+  status:
+    | 'pending'
+    | 'running'
+    | 'completed'
+    | 'failed'
+    | 'rolled_back'
+    | 'rollback_partial'
+    | 'interrupted'
+    | 'cancelled'
+    /** A run that found nothing to do, e.g. a scheduled run on a tidy folder. */
+    | 'idle';
   source_folder: string;
+  /** Files seen in the source folder when the run was planned. */
+  scanned_files: number;
+  /** Scanned files that matched a rule, and those that matched none. */
+  matched_files: number;
+  unmatched_files: number;
+  /** Planned items, including skipped ones. */
   total_files: number;
+  /** Size of the files the run acted on. */
+  total_bytes: number;
   moved_files: number;
   skipped_files: number;
   error_files: number;
@@ -12,8 +30,87 @@ export interface Run {
   completed_at: string | null;
   rolled_back_at: string | null;
   error_message: string | null;
+  /** Time spent executing, across resumes; null for older runs. */
+  duration_ms: number | null;
+  /** When retention removed the run's items; pruned runs can't be undone. */
+  pruned_at: string | null;
+  /** For `retry` runs, the run whose failed items they retried. */
+  parent_run_id: string | null;
+}
+
+/** Where a file DeskCraft moved is now. */
+export interface FileLocation {
+  /** The item that first moved the file. */
+  run_item_id: string;
+  /** The run that last moved it. */
+  run_id: string;
+  original_path: string;
+  /** Every path the file had, oldest first. */
+  path_history: string[];
+  current_path: string | null;
+  /** `renamed`: found by content under another name next to where it was left. */
+  status: 'found' | 'renamed' | 'restored' | 'deleted' | 'missing';
+  rule_id: string | null;
+  rule_name: string | null;
+  moved_at: string | null;
 }
 
+/** Narrows the run history; unset fields don't filter. Dates are `YYYY-MM-DD`. */
+export interface RunFilter {
+  profile_id?: string;
+  run_type?: Run['run_type'];
+  status?: Run['status'];
+  from?: string;
+  to?: string;
+  /** Runs of this folder or of any folder inside it. */
+  source_folder?: string;
+  /** Retries of this run. */
+  parent_run_id?: string;
+  limit?: number;
+  offset?: number;
+}
+
+/** Files and bytes organized, counting only items still in place. */
+export interface RunStats {
+  files: number;
+  bytes: number;
+  by_day: StatBucket[];
+  by_rule: StatBucket[];
+  by_destination: StatBucket[];
+}
+
+export interface StatBucket {
+  name: string;
+  files: number;
+  bytes: number;
+}
+
+/** What a retention pass removed from the history. */
+export interface PruneResult {
+  runs_pruned: number;
+  items_removed: number;
+}
+
+/** Size of the database file and of the history kept in it. */
+export interface DatabaseSize {
+  total_bytes: number;
+  /** Space held by deleted rows, given back to the disk by compacting. */
+  free_bytes: number;
+  runs: number;
+  pruned_runs: number;
+  run_items: number;
+}
+
+export type ErrorCode =
+  | 'permission_denied'
+  | 'not_found'
+  | 'disk_full'
+  | 'cross_device'
+  | 'locked'
+  | 'path_too_long'
+  | 'unsafe_destination'
+  | 'other';
+
 export interface RunItem {
   id: string;
   run_id: string;
@@ -22,11 +119,112 @@ export interface RunItem {
   destination_path: string;
   file_size: number;
   action_type: 'move' | 'rename' | 'move_rename';
-  status: 'pending' | 'completed' | 'failed' | 'rolled_back' | 'skipped';
+  status: 'pending' | 'completed' | 'failed' | 'rolled_back' | 'rollback_skipped' | 'skipped';
   conflict_strategy: string | null;
   error_message: string | null;
+  /** Category of the last failure, on execution or rollback. */
+  error_code: ErrorCode | null;
+  failed_at: string | null;
   executed_at: string | null;
   rolled_back_at: string | null;
+  archive_entry: string | null;
+  checksum: string | null;
+  replaced_path: string | null;
+  dest_mtime: string | null;
+  /** The destination changed after the run; set by rollback. */
+  dest_modified: boolean;
+  rollback_resolution: RollbackResolution | null;
+  /** Where rollback put the file back, when not at `original_path`. */
+  restored_path: string | null;
+}
+
+/** `chained`: a later move undone by restoring the file straight from where it ended up. */
+export type RollbackResolution = 'restored' | 'suffix' | 'restored_folder' | 'skipped' | 'chained';
+
+/** How rollback handles files a later run moved again. */
+export type ChainMode = 'cascade' | 'latest';
+
+export interface RollbackResult {
+  run_id: string;
+  rolled_back: number;
+  skipped: number;
+  errors: number;
+  /** Items that could not simply go back where they came from. */
+  items: RollbackItemReport[];
+  /** Later runs whose moves were rolled back first, latest first. */
+  dependent_runs: string[];
+}
+
+export interface RollbackItemReport {
+  run_item_id: string;
+  original_path: string;
+  resolution: RollbackResolution;
+  restored_path: string | null;
+  destination_modified: boolean;
+}
+
+/** What rolling back a run would do, worked out without moving anything. */
+export interface RollbackPreview {
+  run_id: string;
+  items: RollbackPreviewItem[];
+  restored: number;
+  relocated: number;
+  kept: number;
+  missing: number;
+  irreversible: number;
+  modified: number;
+}
+
+export interface RollbackPreviewItem {
+  run_item_id: string;
+  action_type: string;
+  original_path: string;
+  /** Where the file is now, following later runs that moved it. */
+  current_path: string;
+  destination_exists: boolean;
+  original_free: boolean;
+  content_changed: boolean;
+  /** Later runs that moved the file again, oldest first. */
+  later_runs: string[];
+  outcome: 'restore' | 'relocate' | 'keep' | 'remove' | 'missing' | 'irreversible';
+  /** Where the file would go, for `restore` and `relocate`. */
+  target_path: string | null;
+}
+
+/** Later runs that moved files of a run again. */
+export interface RollbackDependencies {
+  run_id: string;
+  chains: FileChain[];
+  /** Latest first. */
+  dependent_runs: DependentRun[];
+}
+
+export interface FileChain {
+  run_item_id: string;
+  original_path: string;
+  destination_path: string;
+  /** Oldest first. */
+  steps: ChainStep[];
+  /** Null when a later run deleted the file. */
+  latest_path: string | null;
+  /** Whether the file can be restored straight from `latest_path`. */
+  direct: boolean;
+}
+
+export interface ChainStep {
+  run_id: string;
+  run_item_id: string;
+  action_type: string;
+  from: string;
+  to: string;
+  executed_at: string | null;
+}
+
+export interface DependentRun {
+  run_id: string;
+  started_at: string;
+  source_folder: string;
+  items: number;
 }
 
 export interface FileEntry {
@@ -43,6 +241,7 @@ export interface SimulationResult {
   total_files: number;
   matched_files: number;
   unmatched_files: number;
+  simulation_id: string | null;
 }
 
 export interface SimulationItem {
@@ -52,6 +251,13 @@ export interface SimulationItem {
   action_type: string;
   destination: string;
   has_conflict: boolean;
+  error: string | null;
+  post_action: string;
+  post_destination: string;
+  conflict_strategy: string;
+  /** Where the file will end up once conflicts are resolved; empty if it won't be placed. */
+  final_path: string;
+  skip_reason: string | null;
 }
 
 export interface ExecutionResult {
@@ -60,4 +266,66 @@ export interface ExecutionResult {
   moved: number;
   skipped: number;
   errors: number;
+  error_messages: string[];
+  drifted: DriftedItem[];
+  cancelled: boolean;
+}
+
+export interface DriftedItem {
+  path: string;
+  reason: string;
+}
+
+/** Which items of a stored simulation to execute; empty include lists mean all. */
+export interface ItemSelection {
+  include_indices?: number[];
+  include_paths?: string[];
+  exclude_indices?: number[];
+  exclude_paths?: string[];
+}
+
+/** A file left out of every future simulation of its folder. */
+export interface IgnoredFile {
+  id: string;
+  folder: string;
+  path: string;
+  created_at: string;
+}
+
+export type ReportFormat = 'csv' | 'json' | 'markdown' | 'html';
+
+export interface PreflightReport {
+  /** True when nothing blocks the execution. */
+  go: boolean;
+  blockers: PreflightIssue[];
+  warnings: PreflightIssue[];
+  volumes: VolumeUsage[];
+}
+
+export interface PreflightIssue {
+  kind:
+    | 'insufficient_space'
+    | 'low_space'
+    | 'destination_not_writable'
+    | 'source_not_readable'
+    | 'source_not_deletable'
+    | 'file_locked';
+  path: string;
+  message: string;
+}
+
+export interface VolumeUsage {
+  path: string;
+  required_bytes: number;
+  available_bytes: number;
+}
+
+export interface OrganizerProgress {
+  run_id: string;
+  phase: 'execute' | 'rollback';
+  done: number;
+  total: number;
+  bytes_done: number;
+  bytes_total: number;
+  current_file: string;
 }
@@ -8,6 +8,14 @@ export interface AppSettings {
   theme: 'system' | 'light' | 'dark';
   language: string;
   conflict_strategy: 'suffix' | 'conflict_folder' | 'ask';
+  verify_moves: boolean;
+  rollback_conflict_strategy: 'suffix' | 'skip' | 'restored_folder';
+  /** Days of run history to keep; 0 keeps everything. */
+  history_retention_days: number;
+  /** Most recent runs to keep; 0 keeps everything. */
+  history_retention_runs: number;
+  /** Runs that can still be undone are kept at least this many days. */
+  history_keep_undoable_days: number;
   start_minimized: boolean;
   start_with_os: boolean;
   log_level: string;